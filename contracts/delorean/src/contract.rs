@@ -1,33 +1,102 @@
-use crate::errors::{ContractError, Unauthorized};
+use crate::errors::ContractError;
+use crate::migrations::{migrate_store, set_version, CONTRACT_NAME, CONTRACT_VERSION};
 use crate::msg::{
-    ConfigResponse, Cw721ExecuteMsg, DegenInfoResponse, ExecuteMsg, FundDepositMsg, InstantiateMsg,
-    MigrateMsg, QueryMsg, RoundInfoResponse, SecureMintMsg, WinnersResponse,
+    ConfigResponse, ConfigUpdate, Cw721ExecuteMsg, Cw721ReceiveMsg, DegenInfoResponse, ExecuteMsg,
+    FundDepositMsg, InstantiateMsg, MigrateMsg, PrizePoolResponse, QueryMsg, ReservesResponse,
+    RoundInfoResponse, RoundPrizeResponse, SecureMintMsg, ShareDeposit, ShareOwed,
+    SharesOwedResponse, SimulateDistributeRevenueResponse, StakedTokenInfo, StakedTokensResponse,
+    StateResponse, TicketOwnerResponse, TicketsResponse, WalletStatusResponse,
+    WalletTicketsResponse, WinnersResponse,
+};
+use crate::ownership::{
+    assert_role, get_ownership, get_role_members, init_ownership, try_accept_admin, try_grant_role,
+    try_propose_new_admin, try_renounce_admin, try_revoke_role, Role,
 };
 use crate::state::{
-    Config, Metadata, RoundInfo, WinnerInfo, CONFIG, DEGEN_INFO, NFT2_FUNDS, ROUND_INFO,
+    Config, DrawSeed, Metadata, RoundInfo, RoundState, StakedToken, TicketEntry, TicketSource,
+    WinnerInfo, CONFIG, DEGEN_INFO, DRAW_SEEDS, LEGACY_DEGEN_INFO, MIN_UNSTAKE_COOLDOWN, NFT_FUNDS,
+    PENDING_MINT, PRIZE_POOL, ROUND_DRAWS, ROUND_ID, ROUND_INFO, ROUND_PRIZES, ROUND_STATE,
+    ROUND_TICKETS, SHARES_OWED, STAKED_BY_OWNER, STAKED_TICKETS, STAKED_TOKENS, STAKE_ENTRIES,
+    TICKET_ENTRIES, TOKEN_TIERS, TOTAL_SHARES_OWED, TOTAL_STAKED, UNSTAKED_ROUND, WALLET_TICKETS,
     WINNER_INFO,
 };
 use cosmwasm_std::{
-    coin, entry_point, to_binary, BankMsg, Coin, Deps, DepsMut, Env, MessageInfo, Order,
-    QueryResponse, Response, StdResult, SubMsg, Uint128, WasmMsg,
+    attr, coin, entry_point, to_binary, Attribute, BankMsg, Binary, Coin, Deps, DepsMut, Env,
+    Event, MessageInfo, Order, QueryResponse, Reply, Response, StdError, StdResult, Storage,
+    SubMsg, Uint128, WasmMsg,
 };
+use cw_storage_plus::Bound;
+use delorean_common::errors::invalid_config;
+use delorean_common::events::{instantiate_event, json_attribute};
+use delorean_common::payout::payout;
 use moneymarket::querier::deduct_tax;
+use sha2::{Digest, Sha256};
 
 pub fn instantiate(
-    deps: DepsMut,
+    mut deps: DepsMut,
     _env: Env,
-    _info: MessageInfo,
+    info: MessageInfo,
     msg: InstantiateMsg,
 ) -> Result<Response, ContractError> {
+    let admin = msg.admin.clone().unwrap_or_else(|| info.sender.to_string());
+    let event = instantiate_event(
+        CONTRACT_NAME,
+        CONTRACT_VERSION,
+        &admin,
+        &msg.roles,
+        &msg.config,
+    )?;
+    init_ownership(deps.branch(), admin, msg.roles)?;
+    validate_config(deps.as_ref(), &msg.config)?;
     CONFIG.save(deps.storage, &msg.config)?;
-    let t = Uint128::from(0u32);
-    NFT2_FUNDS.save(deps.storage, &t)?;
-    Ok(Response::default())
+    set_version(deps.storage)?;
+    Ok(Response::default().add_event(event))
+}
+
+fn current_round_id(storage: &dyn Storage) -> StdResult<u64> {
+    Ok(ROUND_ID.may_load(storage)?.unwrap_or_default())
 }
 
 #[cfg_attr(not(feature = "library"), entry_point)]
-pub fn migrate(_deps: DepsMut, _env: Env, _msg: MigrateMsg) -> Result<Response, ContractError> {
-    Ok(Response::default())
+pub fn migrate(deps: DepsMut, _env: Env, msg: MigrateMsg) -> Result<Response, ContractError> {
+    migrate_store(deps, msg)
+}
+
+const MINT_REPLY_ID: u64 = 1;
+
+/// Records the tier of the nft the mint contract just minted
+#[cfg_attr(not(feature = "library"), entry_point)]
+pub fn reply(deps: DepsMut, _env: Env, msg: Reply) -> Result<Response, ContractError> {
+    if msg.id != MINT_REPLY_ID {
+        return Err(ContractError::UnknownReply { id: msg.id });
+    }
+    let config = CONFIG.load(deps.storage)?;
+    let events = msg
+        .result
+        .into_result()
+        .map_err(StdError::generic_err)?
+        .events;
+    let token_id = events
+        .iter()
+        .filter(|event| event.ty == "wasm")
+        .filter(|event| {
+            event
+                .attributes
+                .iter()
+                .any(|a| a.key == "_contract_address" && a.value == config.mint_contract)
+        })
+        .flat_map(|event| event.attributes.iter())
+        .find(|a| a.key == "token_id")
+        .map(|a| a.value.clone())
+        .ok_or_else(|| StdError::generic_err("mint reply has no token_id"))?;
+    let nft_type = PENDING_MINT.load(deps.storage)?;
+    PENDING_MINT.remove(deps.storage);
+    TOKEN_TIERS.save(deps.storage, &token_id, &nft_type)?;
+
+    Ok(Response::default()
+        .add_attribute("action", "record_mint")
+        .add_attribute("token_id", token_id)
+        .add_attribute("nft_type", nft_type.to_string()))
 }
 
 pub fn execute(
@@ -37,7 +106,23 @@ pub fn execute(
     msg: ExecuteMsg,
 ) -> Result<Response, ContractError> {
     match msg {
-        ExecuteMsg::ConfigUpdate { config } => try_config_update(deps, env, info, config),
+        ExecuteMsg::UpdateConfig { config } => try_update_config(deps, env, info, config),
+
+        ExecuteMsg::ProposeNewAdmin { new_admin } => {
+            Ok(try_propose_new_admin(deps, env, info, new_admin)?)
+        }
+
+        ExecuteMsg::AcceptAdmin {} => Ok(try_accept_admin(deps, env, info)?),
+
+        ExecuteMsg::RenounceAdmin {} => Ok(try_renounce_admin(deps, env, info)?),
+
+        ExecuteMsg::GrantRole { role, address } => {
+            Ok(try_grant_role(deps, env, info, role, address)?)
+        }
+
+        ExecuteMsg::RevokeRole { role, address } => {
+            Ok(try_revoke_role(deps, env, info, role, address)?)
+        }
 
         ExecuteMsg::WinnerUpdate { winner } => try_winners_update(deps, env, info, winner),
 
@@ -45,33 +130,156 @@ pub fn execute(
 
         ExecuteMsg::Degen { burn_nft_id } => try_degen_burn(deps, env, info, burn_nft_id),
 
-        ExecuteMsg::RoundUpdate { round_info } => try_round_update(deps, env, info, round_info),
+        ExecuteMsg::StartRound { round_info } => try_start_round(deps, env, info, round_info),
+
+        ExecuteMsg::EditRound { round_info } => try_edit_round(deps, env, info, round_info),
+
+        ExecuteMsg::CloseRound {} => try_close_round(deps, env, info),
+
+        ExecuteMsg::DistributeRevenue {} => try_distribute_revenue(deps, env, info),
+
+        ExecuteMsg::DrawWinner {
+            round_id,
+            claim_end_time,
+        } => try_draw_winner(deps, env, info, round_id, claim_end_time),
+
+        ExecuteMsg::CommitDrawSeed {
+            round_id,
+            commitment,
+        } => try_commit_draw_seed(deps, env, info, round_id, commitment),
+
+        ExecuteMsg::RevealDrawSeed { round_id, secret } => {
+            try_reveal_draw_seed(deps, env, info, round_id, secret)
+        }
+
+        ExecuteMsg::Tick {} => try_tick(deps, env, info),
 
         ExecuteMsg::Mint { nft_type } => try_mint(deps, env, info, nft_type),
+
+        ExecuteMsg::ReceiveNft(receive) => try_stake_nft(deps, env, info, receive),
+
+        ExecuteMsg::UnstakeNft { token_id } => try_unstake_nft(deps, env, info, token_id),
+
+        ExecuteMsg::BuyTickets { count } => try_buy_tickets(deps, env, info, count),
+
+        ExecuteMsg::EnterRound {} => try_enter_round(deps, env, info),
+
+        ExecuteMsg::FundPrizePool {} => try_fund_prize_pool(deps, env, info),
+
+        ExecuteMsg::ClaimShare { recipient } => try_claim_share(deps, env, info, recipient),
     }
 }
 
 pub fn query(deps: Deps, env: Env, msg: QueryMsg) -> StdResult<QueryResponse> {
     match msg {
         QueryMsg::Config {} => get_config(deps, env),
+        QueryMsg::Ownership {} => get_ownership(deps, env),
+        QueryMsg::RoleMembers { role } => get_role_members(deps, env, role),
         QueryMsg::Winners {} => get_winners(deps, env),
         QueryMsg::RoundInfo {} => get_round_info(deps, env),
-        QueryMsg::DegenInfo {} => get_degen_info(deps, env),
+        QueryMsg::DegenInfo { start_after, limit } => get_degen_info(deps, env, start_after, limit),
+        QueryMsg::State {} => get_state(deps, env),
+        QueryMsg::WalletStatus { address } => get_wallet_status(deps, env, address),
+        QueryMsg::SimulateDistributeRevenue {} => get_simulate_distribute_revenue(deps, env),
+        QueryMsg::StakedTokens {
+            owner,
+            start_after,
+            limit,
+        } => get_staked_tokens(deps, env, owner, start_after, limit),
+        QueryMsg::Tickets { round_id } => get_tickets(deps, env, round_id),
+        QueryMsg::WalletTickets { address, round_id } => {
+            get_wallet_tickets(deps, env, address, round_id)
+        }
+        QueryMsg::TicketOwner { round_id, index } => get_ticket_owner(deps, env, round_id, index),
+        QueryMsg::PrizePool {} => get_prize_pool(deps, env),
+        QueryMsg::Reserves {} => get_reserves(deps, env),
+        QueryMsg::SharesOwed { start_after, limit } => {
+            get_shares_owed(deps, env, start_after, limit)
+        }
+        QueryMsg::RoundPrize { round_id } => get_round_prize(deps, env, round_id),
     }
 }
 
-pub fn try_config_update(
+pub fn try_update_config(
     deps: DepsMut,
     _env: Env,
     info: MessageInfo,
-    new_config: Config,
+    update: ConfigUpdate,
 ) -> Result<Response, ContractError> {
-    let _config = CONFIG.load(deps.storage)?;
-    if info.sender != _config.admin {
-        return Err(Unauthorized {}.build());
+    assert_role(deps.storage, &info.sender, Role::ConfigManager)?;
+    let mut config = CONFIG.load(deps.storage)?;
+    let mut changes: Vec<Attribute> = vec![];
+
+    if let Some(mint_contract) = update.mint_contract {
+        changes.push(attr("mint_contract", &mint_contract));
+        config.mint_contract = mint_contract;
+    }
+    if let Some(nft_contract) = update.nft_contract {
+        changes.push(attr("nft_contract", &nft_contract));
+        config.nft_contract = nft_contract;
+    }
+    if let Some(nfts) = update.nfts {
+        changes.push(attr("nfts", json_attribute(&nfts)?));
+        config.nfts = nfts;
+    }
+    if let Some(unstake_cooldown) = update.unstake_cooldown {
+        changes.push(attr("unstake_cooldown", unstake_cooldown.to_string()));
+        config.unstake_cooldown = unstake_cooldown;
+    }
+    if let Some(prize_share) = update.prize_share {
+        changes.push(attr("prize_share", prize_share.to_string()));
+        config.prize_share = prize_share;
+    }
+    if let Some(round_duration) = update.round_duration {
+        changes.push(attr("round_duration", round_duration.to_string()));
+        config.round_duration = round_duration;
+    }
+    if let Some(keeper_reward) = update.keeper_reward {
+        changes.push(attr("keeper_reward", keeper_reward));
+        config.keeper_reward = keeper_reward;
     }
-    CONFIG.save(deps.storage, &new_config)?;
-    Ok(Response::default().add_attribute("action", "config_update"))
+
+    validate_config(deps.as_ref(), &config)?;
+    CONFIG.save(deps.storage, &config)?;
+    Ok(Response::default()
+        .add_attribute("action", "config_update")
+        .add_attributes(changes.clone())
+        .add_event(Event::new("config-updated").add_attributes(changes)))
+}
+
+fn validate_config(deps: Deps, config: &Config) -> Result<(), ContractError> {
+    deps.api
+        .addr_validate(&config.mint_contract)
+        .map_err(|_| invalid_config("mint_contract"))?;
+    deps.api
+        .addr_validate(&config.nft_contract)
+        .map_err(|_| invalid_config("nft_contract"))?;
+
+    if config.unstake_cooldown < MIN_UNSTAKE_COOLDOWN {
+        return Err(invalid_config("unstake_cooldown").into());
+    }
+    if config.prize_share > 10000 {
+        return Err(invalid_config("prize_share").into());
+    }
+    if config.nfts.is_empty() {
+        return Err(invalid_config("nfts").into());
+    }
+    for nft_info in &config.nfts {
+        if nft_info.price.denom.is_empty() || nft_info.price.amount.is_zero() {
+            return Err(invalid_config("nfts").into());
+        }
+        let mut total_share: u32 = 0;
+        for fund_share in &nft_info.shares {
+            deps.api
+                .addr_validate(&fund_share.address)
+                .map_err(|_| invalid_config("nfts"))?;
+            total_share += fund_share.share;
+        }
+        if total_share > 10000 {
+            return Err(invalid_config("nfts").into());
+        }
+    }
+    Ok(())
 }
 
 pub fn try_claim_prize(
@@ -83,39 +291,71 @@ pub fn try_claim_prize(
     let _winner = WINNER_INFO.may_load(deps.storage)?;
     match _winner {
         None => {
-            return Err(ContractError::NotFound {});
+            return Err(ContractError::NoWinner {});
         }
         Some(mut _winner) => {
             let time = _env.block.time.nanos() / 1_000_000_000;
 
-            if _winner.winner_address != info.sender.to_string()
-                || _winner.claimed == true
-                || _winner.claim_end_time < time
-            {
-                return Err(Unauthorized {}.build());
+            if _winner.winner_address != info.sender.to_string() {
+                return Err(ContractError::NotWinner {
+                    sender: info.sender.to_string(),
+                });
+            }
+            if _winner.claimed {
+                return Err(ContractError::PrizeAlreadyClaimed {});
+            }
+            if _winner.claim_end_time < time {
+                return Err(ContractError::ClaimWindowClosed {
+                    claim_end_time: _winner.claim_end_time,
+                });
             }
+            assert_not_staked(deps.storage, &burn_nft_id)?;
+            TOKEN_TIERS.remove(deps.storage, &burn_nft_id);
 
             let _config = CONFIG.load(deps.storage)?;
 
             let msg = Cw721ExecuteMsg::Burn {
-                token_id: burn_nft_id,
+                token_id: burn_nft_id.clone(),
             };
             let burn_nft_submsg = SubMsg::new(WasmMsg::Execute {
                 contract_addr: _config.nft_contract,
                 msg: to_binary(&msg)?,
                 funds: vec![],
             });
+            let mut pool = PRIZE_POOL.may_load(deps.storage)?.unwrap_or_default();
+            let balance = deps
+                .querier
+                .query_balance(_env.contract.address.clone(), "uusd")?;
+            let amount = _winner.winner_amount.amount;
+            if pool.reserved < amount || balance.amount < amount {
+                return Err(ContractError::InsufficientReserve {
+                    amount,
+                    reserved: pool.reserved,
+                    balance: balance.amount,
+                });
+            }
+            pool.reserved -= amount;
+            PRIZE_POOL.save(deps.storage, &pool)?;
             _winner.claimed = true;
             WINNER_INFO.save(deps.storage, &_winner)?;
+            let prize = deduct_tax(deps.as_ref(), _winner.winner_amount)?;
 
             //Burn nft and send winner prize
             Ok(Response::default()
                 .add_message(BankMsg::Send {
-                    to_address: _winner.winner_address,
-                    amount: vec![deduct_tax(deps.as_ref(), _winner.winner_amount)?].to_vec(),
+                    to_address: _winner.winner_address.clone(),
+                    amount: vec![prize.clone()],
                 })
                 .add_submessage(burn_nft_submsg)
-                .add_attribute("action", "winner_prize_claim"))
+                .add_attribute("action", "winner_prize_claim")
+                .add_event(
+                    Event::new("prize-claimed")
+                        .add_attribute("round_id", current_round_id(deps.storage)?.to_string())
+                        .add_attribute("wallet", _winner.winner_address)
+                        .add_attribute("amount", prize.amount)
+                        .add_attribute("denom", prize.denom)
+                        .add_attribute("burned_token_id", burn_nft_id),
+                ))
         }
     }
 }
@@ -127,37 +367,103 @@ pub fn try_degen_burn(
     burn_nft_id: String,
 ) -> Result<Response, ContractError> {
     let _config = CONFIG.load(deps.storage)?;
+    let round_id = assert_tickets_open(deps.storage)?;
+    assert_not_staked(deps.storage, &burn_nft_id)?;
 
     let msg = Cw721ExecuteMsg::Burn {
         token_id: burn_nft_id.clone(),
     };
     let burn_nft_submsg = SubMsg::new(WasmMsg::Execute {
-        contract_addr: _config.nft_contract,
+        contract_addr: _config.nft_contract.clone(),
         msg: to_binary(&msg)?,
         funds: vec![],
     });
     let st = true;
-    DEGEN_INFO.save(deps.storage, info.sender.clone().to_string(), &st)?;
+    DEGEN_INFO.save(deps.storage, (round_id.into(), info.sender.as_str()), &st)?;
+
+    // nfts minted elsewhere have no tier and count once
+    let tickets = match TOKEN_TIERS.may_load(deps.storage, &burn_nft_id)? {
+        Some(nft_type) => _config
+            .nfts
+            .get(nft_type as usize - 1)
+            .map_or(1, |nft_info| nft_info.ticket_weight as u64),
+        None => 1,
+    };
+    TOKEN_TIERS.remove(deps.storage, &burn_nft_id);
+    let issued = issue_tickets(
+        deps.storage,
+        round_id,
+        info.sender.as_str(),
+        tickets,
+        TicketSource::Burn,
+    )?;
 
     //Burn nft and for degen
     Ok(Response::default()
         .add_submessage(burn_nft_submsg)
         .add_attribute("action", "burn_nft")
-        .add_attribute("id", burn_nft_id.clone()))
+        .add_attribute("id", burn_nft_id.clone())
+        .add_event(
+            Event::new("degen-burn")
+                .add_attribute("round_id", round_id.to_string())
+                .add_attribute("wallet", info.sender)
+                .add_attribute("burned_token_id", burn_nft_id),
+        )
+        .add_events(issued))
 }
 
 pub fn try_winners_update(
     deps: DepsMut,
-    _env: Env,
+    env: Env,
     info: MessageInfo,
     winner: WinnerInfo,
 ) -> Result<Response, ContractError> {
-    let _config = CONFIG.load(deps.storage)?;
-    if info.sender != _config.admin {
-        return Err(Unauthorized {}.build());
+    assert_role(deps.storage, &info.sender, Role::RoundOperator)?;
+    if winner.winner_amount.denom != "uusd" {
+        return Err(ContractError::WrongDenom {
+            expected: "uusd".to_string(),
+            received: winner.winner_amount.denom,
+        });
     }
-    WINNER_INFO.save(deps.storage, &winner)?;
-    Ok(Response::default().add_attribute("action", "winner_update"))
+    Ok(Response::default()
+        .add_attribute("action", "winner_update")
+        .add_event(set_winner(deps.storage, &env, winner)?))
+}
+
+fn set_winner(
+    storage: &mut dyn Storage,
+    env: &Env,
+    winner: WinnerInfo,
+) -> Result<Event, ContractError> {
+    if let Some(open) = open_prize(storage, env)? {
+        return Err(ContractError::PrizeStillOpen {
+            winner: open.winner_address,
+            claim_end_time: open.claim_end_time,
+        });
+    }
+    // the winner being replaced has claimed or let the claim expire
+    let mut pool = PRIZE_POOL.may_load(storage)?.unwrap_or_default();
+    let released = pool.release();
+    if !winner.claimed {
+        if winner.winner_amount.amount > pool.locked {
+            return Err(ContractError::PrizeExceedsPool {
+                amount: winner.winner_amount.amount,
+                pool: pool.locked,
+            });
+        }
+        pool.locked -= winner.winner_amount.amount;
+        pool.reserved = winner.winner_amount.amount;
+    }
+    PRIZE_POOL.save(storage, &pool)?;
+    WINNER_INFO.save(storage, &winner)?;
+    Ok(Event::new("winner-set")
+        .add_attribute("round_id", current_round_id(storage)?.to_string())
+        .add_attribute("wallet", winner.winner_address)
+        .add_attribute("amount", winner.winner_amount.amount)
+        .add_attribute("denom", winner.winner_amount.denom)
+        .add_attribute("claim_end_time", winner.claim_end_time.to_string())
+        .add_attribute("reserved", pool.reserved)
+        .add_attribute("released", released))
 }
 
 pub fn try_mint(
@@ -167,28 +473,35 @@ pub fn try_mint(
     mut nft_type: u32,
 ) -> Result<Response, ContractError> {
     let _config = CONFIG.load(deps.storage)?;
-    nft_type = nft_type - 1;
-    if nft_type >= _config.nfts.len() as u32 {
-        return Err(Unauthorized {}.build());
+    let tiers = _config.nfts.len() as u32;
+    if nft_type == 0 || nft_type > tiers {
+        return Err(ContractError::InvalidNftType { nft_type, tiers });
     }
+    nft_type -= 1;
     let sent_funds = info.funds.clone();
     let nft_info = _config.nfts[nft_type as usize].clone();
 
     if sent_funds.is_empty() {
-        return Err(ContractError::EmptyBalance {});
+        return Err(ContractError::NoFundsSent {});
     }
 
     if nft_info.price.denom != sent_funds[0].denom {
-        return Err(ContractError::InsufficientFund {});
+        return Err(ContractError::WrongDenom {
+            expected: nft_info.price.denom,
+            received: sent_funds[0].denom.clone(),
+        });
     }
     if sent_funds[0].amount != nft_info.price.amount {
-        return Err(ContractError::InsufficientFund {});
+        return Err(ContractError::WrongAmount {
+            expected: nft_info.price.amount,
+            received: sent_funds[0].amount,
+        });
     }
 
-    if nft_type == 1 {
-        NFT2_FUNDS.update(deps.storage, |mut c| -> StdResult<_> {
-            c = c + sent_funds[0].amount;
-            Ok(c)
+    PENDING_MINT.save(deps.storage, &(nft_type + 1))?;
+    if nft_type > 0 {
+        NFT_FUNDS.update(deps.storage, nft_type.into(), |c| -> StdResult<_> {
+            Ok(c.unwrap_or_default() + sent_funds[0].amount)
         })?;
     }
 
@@ -200,52 +513,881 @@ pub fn try_mint(
             "".to_string(),
         ))
         .add_attribute("action", "mint")
-        .add_attribute("nft", (nft_type + 1).to_string()))
+        .add_attribute("nft", (nft_type + 1).to_string())
+        .add_event(
+            Event::new("delorean-mint")
+                .add_attribute("round_id", current_round_id(deps.storage)?.to_string())
+                .add_attribute("wallet", info.sender)
+                .add_attribute("nft_type", (nft_type + 1).to_string())
+                .add_attribute("amount", sent_funds[0].amount)
+                .add_attribute("denom", sent_funds[0].denom.clone()),
+        ))
+}
+
+fn round_state(storage: &dyn Storage) -> StdResult<RoundState> {
+    Ok(ROUND_STATE.may_load(storage)?.unwrap_or_default())
+}
+
+fn assert_round_open(storage: &dyn Storage) -> Result<u64, ContractError> {
+    let round_id = current_round_id(storage)?;
+    if round_state(storage)?.closed {
+        return Err(ContractError::RoundNotOpen { round_id });
+    }
+    Ok(round_id)
+}
+
+/// A revealed seed fixes the draw, so the round takes no tickets after it
+fn tickets_open(storage: &dyn Storage, round_id: u64) -> StdResult<bool> {
+    let revealed = DRAW_SEEDS
+        .may_load(storage, round_id.into())?
+        .and_then(|seed| seed.secret)
+        .is_some();
+    Ok(!round_state(storage)?.closed && !revealed)
+}
+
+fn assert_tickets_open(storage: &dyn Storage) -> Result<u64, ContractError> {
+    let round_id = assert_round_open(storage)?;
+    if !tickets_open(storage, round_id)? {
+        return Err(ContractError::TicketsFrozen { round_id });
+    }
+    Ok(round_id)
 }
 
-pub fn try_round_update(
+fn round_event(ty: &str, round_id: u64, round: &RoundInfo) -> Event {
+    Event::new(ty)
+        .add_attribute("round_id", round_id.to_string())
+        .add_attribute("name", round.name.clone())
+        .add_attribute("active", round.active.to_string())
+        .add_attribute("start_date", round.start_date.to_string())
+        .add_attribute("end_date", round.end_date.to_string())
+}
+
+pub fn try_start_round(
     deps: DepsMut,
     _env: Env,
     info: MessageInfo,
     round: RoundInfo,
 ) -> Result<Response, ContractError> {
-    let _config = CONFIG.load(deps.storage)?;
-    if info.sender != _config.admin {
-        return Err(Unauthorized {}.build());
+    assert_role(deps.storage, &info.sender, Role::RoundOperator)?;
+    let round_id = current_round_id(deps.storage)?;
+    // before the first round there is nothing to close
+    if ROUND_INFO.may_load(deps.storage)?.is_some() {
+        let state = round_state(deps.storage)?;
+        if !state.closed {
+            return Err(ContractError::RoundNotClosed { round_id });
+        }
+        if !state.distributed {
+            return Err(ContractError::RevenueNotDistributed { round_id });
+        }
     }
+
+    Ok(Response::default()
+        .add_attribute("action", "start_round")
+        .add_event(start_round(deps.storage, round_id, &round)?))
+}
+
+fn start_round(storage: &mut dyn Storage, round_id: u64, round: &RoundInfo) -> StdResult<Event> {
+    ROUND_INFO.save(storage, round)?;
+    ROUND_ID.save(storage, &(round_id + 1))?;
+    ROUND_STATE.save(storage, &RoundState::default())?;
+    Ok(round_event("round-started", round_id + 1, round))
+}
+
+pub fn try_edit_round(
+    deps: DepsMut,
+    _env: Env,
+    info: MessageInfo,
+    round: RoundInfo,
+) -> Result<Response, ContractError> {
+    assert_role(deps.storage, &info.sender, Role::RoundOperator)?;
+    let round_id = assert_round_open(deps.storage)?;
     ROUND_INFO.save(deps.storage, &round)?;
 
-    let balanace = deps.querier.query_balance(_env.contract.address, "uusd")?;
-    let nft2_funds = NFT2_FUNDS.load(deps.storage)?;
+    Ok(Response::default()
+        .add_attribute("action", "edit_round")
+        .add_event(round_event("round-edited", round_id, &round)))
+}
+
+pub fn try_close_round(
+    deps: DepsMut,
+    env: Env,
+    info: MessageInfo,
+) -> Result<Response, ContractError> {
+    assert_role(deps.storage, &info.sender, Role::RoundOperator)?;
+    let round_id = assert_round_open(deps.storage)?;
 
-    let mut msgs: Vec<SubMsg> = vec![];
+    Ok(Response::default()
+        .add_attribute("action", "close_round")
+        .add_events(close_round(deps.storage, &env, round_id)?))
+}
+
+fn close_round(storage: &mut dyn Storage, env: &Env, round_id: u64) -> StdResult<Vec<Event>> {
+    ROUND_STATE.save(
+        storage,
+        &RoundState {
+            closed: true,
+            distributed: false,
+        },
+    )?;
+    let tickets = ROUND_TICKETS
+        .may_load(storage, round_id.into())?
+        .unwrap_or_default();
+
+    // this round's funding joins whatever the last winner left,
+    // including a prize whose claim window has passed
+    let mut pool = PRIZE_POOL.may_load(storage)?.unwrap_or_default();
+    if open_prize(storage, env)?.is_none() {
+        pool.release();
+    }
+    let rollover = pool.locked;
+    let funded = pool.open;
+    pool.locked = rollover + funded;
+    pool.open = Uint128::zero();
+    PRIZE_POOL.save(storage, &pool)?;
+    ROUND_PRIZES.save(storage, round_id.into(), &pool.locked)?;
 
-    for (i, nft_info) in _config.nfts.iter().enumerate() {
-        let share_amount: u128;
-        if i == 0 {
-            share_amount = balanace.amount.u128() - nft2_funds.u128();
-        } else {
-            share_amount = nft2_funds.u128();
+    Ok(vec![
+        Event::new("round-closed")
+            .add_attribute("round_id", round_id.to_string())
+            .add_attribute("tickets", tickets.to_string()),
+        Event::new("prize-pool-locked")
+            .add_attribute("round_id", round_id.to_string())
+            .add_attribute("rollover", rollover)
+            .add_attribute("funded", funded)
+            .add_attribute("amount", pool.locked),
+    ])
+}
+
+pub fn try_distribute_revenue(
+    deps: DepsMut,
+    env: Env,
+    info: MessageInfo,
+) -> Result<Response, ContractError> {
+    assert_role(deps.storage, &info.sender, Role::TreasuryOperator)?;
+    let round_id = current_round_id(deps.storage)?;
+    let state = round_state(deps.storage)?;
+    if !state.closed {
+        return Err(ContractError::RoundNotClosed { round_id });
+    }
+    if state.distributed {
+        return Err(ContractError::RevenueAlreadyDistributed { round_id });
+    }
+
+    Ok(Response::default()
+        .add_attribute("action", "distribute_revenue")
+        .add_events(distribute_revenue(deps, &env, round_id, Uint128::zero())?))
+}
+
+/// Credits the round's revenue, leaving `held_back` of the free balance
+/// undistributed for a payment the caller makes alongside
+fn distribute_revenue(
+    deps: DepsMut,
+    env: &Env,
+    round_id: u64,
+    held_back: Uint128,
+) -> StdResult<Vec<Event>> {
+    ROUND_STATE.save(
+        deps.storage,
+        &RoundState {
+            closed: true,
+            distributed: true,
+        },
+    )?;
+
+    let (balance, prize_cut, deposits) = round_deposits(deps.as_ref(), env, held_back)?;
+    let mut pool = PRIZE_POOL.may_load(deps.storage)?.unwrap_or_default();
+    pool.locked += prize_cut;
+    PRIZE_POOL.save(deps.storage, &pool)?;
+    ROUND_PRIZES.update(deps.storage, round_id.into(), |prize| -> StdResult<_> {
+        Ok(prize.unwrap_or_default() + prize_cut)
+    })?;
+
+    // shares are credited here and pulled with ClaimShare, so a distribution
+    // only touches one entry per configured share and per tier
+    let mut share_events: Vec<Event> = vec![];
+    let mut credited = Uint128::zero();
+    for deposit in deposits {
+        let amount = deposit.payout.gross.amount;
+        if amount.is_zero() {
+            continue;
         }
-        for fund_share in &nft_info.shares {
-            let amount = fund_share.get_share(Uint128::from(share_amount)).u128();
-            msgs.push(deposit_funds(
-                fund_share.address.clone().to_string(),
-                deduct_tax(deps.as_ref(), coin(amount, balanace.clone().denom))?,
-            )?);
+        SHARES_OWED.update(
+            deps.storage,
+            &deposit.payout.recipient,
+            |owed| -> StdResult<_> { Ok(owed.unwrap_or_default() + amount) },
+        )?;
+        credited += amount;
+        share_events.push(
+            Event::new("share-credited")
+                .add_attribute("round_id", round_id.to_string())
+                .add_attribute("nft_type", deposit.nft_type.to_string())
+                .add_attribute("wallet", deposit.payout.recipient)
+                .add_attribute("note", deposit.note)
+                .add_attribute("amount", amount)
+                .add_attribute("denom", deposit.payout.gross.denom),
+        );
+    }
+    let total_owed = TOTAL_SHARES_OWED
+        .may_load(deps.storage)?
+        .unwrap_or_default();
+    TOTAL_SHARES_OWED.save(deps.storage, &(total_owed + credited))?;
+    for i in 1..CONFIG.load(deps.storage)?.nfts.len() as u32 {
+        NFT_FUNDS.remove(deps.storage, i.into());
+    }
+    let mut events = vec![Event::new("revenue-distributed")
+        .add_attribute("round_id", round_id.to_string())
+        .add_attribute("balance", balance.amount)
+        .add_attribute("denom", balance.denom)
+        .add_attribute("credited", credited)
+        .add_attribute("prize_cut", prize_cut)
+        .add_attribute("prize_pool", pool.locked)];
+    events.extend(share_events);
+    Ok(events)
+}
+
+pub fn try_tick(mut deps: DepsMut, env: Env, info: MessageInfo) -> Result<Response, ContractError> {
+    let config = CONFIG.load(deps.storage)?;
+    if config.round_duration == 0 {
+        return Err(ContractError::AutomationDisabled {});
+    }
+    let round = ROUND_INFO
+        .may_load(deps.storage)?
+        .ok_or(ContractError::NoRound {})?;
+    let now = env.block.time.nanos() / 1_000_000_000;
+    if now < round.end_date {
+        return Err(ContractError::RoundNotEnded {
+            end_date: round.end_date,
+        });
+    }
+    let round_id = current_round_id(deps.storage)?;
+
+    // the reward comes out of tier 0's revenue, the other tiers' funds are
+    // already promised to their shares
+    let balance = deps
+        .querier
+        .query_balance(env.contract.address.clone(), "uusd")?;
+    let free = distributable(deps.as_ref(), balance.amount)?;
+    let tier_zero = tier_revenue(deps.as_ref(), &config, free)?
+        .first()
+        .copied()
+        .unwrap_or_default();
+    let reward = config.keeper_reward.min(tier_zero);
+
+    // picks up after any step the operator already took by hand
+    let state = round_state(deps.storage)?;
+    let mut events: Vec<Event> = vec![];
+    if !state.closed {
+        events.extend(close_round(deps.storage, &env, round_id)?);
+    }
+    if !state.distributed {
+        events.extend(distribute_revenue(deps.branch(), &env, round_id, reward)?);
+    }
+
+    // the next round keeps the cadence, skipping periods nobody ticked
+    let missed = (now - round.end_date) / config.round_duration;
+    let start_date = round.end_date + missed * config.round_duration;
+    let next = RoundInfo {
+        active: true,
+        start_date,
+        end_date: start_date + config.round_duration,
+        name: format!("Round {}", round_id + 1),
+        ticket_price: round.ticket_price,
+    };
+    // the operator can still draw the round by hand once its seed is revealed
+    // or the open prize is settled
+    match draw_round(deps.storage, &env, round_id, next.end_date) {
+        Ok(event) => events.push(event),
+        Err(ContractError::RoundAlreadyDrawn { .. })
+        | Err(ContractError::NoTickets { .. })
+        | Err(ContractError::EmptyPrizePool {})
+        | Err(ContractError::DrawSeedNotRevealed { .. })
+        | Err(ContractError::PrizeStillOpen { .. }) => {}
+        Err(err) => return Err(err),
+    }
+    events.push(start_round(deps.storage, round_id, &next)?);
+
+    let mut rsp = Response::default()
+        .add_attribute("action", "tick")
+        .add_events(events);
+    if !reward.is_zero() {
+        let reward = deduct_tax(deps.as_ref(), coin(reward.u128(), "uusd"))?;
+        rsp = rsp
+            .add_message(BankMsg::Send {
+                to_address: info.sender.to_string(),
+                amount: vec![reward.clone()],
+            })
+            .add_event(
+                Event::new("keeper-rewarded")
+                    .add_attribute("round_id", round_id.to_string())
+                    .add_attribute("keeper", info.sender)
+                    .add_attribute("amount", reward.amount)
+                    .add_attribute("denom", reward.denom),
+            );
+    }
+    Ok(rsp)
+}
+
+pub fn try_draw_winner(
+    deps: DepsMut,
+    env: Env,
+    info: MessageInfo,
+    round_id: u64,
+    claim_end_time: u64,
+) -> Result<Response, ContractError> {
+    assert_role(deps.storage, &info.sender, Role::RoundOperator)?;
+    let current = current_round_id(deps.storage)?;
+    let closed = round_state(deps.storage)?.closed;
+    if round_id > current || (round_id == current && !closed) {
+        return Err(ContractError::RoundNotClosed { round_id });
+    }
+    // an older round's prize has rolled into the rounds after it
+    let latest = if closed { current } else { current - 1 };
+    if round_id != latest {
+        return Err(ContractError::StaleRound { round_id, latest });
+    }
+    let event = draw_round(deps.storage, &env, round_id, claim_end_time)?;
+    Ok(Response::default()
+        .add_attribute("action", "draw_winner")
+        .add_event(event))
+}
+
+/// Makes the holder of the ticket the round's revealed seed picks the winner
+/// of the round's prize, claimable until `claim_end_time`
+fn draw_round(
+    storage: &mut dyn Storage,
+    env: &Env,
+    round_id: u64,
+    claim_end_time: u64,
+) -> Result<Event, ContractError> {
+    if ROUND_DRAWS.has(storage, round_id.into()) {
+        return Err(ContractError::RoundAlreadyDrawn { round_id });
+    }
+    let tickets = ROUND_TICKETS
+        .may_load(storage, round_id.into())?
+        .unwrap_or_default();
+    if tickets == 0 {
+        return Err(ContractError::NoTickets { round_id });
+    }
+    let pool = PRIZE_POOL.may_load(storage)?.unwrap_or_default();
+    let prize = ROUND_PRIZES
+        .may_load(storage, round_id.into())?
+        .unwrap_or_default()
+        .min(pool.locked);
+    if prize.is_zero() {
+        return Err(ContractError::EmptyPrizePool {});
+    }
+    let secret = DRAW_SEEDS
+        .may_load(storage, round_id.into())?
+        .and_then(|seed| seed.secret)
+        .ok_or(ContractError::DrawSeedNotRevealed { round_id })?;
+
+    let index = draw_ticket(&secret, round_id) % tickets;
+    let entry = ticket_entry(storage, round_id, index)?;
+    let event = set_winner(
+        storage,
+        env,
+        WinnerInfo {
+            winner_address: entry.wallet,
+            winner_amount: coin(prize.u128(), "uusd"),
+            claimed: false,
+            claim_end_time,
+        },
+    )?;
+    ROUND_DRAWS.save(storage, round_id.into(), &index)?;
+    Ok(event
+        .add_attribute("drawn_round_id", round_id.to_string())
+        .add_attribute("ticket", index.to_string()))
+}
+
+/// Hashes the revealed secret with the round. The secret was committed
+/// before the round's tickets were final, so the sender and the block it
+/// lands in have no say over the ticket.
+fn draw_ticket(secret: &str, round_id: u64) -> u64 {
+    let digest = Sha256::new()
+        .chain(secret.as_bytes())
+        .chain(round_id.to_be_bytes())
+        .finalize();
+    let mut seed = [0u8; 8];
+    seed.copy_from_slice(&digest[0..8]);
+    u64::from_be_bytes(seed)
+}
+
+pub fn try_commit_draw_seed(
+    deps: DepsMut,
+    _env: Env,
+    info: MessageInfo,
+    round_id: u64,
+    commitment: Binary,
+) -> Result<Response, ContractError> {
+    assert_role(deps.storage, &info.sender, Role::RoundOperator)?;
+    // a seed picked once the round's tickets are final could pick the winner
+    let current = current_round_id(deps.storage)?;
+    if round_id < current || (round_id == current && !tickets_open(deps.storage, round_id)?) {
+        return Err(ContractError::RoundNotOpen { round_id });
+    }
+    if DRAW_SEEDS.has(deps.storage, round_id.into()) {
+        return Err(ContractError::DrawSeedCommitted { round_id });
+    }
+    if commitment.len() != 32 {
+        return Err(StdError::generic_err("commitment must be a sha256 digest").into());
+    }
+    DRAW_SEEDS.save(
+        deps.storage,
+        round_id.into(),
+        &DrawSeed {
+            commitment: commitment.clone(),
+            secret: None,
+        },
+    )?;
+
+    Ok(Response::default()
+        .add_attribute("action", "commit_draw_seed")
+        .add_event(
+            Event::new("draw-seed-committed")
+                .add_attribute("round_id", round_id.to_string())
+                .add_attribute("commitment", commitment.to_base64()),
+        ))
+}
+
+pub fn try_reveal_draw_seed(
+    deps: DepsMut,
+    env: Env,
+    _info: MessageInfo,
+    round_id: u64,
+    secret: String,
+) -> Result<Response, ContractError> {
+    let mut seed = DRAW_SEEDS
+        .may_load(deps.storage, round_id.into())?
+        .ok_or(ContractError::NoDrawSeed { round_id })?;
+    let current = current_round_id(deps.storage)?;
+    if round_id > current {
+        return Err(ContractError::RoundNotClosed { round_id });
+    }
+    if round_id == current && !round_state(deps.storage)?.closed {
+        let end_date = ROUND_INFO.may_load(deps.storage)?.map_or(0, |r| r.end_date);
+        if env.block.time.nanos() / 1_000_000_000 < end_date {
+            return Err(ContractError::RoundNotEnded { end_date });
         }
     }
-    let t = Uint128::from(0u32);
-    NFT2_FUNDS.save(deps.storage, &t)?;
-    let all: StdResult<Vec<_>> = DEGEN_INFO
-        .range(deps.storage, None, None, Order::Ascending)
-        .collect();
-    for (key, _value) in all.unwrap() {
-        DEGEN_INFO.remove(deps.storage, String::from_utf8(key).unwrap());
+    if Sha256::digest(secret.as_bytes()).as_slice() != seed.commitment.as_slice() {
+        return Err(ContractError::DrawSeedMismatch { round_id });
+    }
+    seed.secret = Some(secret.clone());
+    DRAW_SEEDS.save(deps.storage, round_id.into(), &seed)?;
+
+    Ok(Response::default()
+        .add_attribute("action", "reveal_draw_seed")
+        .add_event(
+            Event::new("draw-seed-revealed")
+                .add_attribute("round_id", round_id.to_string())
+                .add_attribute("secret", secret),
+        ))
+}
+
+/// Staked nfts are held here, so they must not be burned through the contract
+fn assert_not_staked(storage: &dyn Storage, token_id: &str) -> Result<(), ContractError> {
+    if STAKED_TOKENS.has(storage, token_id) {
+        return Err(ContractError::TokenStaked {
+            token_id: token_id.to_string(),
+        });
+    }
+    Ok(())
+}
+
+/// Applies a ticket change to the owner and the running total
+fn update_tickets<F>(storage: &mut dyn Storage, owner: &str, change: F) -> StdResult<(u64, u64)>
+where
+    F: Fn(u64) -> u64,
+{
+    let wallet = change(STAKED_TICKETS.may_load(storage, owner)?.unwrap_or_default());
+    let total = change(TOTAL_STAKED.may_load(storage)?.unwrap_or_default());
+    if wallet == 0 {
+        STAKED_TICKETS.remove(storage, owner);
+    } else {
+        STAKED_TICKETS.save(storage, owner, &wallet)?;
+    }
+    TOTAL_STAKED.save(storage, &total)?;
+    Ok((wallet, total))
+}
+
+pub fn try_stake_nft(
+    deps: DepsMut,
+    env: Env,
+    info: MessageInfo,
+    receive: Cw721ReceiveMsg,
+) -> Result<Response, ContractError> {
+    let config = CONFIG.load(deps.storage)?;
+    if info.sender != config.nft_contract {
+        return Err(ContractError::NotNftContract {
+            sender: info.sender.to_string(),
+        });
+    }
+    let token_id = receive.token_id;
+    // nfts minted elsewhere have no tier and count once, as on Degen
+    let (nft_type, tickets) = match TOKEN_TIERS.may_load(deps.storage, &token_id)? {
+        Some(nft_type) => {
+            let tiers = config.nfts.len() as u32;
+            let nft_info = config
+                .nfts
+                .get(nft_type as usize - 1)
+                .ok_or(ContractError::InvalidNftType { nft_type, tiers })?;
+            (nft_type, nft_info.ticket_weight as u64)
+        }
+        None => (0, 1),
+    };
+    let owner = deps.api.addr_validate(&receive.sender)?;
+
+    let staked = StakedToken {
+        owner: owner.to_string(),
+        nft_type,
+        tickets,
+        staked_at: env.block.time.nanos() / 1_000_000_000,
+    };
+    STAKED_TOKENS.save(deps.storage, &token_id, &staked)?;
+    STAKED_BY_OWNER.save(deps.storage, (owner.as_str(), &token_id), &true)?;
+    let (wallet_tickets, total_tickets) =
+        update_tickets(deps.storage, owner.as_str(), |t| t + staked.tickets)?;
+    let round_id = current_round_id(deps.storage)?;
+    if UNSTAKED_ROUND.may_load(deps.storage, &token_id)? == Some(round_id) {
+        // the token's tickets may already be in the round under its last owner
+        STAKE_ENTRIES.update(
+            deps.storage,
+            (round_id.into(), owner.as_str()),
+            |entered| -> StdResult<_> { Ok(entered.unwrap_or_default() + staked.tickets) },
+        )?;
+    }
+    // a closed or drawn round takes no new tickets, they enter the next one
+    // on EnterRound
+    let entered = if !tickets_open(deps.storage, round_id)? {
+        None
+    } else {
+        enter_staked(deps.storage, round_id, owner.as_str())?
+    };
+
+    Ok(Response::default()
+        .add_attribute("action", "stake_nft")
+        .add_attribute("token_id", token_id.clone())
+        .add_event(
+            Event::new("nft-staked")
+                .add_attribute("round_id", round_id.to_string())
+                .add_attribute("wallet", owner)
+                .add_attribute("token_id", token_id)
+                .add_attribute("nft_type", nft_type.to_string())
+                .add_attribute("tickets", staked.tickets.to_string())
+                .add_attribute("wallet_tickets", wallet_tickets.to_string())
+                .add_attribute("total_tickets", total_tickets.to_string()),
+        )
+        .add_events(entered))
+}
+
+pub fn try_unstake_nft(
+    deps: DepsMut,
+    env: Env,
+    info: MessageInfo,
+    token_id: String,
+) -> Result<Response, ContractError> {
+    let staked = match STAKED_TOKENS.may_load(deps.storage, &token_id)? {
+        Some(staked) if staked.owner == info.sender.as_str() => staked,
+        _ => {
+            return Err(ContractError::NotTokenOwner {
+                sender: info.sender.to_string(),
+                token_id,
+            })
+        }
+    };
+    let config = CONFIG.load(deps.storage)?;
+    let unlock_time = staked.unlock_time(config.unstake_cooldown);
+    if env.block.time.nanos() / 1_000_000_000 < unlock_time {
+        return Err(ContractError::StakeLocked {
+            token_id,
+            unlock_time,
+        });
+    }
+
+    STAKED_TOKENS.remove(deps.storage, &token_id);
+    STAKED_BY_OWNER.remove(deps.storage, (staked.owner.as_str(), &token_id));
+    let (wallet_tickets, total_tickets) =
+        update_tickets(deps.storage, &staked.owner, |t| t - staked.tickets)?;
+    let round_id = current_round_id(deps.storage)?;
+    UNSTAKED_ROUND.save(deps.storage, &token_id, &round_id)?;
+
+    let msg = Cw721ExecuteMsg::TransferNft {
+        recipient: staked.owner.clone(),
+        token_id: token_id.clone(),
+    };
+    Ok(Response::default()
+        .add_message(WasmMsg::Execute {
+            contract_addr: config.nft_contract,
+            msg: to_binary(&msg)?,
+            funds: vec![],
+        })
+        .add_attribute("action", "unstake_nft")
+        .add_attribute("token_id", token_id.clone())
+        .add_event(
+            Event::new("nft-unstaked")
+                .add_attribute("round_id", round_id.to_string())
+                .add_attribute("wallet", staked.owner)
+                .add_attribute("token_id", token_id)
+                .add_attribute("nft_type", staked.nft_type.to_string())
+                .add_attribute("tickets", staked.tickets.to_string())
+                .add_attribute("wallet_tickets", wallet_tickets.to_string())
+                .add_attribute("total_tickets", total_tickets.to_string()),
+        ))
+}
+
+/// Appends a run of tickets to the round, None when there is nothing to issue
+fn issue_tickets(
+    storage: &mut dyn Storage,
+    round_id: u64,
+    wallet: &str,
+    count: u64,
+    source: TicketSource,
+) -> StdResult<Option<Event>> {
+    if count == 0 {
+        return Ok(None);
+    }
+    let first_index = ROUND_TICKETS
+        .may_load(storage, round_id.into())?
+        .unwrap_or_default();
+    let total = first_index + count;
+    let entry = TicketEntry {
+        wallet: wallet.to_string(),
+        count,
+        source,
+    };
+    TICKET_ENTRIES.save(storage, (round_id.into(), total.into()), &entry)?;
+    ROUND_TICKETS.save(storage, round_id.into(), &total)?;
+    WALLET_TICKETS.update(storage, (round_id.into(), wallet), |t| -> StdResult<_> {
+        Ok(t.unwrap_or_default() + count)
+    })?;
+
+    Ok(Some(
+        Event::new("tickets-issued")
+            .add_attribute("round_id", round_id.to_string())
+            .add_attribute("wallet", wallet)
+            .add_attribute("source", entry.source.as_str())
+            .add_attribute("count", count.to_string())
+            .add_attribute("first_index", first_index.to_string())
+            .add_attribute("round_tickets", total.to_string()),
+    ))
+}
+
+/// Issues the owner's staked tickets the round has not counted yet, so
+/// restaking within a round does not count twice
+fn enter_staked(storage: &mut dyn Storage, round_id: u64, owner: &str) -> StdResult<Option<Event>> {
+    let staked = STAKED_TICKETS.may_load(storage, owner)?.unwrap_or_default();
+    let entered = STAKE_ENTRIES
+        .may_load(storage, (round_id.into(), owner))?
+        .unwrap_or_default();
+    if staked <= entered {
+        return Ok(None);
+    }
+    STAKE_ENTRIES.save(storage, (round_id.into(), owner), &staked)?;
+    issue_tickets(
+        storage,
+        round_id,
+        owner,
+        staked - entered,
+        TicketSource::Stake,
+    )
+}
+
+pub fn try_buy_tickets(
+    deps: DepsMut,
+    _env: Env,
+    info: MessageInfo,
+    count: u64,
+) -> Result<Response, ContractError> {
+    let price = match ROUND_INFO.may_load(deps.storage)? {
+        Some(RoundInfo {
+            active: true,
+            ticket_price: Some(price),
+            ..
+        }) => price,
+        _ => return Err(ContractError::TicketSalesClosed {}),
+    };
+    if !tickets_open(deps.storage, current_round_id(deps.storage)?)? {
+        return Err(ContractError::TicketSalesClosed {});
+    }
+    if count == 0 {
+        return Err(ContractError::InvalidTicketCount {});
     }
+    let expected = price
+        .amount
+        .checked_mul(count.into())
+        .map_err(StdError::from)?;
+    let sent_funds = info.funds.clone();
+    if sent_funds.is_empty() {
+        return Err(ContractError::NoFundsSent {});
+    }
+    if price.denom != sent_funds[0].denom {
+        return Err(ContractError::WrongDenom {
+            expected: price.denom,
+            received: sent_funds[0].denom.clone(),
+        });
+    }
+    if sent_funds[0].amount != expected {
+        return Err(ContractError::WrongAmount {
+            expected,
+            received: sent_funds[0].amount,
+        });
+    }
+
+    let round_id = current_round_id(deps.storage)?;
+    let issued = issue_tickets(
+        deps.storage,
+        round_id,
+        info.sender.as_str(),
+        count,
+        TicketSource::Purchase,
+    )?;
     Ok(Response::default()
-        .add_submessages(msgs)
-        .add_attribute("action", "round_update"))
+        .add_attribute("action", "buy_tickets")
+        .add_attribute("count", count.to_string())
+        .add_events(issued))
+}
+
+pub fn try_enter_round(
+    deps: DepsMut,
+    _env: Env,
+    info: MessageInfo,
+) -> Result<Response, ContractError> {
+    let round_id = assert_tickets_open(deps.storage)?;
+    let issued = enter_staked(deps.storage, round_id, info.sender.as_str())?
+        .ok_or(ContractError::NothingToEnter { round_id })?;
+    Ok(Response::default()
+        .add_attribute("action", "enter_round")
+        .add_event(issued))
+}
+
+pub fn try_fund_prize_pool(
+    deps: DepsMut,
+    _env: Env,
+    info: MessageInfo,
+) -> Result<Response, ContractError> {
+    if info.funds.is_empty() {
+        return Err(ContractError::NoFundsSent {});
+    }
+    if info.funds[0].denom != "uusd" {
+        return Err(ContractError::WrongDenom {
+            expected: "uusd".to_string(),
+            received: info.funds[0].denom.clone(),
+        });
+    }
+    let amount = info.funds[0].amount;
+    let mut pool = PRIZE_POOL.may_load(deps.storage)?.unwrap_or_default();
+    pool.open += amount;
+    PRIZE_POOL.save(deps.storage, &pool)?;
+
+    Ok(Response::default()
+        .add_attribute("action", "fund_prize_pool")
+        .add_event(
+            Event::new("prize-pool-funded")
+                .add_attribute("round_id", current_round_id(deps.storage)?.to_string())
+                .add_attribute("sender", info.sender)
+                .add_attribute("amount", amount)
+                .add_attribute("open", pool.open),
+        ))
+}
+
+pub fn try_claim_share(
+    deps: DepsMut,
+    _env: Env,
+    _info: MessageInfo,
+    recipient: String,
+) -> Result<Response, ContractError> {
+    let owed = SHARES_OWED
+        .may_load(deps.storage, &recipient)?
+        .unwrap_or_default();
+    if owed.is_zero() {
+        return Err(ContractError::NothingOwed { recipient });
+    }
+    SHARES_OWED.remove(deps.storage, &recipient);
+    let total_owed = TOTAL_SHARES_OWED.load(deps.storage)?;
+    TOTAL_SHARES_OWED.save(deps.storage, &(total_owed - owed))?;
+
+    let payout = payout(deps.as_ref(), recipient, coin(owed.u128(), "uusd"))?;
+    Ok(Response::default()
+        .add_submessage(deposit_funds(payout.recipient.clone(), payout.net.clone())?)
+        .add_attribute("action", "claim_share")
+        .add_event(
+            Event::new("share-claimed")
+                .add_attribute("wallet", payout.recipient)
+                .add_attribute("amount", payout.net.amount)
+                .add_attribute("denom", payout.net.denom),
+        ))
+}
+
+/// Share revenue credited to recipients and not yet claimed
+fn shares_owed(storage: &dyn Storage) -> StdResult<Uint128> {
+    Ok(TOTAL_SHARES_OWED.may_load(storage)?.unwrap_or_default())
+}
+
+/// Balance left once the prize pool and credited shares are held back
+fn distributable(deps: Deps, balance: Uint128) -> StdResult<Uint128> {
+    let pool = PRIZE_POOL.may_load(deps.storage)?.unwrap_or_default();
+    balance
+        .checked_sub(pool.held() + shares_owed(deps.storage)?)
+        .map_err(StdError::from)
+}
+
+/// Deposits a distribution credits and the prize cut it keeps back.
+/// Tier 0 takes whatever the other tiers and `held_back` are not holding.
+fn round_deposits(
+    deps: Deps,
+    env: &Env,
+    held_back: Uint128,
+) -> StdResult<(Coin, Uint128, Vec<ShareDeposit>)> {
+    let config = CONFIG.load(deps.storage)?;
+    let balance = deps
+        .querier
+        .query_balance(env.contract.address.clone(), "uusd")?;
+    let free = distributable(deps, balance.amount)?
+        .checked_sub(held_back)
+        .map_err(StdError::from)?;
+    let tier_funds = tier_revenue(deps, &config, free)?;
+
+    let mut prize_cut = Uint128::zero();
+    let mut deposits: Vec<ShareDeposit> = vec![];
+    for (i, nft_info) in config.nfts.iter().enumerate() {
+        let cut = config.prize_cut(tier_funds[i]);
+        prize_cut += cut;
+        for fund_share in &nft_info.shares {
+            let amount = fund_share.get_share(tier_funds[i] - cut);
+            deposits.push(ShareDeposit {
+                nft_type: i as u32 + 1,
+                note: fund_share.note.clone(),
+                payout: payout(
+                    deps,
+                    fund_share.address.clone(),
+                    coin(amount.u128(), balance.denom.clone()),
+                )?,
+            });
+        }
+    }
+    Ok((balance, prize_cut, deposits))
+}
+
+fn tier_revenue(deps: Deps, config: &Config, balance: Uint128) -> StdResult<Vec<Uint128>> {
+    let mut tier_funds: Vec<Uint128> = vec![Uint128::zero()];
+    for i in 1..config.nfts.len() as u32 {
+        tier_funds.push(
+            NFT_FUNDS
+                .may_load(deps.storage, i.into())?
+                .unwrap_or_default(),
+        );
+    }
+    let tracked: Uint128 = tier_funds.iter().copied().sum();
+    if !config.nfts.is_empty() {
+        tier_funds[0] = balance.checked_sub(tracked).map_err(StdError::from)?;
+    } else {
+        tier_funds.clear();
+    }
+    Ok(tier_funds)
+}
+
+fn string_from_key(key: Vec<u8>) -> StdResult<String> {
+    String::from_utf8(key).map_err(StdError::from)
 }
 
 fn deposit_funds(contract_addr: String, coin: Coin) -> Result<SubMsg, ContractError> {
@@ -269,11 +1411,14 @@ fn secure_mint_nft(
         extension: extension,
         token_uri: token_uri,
     };
-    let exec = SubMsg::new(WasmMsg::Execute {
-        contract_addr: contract_address,
-        msg: to_binary(&msg)?,
-        funds: vec![],
-    });
+    let exec = SubMsg::reply_on_success(
+        WasmMsg::Execute {
+            contract_addr: contract_address,
+            msg: to_binary(&msg)?,
+            funds: vec![],
+        },
+        MINT_REPLY_ID,
+    );
     Ok(exec)
 }
 
@@ -285,7 +1430,10 @@ fn get_config(deps: Deps, _env: Env) -> StdResult<QueryResponse> {
 
 fn get_round_info(deps: Deps, _env: Env) -> StdResult<QueryResponse> {
     let round_info = ROUND_INFO.may_load(deps.storage)?;
-    let rsp = RoundInfoResponse { round: round_info };
+    let rsp = RoundInfoResponse {
+        round_id: current_round_id(deps.storage)?,
+        round: round_info,
+    };
     to_binary(&rsp)
 }
 
@@ -297,14 +1445,289 @@ fn get_winners(deps: Deps, _env: Env) -> StdResult<QueryResponse> {
     to_binary(&rsp)
 }
 
-fn get_degen_info(deps: Deps, _env: Env) -> StdResult<QueryResponse> {
-    let round_info: Vec<Vec<u8>> = DEGEN_INFO
-        .keys(deps.storage, None, None, Order::Ascending)
-        .collect();
-    let address: Vec<String> = round_info
-        .into_iter()
-        .map(|x| String::from_utf8(x).unwrap())
+const DEFAULT_LIMIT: u32 = 30;
+const MAX_LIMIT: u32 = 100;
+
+fn get_degen_info(
+    deps: Deps,
+    _env: Env,
+    start_after: Option<String>,
+    limit: Option<u32>,
+) -> StdResult<QueryResponse> {
+    let limit = limit.unwrap_or(DEFAULT_LIMIT).min(MAX_LIMIT) as usize;
+    let start = start_after.map(Bound::exclusive);
+    let round_id = current_round_id(deps.storage)?;
+    let address: StdResult<Vec<String>> = DEGEN_INFO
+        .prefix(round_id.into())
+        .keys(deps.storage, start.clone(), None, Order::Ascending)
+        .take(limit)
+        .map(string_from_key)
         .collect();
+    let mut address = address?;
+    if round_id == 0 {
+        // one page from each map, merged back into a single ordered page
+        let legacy: StdResult<Vec<String>> = LEGACY_DEGEN_INFO
+            .keys(deps.storage, start, None, Order::Ascending)
+            .take(limit)
+            .map(string_from_key)
+            .collect();
+        address.extend(legacy?);
+        address.sort();
+        address.dedup();
+        address.truncate(limit);
+    }
     let rsp = DegenInfoResponse { wallets: address };
     to_binary(&rsp)
 }
+
+fn degen_burned(storage: &dyn Storage, round_id: u64, wallet: &str) -> StdResult<bool> {
+    if DEGEN_INFO
+        .may_load(storage, (round_id.into(), wallet))?
+        .unwrap_or(false)
+    {
+        return Ok(true);
+    }
+    Ok(round_id == 0
+        && LEGACY_DEGEN_INFO
+            .may_load(storage, wallet)?
+            .unwrap_or(false))
+}
+
+/// Winner amount still claimable by its winner
+fn open_prize(storage: &dyn Storage, env: &Env) -> StdResult<Option<WinnerInfo>> {
+    let time = env.block.time.nanos() / 1_000_000_000;
+    Ok(WINNER_INFO
+        .may_load(storage)?
+        .filter(|winner| !winner.claimed && winner.claim_end_time >= time))
+}
+
+fn get_state(deps: Deps, env: Env) -> StdResult<QueryResponse> {
+    let config = CONFIG.load(deps.storage)?;
+    let (balance, _, deposits) = round_deposits(deps, &env, Uint128::zero())?;
+    let prize_pool = match open_prize(deps.storage, &env)? {
+        Some(winner) => winner.winner_amount,
+        None => coin(0, balance.denom.clone()),
+    };
+    let rsp = StateResponse {
+        round_id: current_round_id(deps.storage)?,
+        tier_revenue: tier_revenue(deps, &config, distributable(deps, balance.amount)?)?,
+        pending_distribution: deposits
+            .iter()
+            .map(|deposit| deposit.payout.net.amount)
+            .sum(),
+        balance,
+        prize_pool,
+        prize_pools: PRIZE_POOL.may_load(deps.storage)?.unwrap_or_default(),
+    };
+    to_binary(&rsp)
+}
+
+fn get_wallet_status(deps: Deps, env: Env, address: String) -> StdResult<QueryResponse> {
+    let round_id = current_round_id(deps.storage)?;
+    let burned = degen_burned(deps.storage, round_id, &address)?;
+    let active = matches!(ROUND_INFO.may_load(deps.storage)?, Some(round) if round.active);
+    let is_winner = matches!(
+        WINNER_INFO.may_load(deps.storage)?,
+        Some(winner) if winner.winner_address == address
+    );
+    let prize_claimable = match open_prize(deps.storage, &env)? {
+        Some(winner) if winner.winner_address == address => {
+            Some(deduct_tax(deps, winner.winner_amount)?)
+        }
+        _ => None,
+    };
+    let tickets = WALLET_TICKETS
+        .may_load(
+            deps.storage,
+            (current_round_id(deps.storage)?.into(), address.as_str()),
+        )?
+        .unwrap_or_default();
+    let rsp = WalletStatusResponse {
+        address,
+        burned,
+        eligible: (burned || tickets > 0) && active,
+        is_winner,
+        prize_claimable,
+        tickets,
+    };
+    to_binary(&rsp)
+}
+
+fn get_staked_tokens(
+    deps: Deps,
+    _env: Env,
+    owner: String,
+    start_after: Option<String>,
+    limit: Option<u32>,
+) -> StdResult<QueryResponse> {
+    let config = CONFIG.load(deps.storage)?;
+    let limit = limit.unwrap_or(DEFAULT_LIMIT).min(MAX_LIMIT) as usize;
+    let start = start_after.map(Bound::exclusive);
+    let token_ids: StdResult<Vec<String>> = STAKED_BY_OWNER
+        .prefix(&owner)
+        .keys(deps.storage, start, None, Order::Ascending)
+        .take(limit)
+        .map(string_from_key)
+        .collect();
+    let mut tokens: Vec<StakedTokenInfo> = vec![];
+    for token_id in token_ids? {
+        let staked = STAKED_TOKENS.load(deps.storage, &token_id)?;
+        tokens.push(StakedTokenInfo {
+            token_id,
+            nft_type: staked.nft_type,
+            tickets: staked.tickets,
+            staked_at: staked.staked_at,
+            unlock_time: staked.unlock_time(config.unstake_cooldown),
+        });
+    }
+    let rsp = StakedTokensResponse {
+        tickets: STAKED_TICKETS
+            .may_load(deps.storage, &owner)?
+            .unwrap_or_default(),
+        owner,
+        tokens,
+    };
+    to_binary(&rsp)
+}
+
+fn get_tickets(deps: Deps, _env: Env, round_id: Option<u64>) -> StdResult<QueryResponse> {
+    let round_id = match round_id {
+        Some(round_id) => round_id,
+        None => current_round_id(deps.storage)?,
+    };
+    let rsp = TicketsResponse {
+        round_id,
+        total_tickets: ROUND_TICKETS
+            .may_load(deps.storage, round_id.into())?
+            .unwrap_or_default(),
+    };
+    to_binary(&rsp)
+}
+
+fn get_wallet_tickets(
+    deps: Deps,
+    _env: Env,
+    address: String,
+    round_id: Option<u64>,
+) -> StdResult<QueryResponse> {
+    let round_id = match round_id {
+        Some(round_id) => round_id,
+        None => current_round_id(deps.storage)?,
+    };
+    let staked = STAKED_TICKETS
+        .may_load(deps.storage, &address)?
+        .unwrap_or_default();
+    let entered = STAKE_ENTRIES
+        .may_load(deps.storage, (round_id.into(), address.as_str()))?
+        .unwrap_or_default();
+    let rsp = WalletTicketsResponse {
+        round_id,
+        tickets: WALLET_TICKETS
+            .may_load(deps.storage, (round_id.into(), address.as_str()))?
+            .unwrap_or_default(),
+        unentered_stake: staked.saturating_sub(entered),
+        address,
+    };
+    to_binary(&rsp)
+}
+
+/// The entry holding ticket `index`, the first one keyed by a running total above it
+fn ticket_entry(storage: &dyn Storage, round_id: u64, index: u64) -> StdResult<TicketEntry> {
+    let (_, entry) = TICKET_ENTRIES
+        .prefix(round_id.into())
+        .range(
+            storage,
+            Some(Bound::exclusive_int(index)),
+            None,
+            Order::Ascending,
+        )
+        .next()
+        .ok_or_else(|| StdError::not_found("ticket"))??;
+    Ok(entry)
+}
+
+fn get_ticket_owner(
+    deps: Deps,
+    _env: Env,
+    round_id: Option<u64>,
+    index: u64,
+) -> StdResult<QueryResponse> {
+    let round_id = match round_id {
+        Some(round_id) => round_id,
+        None => current_round_id(deps.storage)?,
+    };
+    let entry = ticket_entry(deps.storage, round_id, index)?;
+    let rsp = TicketOwnerResponse {
+        round_id,
+        index,
+        wallet: entry.wallet,
+        source: entry.source,
+    };
+    to_binary(&rsp)
+}
+
+fn get_prize_pool(deps: Deps, _env: Env) -> StdResult<QueryResponse> {
+    let rsp = PrizePoolResponse {
+        round_id: current_round_id(deps.storage)?,
+        pool: PRIZE_POOL.may_load(deps.storage)?.unwrap_or_default(),
+    };
+    to_binary(&rsp)
+}
+
+fn get_reserves(deps: Deps, env: Env) -> StdResult<QueryResponse> {
+    let pool = PRIZE_POOL.may_load(deps.storage)?.unwrap_or_default();
+    let balance = deps.querier.query_balance(env.contract.address, "uusd")?;
+    let held = pool.held();
+    let shares_owed = shares_owed(deps.storage)?;
+    let rsp = ReservesResponse {
+        free: balance.amount.saturating_sub(held + shares_owed),
+        solvent: balance.amount >= held + shares_owed,
+        shares_owed,
+        reserved: pool.reserved,
+        held,
+        balance,
+    };
+    to_binary(&rsp)
+}
+
+fn get_shares_owed(
+    deps: Deps,
+    _env: Env,
+    start_after: Option<String>,
+    limit: Option<u32>,
+) -> StdResult<QueryResponse> {
+    let limit = limit.unwrap_or(DEFAULT_LIMIT).min(MAX_LIMIT) as usize;
+    let start = start_after.map(Bound::exclusive);
+    let shares: StdResult<Vec<ShareOwed>> = SHARES_OWED
+        .range(deps.storage, start, None, Order::Ascending)
+        .take(limit)
+        .map(|item| {
+            let (key, amount) = item?;
+            Ok(ShareOwed {
+                recipient: string_from_key(key)?,
+                amount,
+            })
+        })
+        .collect();
+    let rsp = SharesOwedResponse { shares: shares? };
+    to_binary(&rsp)
+}
+
+fn get_round_prize(deps: Deps, _env: Env, round_id: u64) -> StdResult<QueryResponse> {
+    let rsp = RoundPrizeResponse {
+        round_id,
+        amount: ROUND_PRIZES.may_load(deps.storage, round_id.into())?,
+    };
+    to_binary(&rsp)
+}
+
+fn get_simulate_distribute_revenue(deps: Deps, env: Env) -> StdResult<QueryResponse> {
+    let (balance, prize_cut, deposits) = round_deposits(deps, &env, Uint128::zero())?;
+    let rsp = SimulateDistributeRevenueResponse {
+        round_id: current_round_id(deps.storage)?,
+        balance,
+        prize_cut,
+        deposits,
+    };
+    to_binary(&rsp)
+}
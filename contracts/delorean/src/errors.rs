@@ -1,33 +1,127 @@
-use cosmwasm_std::StdError;
-use snafu::Snafu;
+use cosmwasm_std::{StdError, Uint128};
+use delorean_common::errors::CommonError;
+use thiserror::Error;
 
-#[derive(Snafu, Debug)]
-#[snafu(visibility = "pub(crate)")]
+#[derive(Error, Debug)]
 pub enum ContractError {
-    /// this is needed so we can use `bucket.load(...)?` and have it auto-converted to the custom error
-    #[snafu(display("StdError: {}", original))]
-    Std { original: StdError },
-    #[snafu(display("Unauthorized"))]
-    Unauthorized { backtrace: Option<snafu::Backtrace> },
+    #[error("{0}")]
+    Std(#[from] StdError),
 
-    #[snafu(display("EmptyBalance"))]
-    EmptyBalance {},
+    #[error("{0}")]
+    Common(#[from] CommonError),
 
-    #[snafu(display("InsufficientFund"))]
-    InsufficientFund {},
+    #[error("NoFundsSent")]
+    NoFundsSent {},
 
-    #[snafu(display("MintLimitReached"))]
-    MintLimitReached {},
+    #[error("WrongDenom: expected {expected}, received {received}")]
+    WrongDenom { expected: String, received: String },
 
-    #[snafu(display("NotFound"))]
-    NotFound {},
+    #[error("WrongAmount: expected {expected}, received {received}")]
+    WrongAmount {
+        expected: Uint128,
+        received: Uint128,
+    },
 
-    #[snafu(display("PriceMismatch"))]
-    PriceMismatch {},
-}
+    #[error("InvalidNftType: {nft_type}, expected 1 to {tiers}")]
+    InvalidNftType { nft_type: u32, tiers: u32 },
+
+    #[error("NoWinner")]
+    NoWinner {},
+
+    #[error("NotWinner: {sender}")]
+    NotWinner { sender: String },
+
+    #[error("PrizeAlreadyClaimed")]
+    PrizeAlreadyClaimed {},
+
+    #[error("ClaimWindowClosed: ended at {claim_end_time}")]
+    ClaimWindowClosed { claim_end_time: u64 },
+
+    #[error("NotNftContract: {sender}")]
+    NotNftContract { sender: String },
+
+    #[error("NotTokenOwner: {sender} did not stake {token_id}")]
+    NotTokenOwner { sender: String, token_id: String },
+
+    #[error("TokenStaked: {token_id}")]
+    TokenStaked { token_id: String },
+
+    #[error("StakeLocked: {token_id} unlocks at {unlock_time}")]
+    StakeLocked { token_id: String, unlock_time: u64 },
+
+    #[error("TicketSalesClosed")]
+    TicketSalesClosed {},
+
+    #[error("InvalidTicketCount")]
+    InvalidTicketCount {},
+
+    #[error("NothingToEnter: no staked tickets left for round {round_id}")]
+    NothingToEnter { round_id: u64 },
+
+    #[error("PrizeExceedsPool: {amount} exceeds locked pool {pool}")]
+    PrizeExceedsPool { amount: Uint128, pool: Uint128 },
+
+    #[error("PrizeStillOpen: {winner} can claim until {claim_end_time}")]
+    PrizeStillOpen { winner: String, claim_end_time: u64 },
+
+    #[error("InsufficientReserve: prize {amount}, reserved {reserved}, balance {balance}")]
+    InsufficientReserve {
+        amount: Uint128,
+        reserved: Uint128,
+        balance: Uint128,
+    },
+
+    #[error("NothingOwed: {recipient}")]
+    NothingOwed { recipient: String },
+
+    #[error("RoundNotOpen: round {round_id} is closed")]
+    RoundNotOpen { round_id: u64 },
+
+    #[error("RoundNotClosed: round {round_id} is still open")]
+    RoundNotClosed { round_id: u64 },
+
+    #[error("RoundAlreadyDrawn: round {round_id}")]
+    RoundAlreadyDrawn { round_id: u64 },
+
+    #[error("DrawSeedCommitted: round {round_id}")]
+    DrawSeedCommitted { round_id: u64 },
+
+    #[error("NoDrawSeed: round {round_id} has no committed seed")]
+    NoDrawSeed { round_id: u64 },
+
+    #[error("DrawSeedMismatch: secret does not match the commitment for round {round_id}")]
+    DrawSeedMismatch { round_id: u64 },
+
+    #[error("DrawSeedNotRevealed: round {round_id}")]
+    DrawSeedNotRevealed { round_id: u64 },
+
+    #[error("TicketsFrozen: round {round_id} revealed its draw seed")]
+    TicketsFrozen { round_id: u64 },
+
+    #[error("StaleRound: round {round_id}, only round {latest} can be drawn")]
+    StaleRound { round_id: u64, latest: u64 },
+
+    #[error("NoTickets: round {round_id} issued no tickets")]
+    NoTickets { round_id: u64 },
+
+    #[error("EmptyPrizePool")]
+    EmptyPrizePool {},
+
+    #[error("RevenueAlreadyDistributed: round {round_id}")]
+    RevenueAlreadyDistributed { round_id: u64 },
+
+    #[error("RevenueNotDistributed: round {round_id}")]
+    RevenueNotDistributed { round_id: u64 },
+
+    #[error("AutomationDisabled")]
+    AutomationDisabled {},
+
+    #[error("NoRound")]
+    NoRound {},
+
+    #[error("RoundNotEnded: ends at {end_date}")]
+    RoundNotEnded { end_date: u64 },
 
-impl From<StdError> for ContractError {
-    fn from(original: StdError) -> Self {
-        Std { original }.build()
-    }
+    #[error("UnknownReply: {id}")]
+    UnknownReply { id: u64 },
 }
@@ -0,0 +1,113 @@
+use crate::errors::ContractError;
+use crate::msg::{MigrateMsg, TokenTier};
+use crate::ownership::{init_ownership, Role};
+use crate::state::{
+    Config, NftMetaInfo, PrizePool, RoundInfo, WinnerInfo, CONFIG, MIN_UNSTAKE_COOLDOWN, NFT_FUNDS,
+    PRIZE_POOL, ROUND_INFO, TOKEN_TIERS, WINNER_INFO,
+};
+use cosmwasm_std::{DepsMut, Response, StdResult, Storage, Uint128};
+use cw2::set_contract_version;
+use cw_storage_plus::Item;
+use delorean_common::versioning::{assert_upgrade, migrate_response};
+use semver::Version;
+use serde::{Deserialize, Serialize};
+
+pub const CONTRACT_NAME: &str = concat!("crates.io:", env!("CARGO_PKG_NAME"));
+pub const CONTRACT_VERSION: &str = env!("CARGO_PKG_VERSION");
+
+const LEGACY_CONFIG_KEY: &str = "config_1sd&23";
+const LEGACY_WINNERS_KEY: &str = "winners_1gasd2";
+const LEGACY_ROUNDINFO_KEY: &str = "round_info_12fas";
+const LEGACY_NFT2_FUNDS_KEY: &str = "nft2_funds_14fas";
+
+/// Config layout from before the admin moved into ownership
+#[derive(Serialize, Deserialize)]
+struct LegacyConfig {
+    admin: String,
+    mint_contract: String,
+    nft_contract: String,
+    nfts: Vec<NftMetaInfo>,
+}
+
+const LEGACY_CONFIG: Item<LegacyConfig> = Item::new(LEGACY_CONFIG_KEY);
+const LEGACY_WINNER_INFO: Item<WinnerInfo> = Item::new(LEGACY_WINNERS_KEY);
+const LEGACY_ROUND_INFO: Item<RoundInfo> = Item::new(LEGACY_ROUNDINFO_KEY);
+const LEGACY_NFT2_FUNDS: Item<Uint128> = Item::new(LEGACY_NFT2_FUNDS_KEY);
+
+pub fn set_version(storage: &mut dyn Storage) -> StdResult<()> {
+    set_contract_version(storage, CONTRACT_NAME, CONTRACT_VERSION)
+}
+
+pub fn migrate_store(mut deps: DepsMut, msg: MigrateMsg) -> Result<Response, ContractError> {
+    let stored = assert_upgrade(deps.storage, CONTRACT_NAME, CONTRACT_VERSION)?;
+    if stored < Version::new(0, 2, 0) {
+        migrate_from_legacy(deps.branch())?;
+    }
+    backfill_token_tiers(deps.storage, msg.token_tiers)?;
+
+    set_version(deps.storage)?;
+    Ok(migrate_response(CONTRACT_NAME, &stored, CONTRACT_VERSION))
+}
+
+/// Moves the salted keys to plain ones, the second tier's funds into the
+/// per tier map and the admin out of the config. Degens stay where they are,
+/// there can be any number of them and the contract reads them as round 0
+fn migrate_from_legacy(mut deps: DepsMut) -> Result<(), ContractError> {
+    let legacy = LEGACY_CONFIG.load(deps.storage)?;
+    init_ownership::<Role>(deps.branch(), legacy.admin, vec![])?;
+    CONFIG.save(
+        deps.storage,
+        &Config {
+            mint_contract: legacy.mint_contract,
+            nft_contract: legacy.nft_contract,
+            nfts: legacy.nfts,
+            unstake_cooldown: MIN_UNSTAKE_COOLDOWN,
+            prize_share: 0,
+            round_duration: 0,
+            keeper_reward: Uint128::zero(),
+        },
+    )?;
+    LEGACY_CONFIG.remove(deps.storage);
+
+    if let Some(winner) = LEGACY_WINNER_INFO.may_load(deps.storage)? {
+        // the unversioned contract paid prizes straight from its balance
+        if !winner.claimed {
+            let pool = PrizePool {
+                reserved: winner.winner_amount.amount,
+                ..PrizePool::default()
+            };
+            PRIZE_POOL.save(deps.storage, &pool)?;
+        }
+        WINNER_INFO.save(deps.storage, &winner)?;
+        LEGACY_WINNER_INFO.remove(deps.storage);
+    }
+    if let Some(round) = LEGACY_ROUND_INFO.may_load(deps.storage)? {
+        ROUND_INFO.save(deps.storage, &round)?;
+        LEGACY_ROUND_INFO.remove(deps.storage);
+    }
+
+    if let Some(nft2_funds) = LEGACY_NFT2_FUNDS.may_load(deps.storage)? {
+        if !nft2_funds.is_zero() {
+            NFT_FUNDS.save(deps.storage, 1u32.into(), &nft2_funds)?;
+        }
+        LEGACY_NFT2_FUNDS.remove(deps.storage);
+    }
+    Ok(())
+}
+
+/// Records the tiers of nfts minted before the mint reply did, so they
+/// stake at their tier weight
+fn backfill_token_tiers(
+    storage: &mut dyn Storage,
+    token_tiers: Vec<TokenTier>,
+) -> Result<(), ContractError> {
+    let tiers = CONFIG.load(storage)?.nfts.len() as u32;
+    for token_tier in token_tiers {
+        let nft_type = token_tier.nft_type;
+        if nft_type == 0 || nft_type > tiers {
+            return Err(ContractError::InvalidNftType { nft_type, tiers });
+        }
+        TOKEN_TIERS.save(storage, &token_tier.token_id, &nft_type)?;
+    }
+    Ok(())
+}
@@ -1,25 +1,127 @@
-use crate::state::{Config, Metadata, RoundInfo, WinnerInfo};
+use crate::ownership::{Role, RoleGrant};
+use crate::state::{Config, Metadata, NftMetaInfo, PrizePool, RoundInfo, TicketSource, WinnerInfo};
+use cosmwasm_std::{Binary, Coin, Uint128};
+pub use delorean_common::ownership::OwnershipResponse;
+pub use delorean_common::payout::Payout;
 use schemars::JsonSchema;
 use serde::{Deserialize, Serialize};
 
 #[derive(Serialize, Deserialize, Clone, Debug, PartialEq, JsonSchema)]
 pub struct InstantiateMsg {
     pub config: Config,
+    /// Defaults to the instantiating wallet
+    pub admin: Option<String>,
+    #[serde(default)]
+    pub roles: Vec<RoleGrant>,
 }
 
 #[derive(Serialize, Deserialize, Clone, Debug, PartialEq, JsonSchema)]
 #[serde(rename_all = "snake_case")]
 pub enum ExecuteMsg {
-    ConfigUpdate { config: Config },
-    WinnerUpdate { winner: WinnerInfo },
-    RoundUpdate { round_info: RoundInfo },
-    ClaimPrize { burn_nft_id: String },
-    Degen { burn_nft_id: String },
-    Mint { nft_type: u32 },
+    UpdateConfig {
+        config: ConfigUpdate,
+    },
+    ProposeNewAdmin {
+        new_admin: String,
+    },
+    AcceptAdmin {},
+    RenounceAdmin {},
+    GrantRole {
+        role: Role,
+        address: String,
+    },
+    RevokeRole {
+        role: Role,
+        address: String,
+    },
+    WinnerUpdate {
+        winner: WinnerInfo,
+    },
+    /// Opens the next round once the current one is closed and distributed
+    StartRound {
+        round_info: RoundInfo,
+    },
+    /// Replaces the open round's metadata, moves no funds
+    EditRound {
+        round_info: RoundInfo,
+    },
+    /// Ends ticket sales and locks the prize pool for the round
+    CloseRound {},
+    /// Credits the closed round's revenue to its shares and prize pool
+    DistributeRevenue {},
+    /// Makes the holder of the ticket the round's revealed seed picks the
+    /// winner of that round's prize, for the last closed round
+    DrawWinner {
+        round_id: u64,
+        claim_end_time: u64,
+    },
+    /// Commits the sha256 of the secret a current or future round is drawn with
+    CommitDrawSeed {
+        round_id: u64,
+        commitment: Binary,
+    },
+    /// Anyone holding the committed secret may reveal it once the round has
+    /// ended, the round issues no tickets after that
+    RevealDrawSeed {
+        round_id: u64,
+        secret: String,
+    },
+    /// Anyone may send this once the round has ended, it closes and
+    /// distributes the round, draws it if its seed is revealed and opens the
+    /// next round
+    Tick {},
+    ClaimPrize {
+        burn_nft_id: String,
+    },
+    Degen {
+        burn_nft_id: String,
+    },
+    Mint {
+        nft_type: u32,
+    },
+    /// cw721 send hook, stakes the nft for draw tickets
+    ReceiveNft(Cw721ReceiveMsg),
+    UnstakeNft {
+        token_id: String,
+    },
+    /// Buys tickets for the current round at its ticket price
+    BuyTickets {
+        count: u64,
+    },
+    /// Enters the sender's staked tickets into the current round
+    EnterRound {},
+    /// Adds the sent uusd to the current round's prize pool, e.g. harvested yield
+    FundPrizePool {},
+    /// Deposits the recipient's credited share revenue into it, callable by anyone
+    ClaimShare {
+        recipient: String,
+    },
+}
+
+/// Only the provided fields are changed
+#[derive(Serialize, Deserialize, Clone, Debug, PartialEq, JsonSchema, Default)]
+pub struct ConfigUpdate {
+    pub mint_contract: Option<String>,
+    pub nft_contract: Option<String>,
+    pub nfts: Option<Vec<NftMetaInfo>>,
+    pub unstake_cooldown: Option<u64>,
+    pub prize_share: Option<u32>,
+    pub round_duration: Option<u64>,
+    pub keeper_reward: Option<Uint128>,
+}
+
+#[derive(Serialize, Deserialize, Clone, Debug, PartialEq, JsonSchema, Default)]
+pub struct MigrateMsg {
+    /// Tiers of nfts minted before the mint reply recorded them
+    #[serde(default)]
+    pub token_tiers: Vec<TokenTier>,
 }
 
 #[derive(Serialize, Deserialize, Clone, Debug, PartialEq, JsonSchema)]
-pub struct MigrateMsg {}
+pub struct TokenTier {
+    pub token_id: String,
+    pub nft_type: u32,
+}
 
 #[derive(Serialize, Deserialize, Clone, Debug, PartialEq, JsonSchema)]
 #[serde(rename_all = "snake_case")]
@@ -41,15 +143,65 @@ pub enum SecureMintMsg {
 #[serde(rename_all = "snake_case")]
 pub enum Cw721ExecuteMsg {
     Burn { token_id: String },
+    TransferNft { recipient: String, token_id: String },
+}
+
+#[derive(Serialize, Deserialize, Clone, Debug, PartialEq, JsonSchema)]
+pub struct Cw721ReceiveMsg {
+    pub sender: String,
+    pub token_id: String,
+    pub msg: Binary,
 }
 
 #[derive(Serialize, Deserialize, Clone, Debug, PartialEq, JsonSchema)]
 #[serde(rename_all = "snake_case")]
 pub enum QueryMsg {
+    Ownership {},
+    RoleMembers {
+        role: Role,
+    },
     Config {},
     Winners {},
     RoundInfo {},
-    DegenInfo {},
+    DegenInfo {
+        start_after: Option<String>,
+        limit: Option<u32>,
+    },
+    State {},
+    WalletStatus {
+        address: String,
+    },
+    SimulateDistributeRevenue {},
+    StakedTokens {
+        owner: String,
+        start_after: Option<String>,
+        limit: Option<u32>,
+    },
+    /// Defaults to the current round
+    Tickets {
+        round_id: Option<u64>,
+    },
+    /// Defaults to the current round
+    WalletTickets {
+        address: String,
+        round_id: Option<u64>,
+    },
+    /// Wallet holding ticket `index` of the round, defaults to the current round
+    TicketOwner {
+        round_id: Option<u64>,
+        index: u64,
+    },
+    PrizePool {},
+    /// Prize funds against the bank balance
+    Reserves {},
+    SharesOwed {
+        start_after: Option<String>,
+        limit: Option<u32>,
+    },
+    /// Pool locked when the round closed
+    RoundPrize {
+        round_id: u64,
+    },
 }
 
 #[derive(Serialize, Deserialize, Clone, Debug, PartialEq, JsonSchema)]
@@ -57,6 +209,8 @@ pub struct ConfigResponse {
     pub config: Config,
 }
 
+pub type RoleMembersResponse = delorean_common::ownership::RoleMembersResponse<Role>;
+
 #[derive(Serialize, Deserialize, Clone, Debug, PartialEq, JsonSchema)]
 pub struct WinnersResponse {
     pub winner: Option<WinnerInfo>,
@@ -64,6 +218,7 @@ pub struct WinnersResponse {
 
 #[derive(Serialize, Deserialize, Clone, Debug, PartialEq, JsonSchema)]
 pub struct RoundInfoResponse {
+    pub round_id: u64,
     pub round: Option<RoundInfo>,
 }
 
@@ -71,3 +226,123 @@ pub struct RoundInfoResponse {
 pub struct DegenInfoResponse {
     pub wallets: Vec<String>,
 }
+
+#[derive(Serialize, Deserialize, Clone, Debug, PartialEq, JsonSchema)]
+pub struct StateResponse {
+    pub round_id: u64,
+    pub balance: Coin,
+    /// Unclaimed winner amount still inside its claim window
+    pub prize_pool: Coin,
+    /// Revenue per tier that the next distribution splits, indexed by nft_type - 1
+    pub tier_revenue: Vec<Uint128>,
+    /// Sum of the shares the next distribution credits, after tax
+    pub pending_distribution: Uint128,
+    pub prize_pools: PrizePool,
+}
+
+#[derive(Serialize, Deserialize, Clone, Debug, PartialEq, JsonSchema)]
+pub struct WalletStatusResponse {
+    pub address: String,
+    /// Burned an nft into the current round
+    pub burned: bool,
+    /// Burned and the current round is active
+    pub eligible: bool,
+    pub is_winner: bool,
+    /// Prize the wallet can claim right now, after tax
+    pub prize_claimable: Option<Coin>,
+    /// Tickets held in the current round
+    pub tickets: u64,
+}
+
+#[derive(Serialize, Deserialize, Clone, Debug, PartialEq, JsonSchema)]
+pub struct ShareDeposit {
+    pub nft_type: u32,
+    pub note: String,
+    pub payout: Payout,
+}
+
+#[derive(Serialize, Deserialize, Clone, Debug, PartialEq, JsonSchema)]
+pub struct SimulateDistributeRevenueResponse {
+    pub round_id: u64,
+    pub balance: Coin,
+    /// Revenue the distribution moves into the prize pool
+    pub prize_cut: Uint128,
+    pub deposits: Vec<ShareDeposit>,
+}
+
+#[derive(Serialize, Deserialize, Clone, Debug, PartialEq, JsonSchema)]
+pub struct StakedTokenInfo {
+    pub token_id: String,
+    pub nft_type: u32,
+    pub tickets: u64,
+    pub staked_at: u64,
+    pub unlock_time: u64,
+}
+
+#[derive(Serialize, Deserialize, Clone, Debug, PartialEq, JsonSchema)]
+pub struct StakedTokensResponse {
+    pub owner: String,
+    /// Tickets across all of the owner's staked nfts
+    pub tickets: u64,
+    pub tokens: Vec<StakedTokenInfo>,
+}
+
+#[derive(Serialize, Deserialize, Clone, Debug, PartialEq, JsonSchema)]
+pub struct TicketsResponse {
+    pub round_id: u64,
+    pub total_tickets: u64,
+}
+
+#[derive(Serialize, Deserialize, Clone, Debug, PartialEq, JsonSchema)]
+pub struct WalletTicketsResponse {
+    pub round_id: u64,
+    pub address: String,
+    pub tickets: u64,
+    /// Staked tickets not yet entered into the round
+    pub unentered_stake: u64,
+}
+
+#[derive(Serialize, Deserialize, Clone, Debug, PartialEq, JsonSchema)]
+pub struct TicketOwnerResponse {
+    pub round_id: u64,
+    pub index: u64,
+    pub wallet: String,
+    pub source: TicketSource,
+}
+
+#[derive(Serialize, Deserialize, Clone, Debug, PartialEq, JsonSchema)]
+pub struct PrizePoolResponse {
+    pub round_id: u64,
+    pub pool: PrizePool,
+}
+
+#[derive(Serialize, Deserialize, Clone, Debug, PartialEq, JsonSchema)]
+pub struct ReservesResponse {
+    pub balance: Coin,
+    /// Open, locked and reserved prize funds
+    pub held: Uint128,
+    /// Reserved for the current winner
+    pub reserved: Uint128,
+    /// Share revenue credited but not yet claimed
+    pub shares_owed: Uint128,
+    /// Balance a distribution may split
+    pub free: Uint128,
+    pub solvent: bool,
+}
+
+#[derive(Serialize, Deserialize, Clone, Debug, PartialEq, JsonSchema)]
+pub struct RoundPrizeResponse {
+    pub round_id: u64,
+    pub amount: Option<Uint128>,
+}
+
+#[derive(Serialize, Deserialize, Clone, Debug, PartialEq, JsonSchema)]
+pub struct ShareOwed {
+    pub recipient: String,
+    pub amount: Uint128,
+}
+
+#[derive(Serialize, Deserialize, Clone, Debug, PartialEq, JsonSchema)]
+pub struct SharesOwedResponse {
+    pub shares: Vec<ShareOwed>,
+}
@@ -0,0 +1,29 @@
+use schemars::JsonSchema;
+use serde::{Deserialize, Serialize};
+
+pub use delorean_common::ownership::{
+    assert_admin, assert_role, get_ownership, get_role_members, has_role, init_ownership, is_admin,
+    try_accept_admin, try_grant_role, try_propose_new_admin, try_renounce_admin, try_revoke_role,
+    unauthorized, Ownership, RoleName, OWNERSHIP, ROLES,
+};
+
+/// The roles this contract checks, the admin holds all of them
+#[derive(Serialize, Deserialize, Clone, Debug, PartialEq, JsonSchema)]
+#[serde(rename_all = "snake_case")]
+pub enum Role {
+    ConfigManager,
+    RoundOperator,
+    TreasuryOperator,
+}
+
+impl RoleName for Role {
+    fn as_str(&self) -> &str {
+        match self {
+            Role::ConfigManager => "config_manager",
+            Role::RoundOperator => "round_operator",
+            Role::TreasuryOperator => "treasury_operator",
+        }
+    }
+}
+
+pub type RoleGrant = delorean_common::ownership::RoleGrant<Role>;
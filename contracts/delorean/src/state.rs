@@ -1,21 +1,63 @@
 use schemars::JsonSchema;
 use serde::{Deserialize, Serialize};
 
-use cosmwasm_std::{Coin, Uint128};
-use cw_storage_plus::{Item, Map};
+use cosmwasm_std::{Binary, Coin, Uint128};
+use cw_storage_plus::{Item, Map, U32Key, U64Key};
 
-const CONFIG_KEY: &str = "config_1sd&23";
-const WINNERS_KEY: &str = "winners_1gasd2";
-const ROUNDINFO_KEY: &str = "round_info_12fas";
-const DEGEN_KEY: &str = "degen_state_12fas";
-const NFT2_FUNDS_KEY: &str = "nft2_funds_14fas";
+const CONFIG_KEY: &str = "config";
+const WINNERS_KEY: &str = "winners";
+const ROUNDINFO_KEY: &str = "round_info";
+const DEGEN_KEY: &str = "round_degens";
+const NFT_FUNDS_KEY: &str = "nft_funds";
+const ROUND_ID_KEY: &str = "round_id";
+const PENDING_MINT_KEY: &str = "pending_mint";
+const TOKEN_TIERS_KEY: &str = "token_tiers";
+const STAKED_TOKENS_KEY: &str = "staked_tokens";
+const STAKED_BY_OWNER_KEY: &str = "staked_by_owner";
+const STAKED_TICKETS_KEY: &str = "staked_tickets";
+const TOTAL_STAKED_KEY: &str = "total_staked";
+const TICKET_ENTRIES_KEY: &str = "ticket_entries";
+const ROUND_TICKETS_KEY: &str = "round_tickets";
+const WALLET_TICKETS_KEY: &str = "wallet_tickets";
+const STAKE_ENTRIES_KEY: &str = "stake_entries";
+const PRIZE_POOL_KEY: &str = "prize_pool";
+const ROUND_PRIZES_KEY: &str = "round_prizes";
+const SHARES_OWED_KEY: &str = "shares_owed";
+const TOTAL_SHARES_OWED_KEY: &str = "total_shares_owed";
+const ROUND_STATE_KEY: &str = "round_state";
+const LEGACY_DEGEN_KEY: &str = "degen_state_12fas";
+const UNSTAKED_ROUND_KEY: &str = "unstaked_round";
+const ROUND_DRAWS_KEY: &str = "round_draws";
+const DRAW_SEEDS_KEY: &str = "draw_seeds";
+
+/// Shortest unstake_cooldown a config may set, so a staked nft cannot move
+/// between wallets within a round
+pub const MIN_UNSTAKE_COOLDOWN: u64 = 86400;
 
 #[derive(Serialize, Deserialize, Clone, Debug, PartialEq, JsonSchema)]
 pub struct Config {
-    pub admin: String,
     pub mint_contract: String,
     pub nft_contract: String,
     pub nfts: Vec<NftMetaInfo>,
+    /// Seconds a staked nft stays locked before it can be unstaked
+    #[serde(default)]
+    pub unstake_cooldown: u64,
+    /// Part of each tier's revenue set aside for the prize pool, in decimal of 100
+    #[serde(default)]
+    pub prize_share: u32,
+    /// Seconds each round opened by Tick runs for, 0 leaves rounds to the operator
+    #[serde(default)]
+    pub round_duration: u64,
+    /// uusd paid to whoever sends a Tick that advances the round, capped at
+    /// the first tier's revenue
+    #[serde(default)]
+    pub keeper_reward: Uint128,
+}
+
+impl Config {
+    pub fn prize_cut(&self, revenue: Uint128) -> Uint128 {
+        revenue.multiply_ratio(self.prize_share, 10000u32)
+    }
 }
 
 #[derive(Serialize, Deserialize, Clone, Debug, PartialEq, JsonSchema)]
@@ -23,6 +65,13 @@ pub struct NftMetaInfo {
     pub nft_metadata: Metadata,
     pub price: Coin,
     pub shares: Vec<FundShare>,
+    /// Draw tickets a staked nft of this tier counts for
+    #[serde(default = "default_ticket_weight")]
+    pub ticket_weight: u32,
+}
+
+fn default_ticket_weight() -> u32 {
+    1
 }
 
 #[derive(Serialize, Deserialize, Clone, Debug, PartialEq, JsonSchema)]
@@ -31,6 +80,9 @@ pub struct RoundInfo {
     pub start_date: u64,
     pub end_date: u64,
     pub name: String,
+    /// None when tickets are not on sale this round
+    #[serde(default)]
+    pub ticket_price: Option<Coin>,
 }
 
 #[derive(Serialize, Deserialize, Clone, Debug, PartialEq, JsonSchema)]
@@ -76,8 +128,131 @@ pub struct WinnerInfo {
     pub claimed: bool,
 }
 
+#[derive(Serialize, Deserialize, Clone, Debug, PartialEq, JsonSchema)]
+pub struct StakedToken {
+    pub owner: String,
+    /// 0 for nfts minted outside this contract
+    pub nft_type: u32,
+    /// Tier weight when staked, so a config change does not skew the totals
+    pub tickets: u64,
+    pub staked_at: u64,
+}
+
+impl StakedToken {
+    pub fn unlock_time(&self, cooldown: u64) -> u64 {
+        self.staked_at + cooldown
+    }
+}
+
+#[derive(Serialize, Deserialize, Clone, Debug, PartialEq, JsonSchema)]
+#[serde(rename_all = "snake_case")]
+pub enum TicketSource {
+    Purchase,
+    Burn,
+    Stake,
+}
+
+impl TicketSource {
+    pub fn as_str(&self) -> &str {
+        match self {
+            TicketSource::Purchase => "purchase",
+            TicketSource::Burn => "burn",
+            TicketSource::Stake => "stake",
+        }
+    }
+}
+
+/// A run of consecutive ticket indexes issued to one wallet
+#[derive(Serialize, Deserialize, Clone, Debug, PartialEq, JsonSchema)]
+pub struct TicketEntry {
+    pub wallet: String,
+    pub count: u64,
+    pub source: TicketSource,
+}
+
+/// Funds held back from share distribution for prizes
+#[derive(Serialize, Deserialize, Clone, Debug, PartialEq, JsonSchema, Default)]
+pub struct PrizePool {
+    /// Funded during the current round, locked when it closes
+    pub open: Uint128,
+    /// Payable to winners, what is left rolls into the next lock
+    pub locked: Uint128,
+    /// Taken out of locked for the current winner until claimed or expired
+    #[serde(default)]
+    pub reserved: Uint128,
+}
+
+impl PrizePool {
+    pub fn held(&self) -> Uint128 {
+        self.open + self.locked + self.reserved
+    }
+
+    /// Hands a reservation back to the locked pool
+    pub fn release(&mut self) -> Uint128 {
+        let released = self.reserved;
+        self.locked += released;
+        self.reserved = Uint128::zero();
+        released
+    }
+}
+
+/// Where the current round is between StartRound and the next one
+#[derive(Serialize, Deserialize, Clone, Debug, PartialEq, JsonSchema, Default)]
+pub struct RoundState {
+    pub closed: bool,
+    pub distributed: bool,
+}
+
+/// Seed a round is drawn with. The round operator commits its sha256 before
+/// the round's tickets are final, revealing it stops the round's tickets.
+#[derive(Serialize, Deserialize, Clone, Debug, PartialEq, JsonSchema)]
+pub struct DrawSeed {
+    pub commitment: Binary,
+    pub secret: Option<String>,
+}
+
 pub const CONFIG: Item<Config> = Item::new(CONFIG_KEY);
 pub const ROUND_INFO: Item<RoundInfo> = Item::new(ROUNDINFO_KEY);
+// bumped by every StartRound, 0 until the first one
+pub const ROUND_ID: Item<u64> = Item::new(ROUND_ID_KEY);
 pub const WINNER_INFO: Item<WinnerInfo> = Item::new(WINNERS_KEY);
-pub const DEGEN_INFO: Map<String, bool> = Map::new(DEGEN_KEY);
-pub const NFT2_FUNDS: Item<Uint128> = Item::new(NFT2_FUNDS_KEY);
+// (round id, wallet) -> burned, a new round starts with an empty prefix
+pub const DEGEN_INFO: Map<(U64Key, &str), bool> = Map::new(DEGEN_KEY);
+// wallet -> burned, written before versioning and read as round 0 rather
+// than moved by the migration. These burns predate the ticket ledger and
+// hold no draw tickets.
+pub const LEGACY_DEGEN_INFO: Map<&str, bool> = Map::new(LEGACY_DEGEN_KEY);
+// tier index -> mint revenue held for that tier's shares until the round closes,
+// tier 0 is not tracked and takes the rest of the balance
+pub const NFT_FUNDS: Map<U32Key, Uint128> = Map::new(NFT_FUNDS_KEY);
+// tier of the nft a Mint is waiting on, cleared by the mint reply
+pub const PENDING_MINT: Item<u32> = Item::new(PENDING_MINT_KEY);
+// token id -> nft_type, for every nft minted through this contract
+pub const TOKEN_TIERS: Map<&str, u32> = Map::new(TOKEN_TIERS_KEY);
+pub const STAKED_TOKENS: Map<&str, StakedToken> = Map::new(STAKED_TOKENS_KEY);
+// (owner, token id) -> staked
+pub const STAKED_BY_OWNER: Map<(&str, &str), bool> = Map::new(STAKED_BY_OWNER_KEY);
+// owner -> tickets across the owner's staked nfts
+pub const STAKED_TICKETS: Map<&str, u64> = Map::new(STAKED_TICKETS_KEY);
+pub const TOTAL_STAKED: Item<u64> = Item::new(TOTAL_STAKED_KEY);
+// (round id, running total after the entry) -> entry, so the entry holding
+// ticket index i is the first one keyed above i
+pub const TICKET_ENTRIES: Map<(U64Key, U64Key), TicketEntry> = Map::new(TICKET_ENTRIES_KEY);
+// round id -> tickets issued so far
+pub const ROUND_TICKETS: Map<U64Key, u64> = Map::new(ROUND_TICKETS_KEY);
+pub const WALLET_TICKETS: Map<(U64Key, &str), u64> = Map::new(WALLET_TICKETS_KEY);
+// (round id, owner) -> staked tickets already entered into the round
+pub const STAKE_ENTRIES: Map<(U64Key, &str), u64> = Map::new(STAKE_ENTRIES_KEY);
+// token id -> round the token was last unstaked in, restaking it in that
+// round enters no tickets
+pub const UNSTAKED_ROUND: Map<&str, u64> = Map::new(UNSTAKED_ROUND_KEY);
+pub const PRIZE_POOL: Item<PrizePool> = Item::new(PRIZE_POOL_KEY);
+// round id -> pool locked when the round closed
+pub const ROUND_PRIZES: Map<U64Key, Uint128> = Map::new(ROUND_PRIZES_KEY);
+// round id -> ticket index drawn for the round, a round is drawn at most once
+pub const ROUND_DRAWS: Map<U64Key, u64> = Map::new(ROUND_DRAWS_KEY);
+pub const DRAW_SEEDS: Map<U64Key, DrawSeed> = Map::new(DRAW_SEEDS_KEY);
+// recipient -> share revenue credited by round closes, paid out on ClaimShare
+pub const SHARES_OWED: Map<&str, Uint128> = Map::new(SHARES_OWED_KEY);
+pub const TOTAL_SHARES_OWED: Item<Uint128> = Item::new(TOTAL_SHARES_OWED_KEY);
+pub const ROUND_STATE: Item<RoundState> = Item::new(ROUND_STATE_KEY);
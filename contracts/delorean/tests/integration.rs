@@ -1,17 +1,44 @@
-use cosmwasm_std::{coin, from_binary, ContractResult, Response};
+use cosmwasm_std::{
+    coin, from_binary, to_vec, BankMsg, Binary, ContractResult, CosmosMsg, Event, Reply, ReplyOn,
+    Response, SubMsgExecutionResponse, Uint128, WasmMsg,
+};
 use cosmwasm_vm::testing::{
-    execute, instantiate, migrate, mock_env, mock_info, mock_instance_options, query,
+    execute, instantiate, migrate, mock_env, mock_info, mock_instance_options, query, reply,
 };
-use cosmwasm_vm::Instance;
+use cosmwasm_vm::{Instance, Storage};
 use delorean_app::msg::{
-    ConfigResponse, DegenInfoResponse, ExecuteMsg, InstantiateMsg, MigrateMsg, QueryMsg,
+    ConfigResponse, ConfigUpdate, Cw721ReceiveMsg, DegenInfoResponse, ExecuteMsg, InstantiateMsg,
+    MigrateMsg, OwnershipResponse, PrizePoolResponse, QueryMsg, ReservesResponse,
+    RoundInfoResponse, SharesOwedResponse, SimulateDistributeRevenueResponse, StakedTokensResponse,
+    TicketOwnerResponse, TicketsResponse, TokenTier, WalletStatusResponse, WalletTicketsResponse,
+    WinnersResponse,
+};
+use delorean_app::ownership::{Role, RoleGrant};
+use delorean_app::state::{
+    Config, FundShare, Metadata, NftMetaInfo, PrizePool, RoundInfo, TicketSource, WinnerInfo,
+    MIN_UNSTAKE_COOLDOWN,
 };
-use delorean_app::state::{Config, FundShare, Metadata, NftMetaInfo, RoundInfo, WinnerInfo};
 use mock_tax::mock_dependencies::mock_dependencies_with_custom_querier;
+use sha2::{Digest, Sha256};
 
 static WASM: &[u8] =
     include_bytes!("../../../target/wasm32-unknown-unknown/release/delorean_app.wasm");
 
+/// Attributes of each event of type `ty`, in emission order
+fn events<'a>(rsp: &'a Response, ty: &str) -> Vec<Vec<(&'a str, &'a str)>> {
+    rsp.events
+        .iter()
+        .filter(|event| event.ty == ty)
+        .map(|event| {
+            event
+                .attributes
+                .iter()
+                .map(|attr| (attr.key.as_str(), attr.value.as_str()))
+                .collect()
+        })
+        .collect()
+}
+
 #[test]
 fn delorean_distributer_test() {
     let backend = mock_dependencies_with_custom_querier(&[coin(900000000u128, "uusd".to_string())]);
@@ -24,7 +51,6 @@ fn delorean_distributer_test() {
     let mut user2_info = mock_info(&String::from("user2"), &[coin(10000u128, "uusd")].to_vec());
 
     let config = Config {
-        admin: admin.clone(),
         mint_contract: "xx1".to_string(),
         nft_contract: "xx2".to_string(),
         nfts: vec![
@@ -53,6 +79,7 @@ fn delorean_distributer_test() {
                         share: 6000u32, //40.00
                     },
                 ],
+                ticket_weight: 1,
             },
             NftMetaInfo {
                 nft_metadata: Metadata {
@@ -79,12 +106,22 @@ fn delorean_distributer_test() {
                         share: 4000u32, //40.00
                     },
                 ],
+                ticket_weight: 1,
             },
         ],
+        unstake_cooldown: MIN_UNSTAKE_COOLDOWN,
+        prize_share: 0,
+        round_duration: 0,
+        keeper_reward: Uint128::zero(),
     };
 
     let instatiate_msg = InstantiateMsg {
         config: config.clone(),
+        admin: None,
+        roles: vec![RoleGrant {
+            role: Role::RoundOperator,
+            address: String::from("operator"),
+        }],
     };
 
     let (instance_options, memory_limit) = mock_instance_options();
@@ -97,14 +134,17 @@ fn delorean_distributer_test() {
 
     assert_eq!(config_rsp.config, config, "Config updated successfully");
 
-    let update_config_msg = ExecuteMsg::ConfigUpdate {
-        config: config.clone(),
+    let update_config_msg = ExecuteMsg::UpdateConfig {
+        config: ConfigUpdate {
+            nfts: Some(config.nfts.clone()),
+            ..ConfigUpdate::default()
+        },
     };
     let rsp: ContractResult<Response> =
         execute(&mut deps, mock_env(), user1_info.clone(), update_config_msg);
     assert_eq!(
         rsp.unwrap_err(),
-        "Unauthorized",
+        "Unauthorized: user1 is not config_manager",
         "Config update should faill on unauthorized access"
     );
 
@@ -112,8 +152,11 @@ fn delorean_distributer_test() {
         &mut deps,
         mock_env(),
         admin_info.clone(),
-        ExecuteMsg::ConfigUpdate {
-            config: config.clone(),
+        ExecuteMsg::UpdateConfig {
+            config: ConfigUpdate {
+                nfts: Some(config.nfts.clone()),
+                ..ConfigUpdate::default()
+            },
         },
     );
     assert_eq!(
@@ -121,8 +164,9 @@ fn delorean_distributer_test() {
         false,
         "Config update should pass on authorized access"
     );
+    assert_eq!(events(&rsp.unwrap(), "config-updated")[0][0].0, "nfts");
 
-    let rsp: ContractResult<Response> = migrate(&mut deps, mock_env(), MigrateMsg {});
+    let rsp: ContractResult<Response> = migrate(&mut deps, mock_env(), MigrateMsg::default());
     assert_eq!(rsp.is_err(), false, "Migrate should work");
 
     let rsp: ContractResult<Response> = execute(
@@ -133,7 +177,7 @@ fn delorean_distributer_test() {
     );
     assert_eq!(
         rsp.unwrap_err(),
-        "InsufficientFund",
+        "WrongAmount: expected 150000000, received 10000",
         "InsufficientFund test on mint of nft 1"
     );
 
@@ -149,6 +193,16 @@ fn delorean_distributer_test() {
         false,
         "Mint with sufficient fund passes for nft 1"
     );
+    assert_eq!(
+        events(&rsp.unwrap(), "delorean-mint"),
+        vec![vec![
+            ("round_id", "0"),
+            ("wallet", "user1"),
+            ("nft_type", "1"),
+            ("amount", "150000000"),
+            ("denom", "uusd"),
+        ]]
+    );
 
     let rsp: ContractResult<Response> = execute(
         &mut deps,
@@ -181,10 +235,91 @@ fn delorean_distributer_test() {
     );
     assert_eq!(
         rsp.unwrap_err(),
-        "NotFound",
+        "NoWinner",
         "Claim prize should not work if winner not set"
     );
 
+    let rsp: ContractResult<Response> = execute(
+        &mut deps,
+        mock_env(),
+        admin_info.clone(),
+        ExecuteMsg::WinnerUpdate {
+            winner: WinnerInfo {
+                winner_address: "user1".to_string(),
+                winner_amount: coin(700000000u128, "uusd".to_string()),
+                claimed: false,
+                claim_end_time: (mock_env().block.time.nanos() / 1_000_000_000) + 1000,
+            },
+        },
+    );
+    assert_eq!(
+        rsp.unwrap_err(),
+        "PrizeExceedsPool: 700000000 exceeds locked pool 0",
+        "Winner amount must be backed by the locked pool"
+    );
+
+    // fund and lock the first round's prize pool
+    let rsp: ContractResult<Response> = execute(
+        &mut deps,
+        mock_env(),
+        mock_info(&admin, &[coin(700000000u128, "uusd")]),
+        ExecuteMsg::FundPrizePool {},
+    );
+    assert_eq!(
+        events(&rsp.unwrap(), "prize-pool-funded"),
+        vec![vec![
+            ("round_id", "0"),
+            ("sender", "admin"),
+            ("amount", "700000000"),
+            ("open", "700000000"),
+        ]]
+    );
+    let rsp: ContractResult<Response> = execute(
+        &mut deps,
+        mock_env(),
+        admin_info.clone(),
+        ExecuteMsg::StartRound {
+            round_info: RoundInfo {
+                active: true,
+                start_date: (mock_env().block.time.nanos() / 1_000_000_000) - 100000,
+                name: "Day 0".to_string(),
+                end_date: (mock_env().block.time.nanos() / 1_000_000_000) - 100000,
+                ticket_price: None,
+            },
+        },
+    );
+    assert_eq!(rsp.is_err(), false);
+    for msg in [
+        ExecuteMsg::CloseRound {},
+        ExecuteMsg::DistributeRevenue {},
+        ExecuteMsg::StartRound {
+            round_info: RoundInfo {
+                active: true,
+                start_date: (mock_env().block.time.nanos() / 1_000_000_000) - 100000,
+                name: "Day 1".to_string(),
+                end_date: (mock_env().block.time.nanos() / 1_000_000_000) - 100000,
+                ticket_price: None,
+            },
+        },
+    ] {
+        let rsp: ContractResult<Response> = execute(&mut deps, mock_env(), admin_info.clone(), msg);
+        assert_eq!(rsp.is_err(), false);
+    }
+    for recipient in [&user1, &user2] {
+        let rsp: ContractResult<Response> = execute(
+            &mut deps,
+            mock_env(),
+            admin_info.clone(),
+            ExecuteMsg::ClaimShare {
+                recipient: recipient.clone(),
+            },
+        );
+        assert_eq!(
+            events(&rsp.unwrap(), "share-claimed")[0][0],
+            ("wallet", recipient.as_str())
+        );
+    }
+
     let rsp: ContractResult<Response> = execute(
         &mut deps,
         mock_env(),
@@ -208,18 +343,19 @@ fn delorean_distributer_test() {
         &mut deps,
         mock_env(),
         user1_info.clone(),
-        ExecuteMsg::RoundUpdate {
+        ExecuteMsg::StartRound {
             round_info: RoundInfo {
                 active: true,
                 start_date: (mock_env().block.time.nanos() / 1_000_000_000) - 100000,
                 name: "Day 1".to_string(),
                 end_date: (mock_env().block.time.nanos() / 1_000_000_000) - 100000,
+                ticket_price: None,
             },
         },
     );
     assert_eq!(
         rsp.unwrap_err(),
-        "Unauthorized",
+        "Unauthorized: user1 is not round_operator",
         "Round update should fail for not authrozied wallet"
     );
 
@@ -242,6 +378,57 @@ fn delorean_distributer_test() {
         "Winner update should work for authrozied wallet"
     );
 
+    let operator_info = mock_info(&String::from("operator"), &[]);
+    let rsp: ContractResult<Response> = execute(
+        &mut deps,
+        mock_env(),
+        operator_info.clone(),
+        ExecuteMsg::UpdateConfig {
+            config: ConfigUpdate {
+                nfts: Some(config.nfts.clone()),
+                ..ConfigUpdate::default()
+            },
+        },
+    );
+    assert_eq!(
+        rsp.unwrap_err(),
+        "Unauthorized: operator is not config_manager",
+        "Round operator should not update config"
+    );
+
+    let rsp: ContractResult<Response> = execute(
+        &mut deps,
+        mock_env(),
+        operator_info,
+        ExecuteMsg::WinnerUpdate {
+            winner: WinnerInfo {
+                winner_address: "user1".to_string(),
+                winner_amount: coin(700000000u128, "uusd".to_string()),
+                claimed: false,
+                claim_end_time: (mock_env().block.time.nanos() / 1_000_000_000) - 100000,
+            },
+        },
+    );
+    assert_eq!(
+        rsp.is_err(),
+        false,
+        "Winner update should work for round operator"
+    );
+    let claim_end_time = ((mock_env().block.time.nanos() / 1_000_000_000) - 100000).to_string();
+    assert_eq!(
+        events(&rsp.unwrap(), "winner-set"),
+        vec![vec![
+            ("round_id", "2"),
+            ("wallet", "user1"),
+            ("amount", "700000000"),
+            ("denom", "uusd"),
+            ("claim_end_time", claim_end_time.as_str()),
+            ("reserved", "700000000"),
+            ("released", "700000000"),
+        ]],
+        "Replacing the winner releases the old reservation"
+    );
+
     let rsp: ContractResult<Response> = execute(
         &mut deps,
         mock_env(),
@@ -252,7 +439,10 @@ fn delorean_distributer_test() {
     );
     assert_eq!(
         rsp.unwrap_err(),
-        "Unauthorized",
+        format!(
+            "ClaimWindowClosed: ended at {}",
+            (mock_env().block.time.nanos() / 1_000_000_000) - 100000
+        ),
         "Claim prize should fail for expired time"
     );
 
@@ -279,7 +469,7 @@ fn delorean_distributer_test() {
     );
     assert_eq!(
         rsp.unwrap_err(),
-        "Unauthorized",
+        "PrizeAlreadyClaimed",
         "Claim prize should fail for already claimed"
     );
 
@@ -305,6 +495,16 @@ fn delorean_distributer_test() {
         },
     );
     assert_eq!(rsp.is_err(), false, "Claim prize should work");
+    assert_eq!(
+        events(&rsp.unwrap(), "prize-claimed"),
+        vec![vec![
+            ("round_id", "2"),
+            ("wallet", "user1"),
+            ("amount", "700000000"),
+            ("denom", "uusd"),
+            ("burned_token_id", "1"),
+        ]]
+    );
 
     let rsp: ContractResult<Response> = execute(
         &mut deps,
@@ -315,6 +515,26 @@ fn delorean_distributer_test() {
         },
     );
     assert_eq!(rsp.is_err(), false, "Degen should work 1");
+    let rsp = rsp.unwrap();
+    assert_eq!(
+        events(&rsp, "degen-burn"),
+        vec![vec![
+            ("round_id", "2"),
+            ("wallet", "user1"),
+            ("burned_token_id", "1"),
+        ]]
+    );
+    assert_eq!(
+        events(&rsp, "tickets-issued"),
+        vec![vec![
+            ("round_id", "2"),
+            ("wallet", "user1"),
+            ("source", "burn"),
+            ("count", "1"),
+            ("first_index", "0"),
+            ("round_tickets", "1"),
+        ]]
+    );
 
     let rsp: ContractResult<Response> = execute(
         &mut deps,
@@ -326,7 +546,15 @@ fn delorean_distributer_test() {
     );
     assert_eq!(rsp.is_err(), false, "Degen should work 2");
 
-    let rsp = query(&mut deps, mock_env(), QueryMsg::DegenInfo {}).unwrap();
+    let rsp = query(
+        &mut deps,
+        mock_env(),
+        QueryMsg::DegenInfo {
+            start_after: None,
+            limit: None,
+        },
+    )
+    .unwrap();
     let rsp: DegenInfoResponse = from_binary(&rsp).unwrap();
     assert_eq!(
         rsp,
@@ -336,30 +564,1470 @@ fn delorean_distributer_test() {
         "Degen info response should work with proper response"
     );
 
+    let rsp = query(
+        &mut deps,
+        mock_env(),
+        QueryMsg::DegenInfo {
+            start_after: Some("user1".to_string()),
+            limit: Some(1),
+        },
+    )
+    .unwrap();
+    let rsp: DegenInfoResponse = from_binary(&rsp).unwrap();
+    assert_eq!(rsp.wallets, vec!["user2".to_string()]);
+
+    let rsp = query(
+        &mut deps,
+        mock_env(),
+        QueryMsg::WalletStatus {
+            address: "user1".to_string(),
+        },
+    )
+    .unwrap();
+    let rsp: WalletStatusResponse = from_binary(&rsp).unwrap();
+    assert_eq!(rsp.burned, true, "Wallet status should show the burn");
+
+    let rsp = query(
+        &mut deps,
+        mock_env(),
+        QueryMsg::SimulateDistributeRevenue {},
+    )
+    .unwrap();
+    let simulated: SimulateDistributeRevenueResponse = from_binary(&rsp).unwrap();
+
+    let day2 = RoundInfo {
+        active: true,
+        start_date: (mock_env().block.time.nanos() / 1_000_000_000) - 100000,
+        name: "Day 2".to_string(),
+        end_date: (mock_env().block.time.nanos() / 1_000_000_000) - 100000,
+        ticket_price: None,
+    };
     let rsp: ContractResult<Response> = execute(
         &mut deps,
         mock_env(),
         admin_info.clone(),
-        ExecuteMsg::RoundUpdate {
+        ExecuteMsg::EditRound {
             round_info: RoundInfo {
-                active: true,
-                start_date: (mock_env().block.time.nanos() / 1_000_000_000) - 100000,
-                name: "Day 1".to_string(),
-                end_date: (mock_env().block.time.nanos() / 1_000_000_000) - 100000,
+                name: "Day 1 (renamed)".to_string(),
+                ..day2.clone()
             },
         },
     );
+    assert_eq!(
+        rsp.unwrap().messages.len(),
+        0,
+        "Editing a round moves no funds"
+    );
+    let rsp = query(
+        &mut deps,
+        mock_env(),
+        QueryMsg::SimulateDistributeRevenue {},
+    )
+    .unwrap();
+    let rsp: SimulateDistributeRevenueResponse = from_binary(&rsp).unwrap();
+    assert_eq!(rsp, simulated, "Editing a round leaves its revenue alone");
+
+    let rsp: ContractResult<Response> = execute(
+        &mut deps,
+        mock_env(),
+        admin_info.clone(),
+        ExecuteMsg::StartRound {
+            round_info: day2.clone(),
+        },
+    );
+    assert_eq!(rsp.unwrap_err(), "RoundNotClosed: round 2 is still open");
+    let rsp: ContractResult<Response> = execute(
+        &mut deps,
+        mock_env(),
+        admin_info.clone(),
+        ExecuteMsg::DistributeRevenue {},
+    );
+    assert_eq!(rsp.unwrap_err(), "RoundNotClosed: round 2 is still open");
+
+    let rsp: ContractResult<Response> = execute(
+        &mut deps,
+        mock_env(),
+        admin_info.clone(),
+        ExecuteMsg::CloseRound {},
+    );
+    let types: Vec<String> = rsp
+        .unwrap()
+        .events
+        .iter()
+        .map(|event| event.ty.clone())
+        .collect();
+    assert_eq!(types.first(), Some(&"round-closed".to_string()));
+    let rsp: ContractResult<Response> = execute(
+        &mut deps,
+        mock_env(),
+        admin_info.clone(),
+        ExecuteMsg::EditRound {
+            round_info: day2.clone(),
+        },
+    );
+    assert_eq!(rsp.unwrap_err(), "RoundNotOpen: round 2 is closed");
+    let rsp: ContractResult<Response> = execute(
+        &mut deps,
+        mock_env(),
+        admin_info.clone(),
+        ExecuteMsg::StartRound {
+            round_info: day2.clone(),
+        },
+    );
+    assert_eq!(rsp.unwrap_err(), "RevenueNotDistributed: round 2");
+    let rsp: ContractResult<Response> = execute(
+        &mut deps,
+        mock_env(),
+        user1_info.clone(),
+        ExecuteMsg::DistributeRevenue {},
+    );
+    assert_eq!(
+        rsp.unwrap_err(),
+        "Unauthorized: user1 is not treasury_operator"
+    );
+
+    let rsp: ContractResult<Response> = execute(
+        &mut deps,
+        mock_env(),
+        admin_info.clone(),
+        ExecuteMsg::DistributeRevenue {},
+    );
     assert_eq!(
         rsp.is_err(),
         false,
-        "Round update should pass with funds moving to different wallets"
+        "Distribution should pass with funds credited to different wallets"
+    );
+    let rsp = rsp.unwrap();
+    assert_eq!(rsp.messages.len(), 0, "Shares are credited, not pushed");
+    let types: Vec<String> = rsp.events.iter().map(|event| event.ty.clone()).collect();
+    assert_eq!(types.first(), Some(&"revenue-distributed".to_string()));
+    let prize_cut = simulated.prize_cut.to_string();
+    assert_eq!(
+        events(&rsp, "revenue-distributed")[0][4],
+        ("prize_cut", prize_cut.as_str()),
+        "Distribution should move the simulated prize cut"
+    );
+    let credited: Vec<(String, String)> = events(&rsp, "share-credited")
+        .iter()
+        .map(|attrs| (attrs[2].1.to_string(), attrs[4].1.to_string()))
+        .collect();
+    let previewed: Vec<(String, String)> = simulated
+        .deposits
+        .iter()
+        .filter(|deposit| !deposit.payout.gross.amount.is_zero())
+        .map(|deposit| {
+            (
+                deposit.payout.recipient.clone(),
+                deposit.payout.gross.amount.to_string(),
+            )
+        })
+        .collect();
+    assert_eq!(
+        credited, previewed,
+        "Distribution should credit each simulated deposit"
+    );
+    let rsp: ContractResult<Response> = execute(
+        &mut deps,
+        mock_env(),
+        admin_info.clone(),
+        ExecuteMsg::DistributeRevenue {},
+    );
+    assert_eq!(rsp.unwrap_err(), "RevenueAlreadyDistributed: round 2");
+    let mut expected: Vec<(String, Uint128)> = vec![];
+    for deposit in simulated.deposits {
+        let gross = deposit.payout.gross.amount;
+        match expected
+            .iter_mut()
+            .find(|(recipient, _)| *recipient == deposit.payout.recipient)
+        {
+            Some((_, amount)) => *amount += gross,
+            None => expected.push((deposit.payout.recipient, gross)),
+        }
+    }
+    let owed = query(
+        &mut deps,
+        mock_env(),
+        QueryMsg::SharesOwed {
+            start_after: None,
+            limit: None,
+        },
+    )
+    .unwrap();
+    let owed: SharesOwedResponse = from_binary(&owed).unwrap();
+    let owed: Vec<(String, Uint128)> = owed
+        .shares
+        .into_iter()
+        .map(|share| (share.recipient, share.amount))
+        .collect();
+    assert_eq!(
+        owed, expected,
+        "Simulation should match the credited shares"
+    );
+
+    let claim: ContractResult<Response> = execute(
+        &mut deps,
+        mock_env(),
+        user2_info.clone(),
+        ExecuteMsg::ClaimShare {
+            recipient: user1.clone(),
+        },
+    );
+    let claimed: Vec<(String, Uint128)> = claim
+        .unwrap()
+        .messages
+        .iter()
+        .map(|sub_msg| match &sub_msg.msg {
+            CosmosMsg::Wasm(WasmMsg::Execute {
+                contract_addr,
+                funds,
+                ..
+            }) => (contract_addr.clone(), funds[0].amount),
+            _ => panic!("Unexpected message"),
+        })
+        .collect();
+    assert_eq!(
+        claimed,
+        vec![expected[0].clone()],
+        "Anyone can deposit a recipient's share into it"
+    );
+    let claim: ContractResult<Response> = execute(
+        &mut deps,
+        mock_env(),
+        user2_info.clone(),
+        ExecuteMsg::ClaimShare {
+            recipient: user1.clone(),
+        },
+    );
+    assert_eq!(claim.unwrap_err(), "NothingOwed: user1");
+
+    let rsp: ContractResult<Response> = execute(
+        &mut deps,
+        mock_env(),
+        admin_info.clone(),
+        ExecuteMsg::StartRound { round_info: day2 },
     );
+    assert_eq!(rsp.is_err(), false, "Next round starts once distributed");
 
-    let rsp = query(&mut deps, mock_env(), QueryMsg::DegenInfo {}).unwrap();
+    let rsp = query(&mut deps, mock_env(), QueryMsg::RoundInfo {}).unwrap();
+    let rsp: RoundInfoResponse = from_binary(&rsp).unwrap();
+    assert_eq!(rsp.round_id, 3, "Starting a round should bump the round id");
+
+    let rsp = query(
+        &mut deps,
+        mock_env(),
+        QueryMsg::DegenInfo {
+            start_after: None,
+            limit: None,
+        },
+    )
+    .unwrap();
     let rsp: DegenInfoResponse = from_binary(&rsp).unwrap();
     assert_eq!(
         rsp,
         DegenInfoResponse { wallets: vec![] },
-        "Degen info response should be blank after round is updated"
+        "Degen info response should be blank in a new round"
+    );
+}
+
+fn legacy_map_key(namespace: &str, key: &str) -> Vec<u8> {
+    let mut full_key = (namespace.len() as u16).to_be_bytes().to_vec();
+    full_key.extend_from_slice(namespace.as_bytes());
+    full_key.extend_from_slice(key.as_bytes());
+    full_key
+}
+
+fn tier_info(name: &str, price: u128, wallet: &str) -> NftMetaInfo {
+    NftMetaInfo {
+        nft_metadata: Metadata {
+            name: Some(String::from(name)),
+            ..Metadata::default()
+        },
+        price: coin(price, "uusd"),
+        shares: vec![FundShare {
+            address: wallet.to_string(),
+            note: "treasury".to_string(),
+            share: 10000u32,
+        }],
+        ticket_weight: 1,
+    }
+}
+
+#[test]
+fn delorean_migrate_test() {
+    let mut backend =
+        mock_dependencies_with_custom_querier(&[coin(900000000u128, "uusd".to_string())]);
+    let admin_info = mock_info(&String::from("admin"), &[]);
+    let nfts = vec![
+        tier_info("NFT 1", 150000000u128, "user1"),
+        tier_info("NFT 2", 75000000u128, "user2"),
+    ];
+    let winner = WinnerInfo {
+        winner_address: "user1".to_string(),
+        winner_amount: coin(700000000u128, "uusd".to_string()),
+        claimed: false,
+        claim_end_time: 100,
+    };
+    let round = RoundInfo {
+        active: true,
+        start_date: 10,
+        end_date: 20,
+        name: "Day 1".to_string(),
+        ticket_price: None,
+    };
+
+    // store as written by the unversioned contract
+    let legacy_config = format!(
+        r#"{{"admin":"admin","mint_contract":"xx1","nft_contract":"xx2","nfts":{}}}"#,
+        String::from_utf8(to_vec(&nfts).unwrap()).unwrap()
+    );
+    let legacy_entries: Vec<(Vec<u8>, Vec<u8>)> = vec![
+        (b"config_1sd&23".to_vec(), legacy_config.into_bytes()),
+        (b"winners_1gasd2".to_vec(), to_vec(&winner).unwrap()),
+        (b"round_info_12fas".to_vec(), to_vec(&round).unwrap()),
+        (
+            legacy_map_key("degen_state_12fas", "user1"),
+            to_vec(&true).unwrap(),
+        ),
+        (
+            legacy_map_key("degen_state_12fas", "user3"),
+            to_vec(&true).unwrap(),
+        ),
+        (b"nft2_funds_14fas".to_vec(), b"\"25000000\"".to_vec()),
+    ];
+    for (key, value) in legacy_entries {
+        backend.storage.set(&key, &value).0.unwrap();
+    }
+
+    let (instance_options, memory_limit) = mock_instance_options();
+    let mut deps = Instance::from_code(WASM, backend, instance_options, memory_limit).unwrap();
+
+    let msg = MigrateMsg {
+        token_tiers: vec![TokenTier {
+            token_id: "5".to_string(),
+            nft_type: 2,
+        }],
+    };
+    let rsp: ContractResult<Response> = migrate(&mut deps, mock_env(), msg);
+    let rsp = rsp.unwrap();
+    assert_eq!(rsp.attributes[1].value, "0.1.0", "Legacy store is 0.1.0");
+    assert_eq!(rsp.attributes[2].value, env!("CARGO_PKG_VERSION"));
+
+    let rsp = query(&mut deps, mock_env(), QueryMsg::Config {}).unwrap();
+    let rsp: ConfigResponse = from_binary(&rsp).unwrap();
+    assert_eq!(
+        rsp.config,
+        Config {
+            mint_contract: "xx1".to_string(),
+            nft_contract: "xx2".to_string(),
+            nfts: nfts.clone(),
+            unstake_cooldown: MIN_UNSTAKE_COOLDOWN,
+            prize_share: 0,
+            round_duration: 0,
+            keeper_reward: Uint128::zero(),
+        },
+        "Config is moved to the new key"
+    );
+
+    let rsp = query(&mut deps, mock_env(), QueryMsg::Ownership {}).unwrap();
+    let rsp: OwnershipResponse = from_binary(&rsp).unwrap();
+    assert_eq!(
+        rsp.admin,
+        Some("admin".to_string()),
+        "Admin moves to ownership"
+    );
+
+    let rsp = query(&mut deps, mock_env(), QueryMsg::Winners {}).unwrap();
+    let rsp: WinnersResponse = from_binary(&rsp).unwrap();
+    assert_eq!(rsp.winner, Some(winner));
+
+    let rsp = query(&mut deps, mock_env(), QueryMsg::RoundInfo {}).unwrap();
+    let rsp: RoundInfoResponse = from_binary(&rsp).unwrap();
+    assert_eq!(rsp.round, Some(round.clone()));
+
+    let rsp = query(
+        &mut deps,
+        mock_env(),
+        QueryMsg::DegenInfo {
+            start_after: None,
+            limit: None,
+        },
+    )
+    .unwrap();
+    let rsp: DegenInfoResponse = from_binary(&rsp).unwrap();
+    assert_eq!(
+        rsp.wallets,
+        vec!["user1".to_string(), "user3".to_string()],
+        "Legacy degens are read in place as round 0"
+    );
+
+    let rsp = query(
+        &mut deps,
+        mock_env(),
+        QueryMsg::DegenInfo {
+            start_after: Some("user1".to_string()),
+            limit: Some(1),
+        },
+    )
+    .unwrap();
+    let rsp: DegenInfoResponse = from_binary(&rsp).unwrap();
+    assert_eq!(rsp.wallets, vec!["user3".to_string()]);
+
+    let rsp = query(
+        &mut deps,
+        mock_env(),
+        QueryMsg::WalletStatus {
+            address: "user3".to_string(),
+        },
+    )
+    .unwrap();
+    let rsp: WalletStatusResponse = from_binary(&rsp).unwrap();
+    assert_eq!(rsp.burned, true);
+
+    // the unclaimed legacy prize stays held as a rollover, second tier keeps
+    // its held funds and first tier gets the rest
+    for msg in [ExecuteMsg::CloseRound {}, ExecuteMsg::DistributeRevenue {}] {
+        let rsp: ContractResult<Response> = execute(&mut deps, mock_env(), admin_info.clone(), msg);
+        assert_eq!(rsp.is_err(), false);
+    }
+
+    // legacy burns carry no tickets, so round 0 has nothing to draw
+    let rsp = query(
+        &mut deps,
+        mock_env(),
+        QueryMsg::WalletTickets {
+            address: "user3".to_string(),
+            round_id: Some(0),
+        },
+    )
+    .unwrap();
+    let rsp: WalletTicketsResponse = from_binary(&rsp).unwrap();
+    assert_eq!(rsp.tickets, 0);
+    let rsp: ContractResult<Response> = execute(
+        &mut deps,
+        mock_env(),
+        admin_info.clone(),
+        ExecuteMsg::DrawWinner {
+            round_id: 0,
+            claim_end_time: 0,
+        },
+    );
+    assert_eq!(rsp.unwrap_err(), "NoTickets: round 0 issued no tickets");
+
+    // minted before the upgrade with a backfilled tier, and minted elsewhere
+    for (token_id, nft_type) in [("5", "2"), ("9", "0")] {
+        let rsp: ContractResult<Response> = execute(
+            &mut deps,
+            mock_env(),
+            mock_info(&String::from("xx2"), &[]),
+            ExecuteMsg::ReceiveNft(Cw721ReceiveMsg {
+                sender: "user3".to_string(),
+                token_id: token_id.to_string(),
+                msg: Binary::default(),
+            }),
+        );
+        let rsp = rsp.unwrap();
+        let staked = &events(&rsp, "nft-staked")[0];
+        assert!(staked.contains(&("nft_type", nft_type)));
+        assert!(staked.contains(&("tickets", "1")));
+    }
+
+    let rsp = query(
+        &mut deps,
+        mock_env(),
+        QueryMsg::SharesOwed {
+            start_after: None,
+            limit: None,
+        },
+    )
+    .unwrap();
+    let rsp: SharesOwedResponse = from_binary(&rsp).unwrap();
+    let owed: Vec<(String, u128)> = rsp
+        .shares
+        .into_iter()
+        .map(|share| (share.recipient, share.amount.u128()))
+        .collect();
+    assert_eq!(
+        owed,
+        vec![
+            ("user1".to_string(), 175000000u128),
+            ("user2".to_string(), 25000000u128)
+        ]
+    );
+
+    let rsp: ContractResult<Response> = migrate(&mut deps, mock_env(), MigrateMsg::default());
+    assert_eq!(rsp.is_err(), false, "Migrating to the same version works");
+
+    deps.with_storage(|storage| {
+        storage
+            .set(
+                b"contract_info",
+                br#"{"contract":"crates.io:delorean_app","version":"9.0.0"}"#,
+            )
+            .0?;
+        Ok(())
+    })
+    .unwrap();
+    let rsp: ContractResult<Response> = migrate(&mut deps, mock_env(), MigrateMsg::default());
+    assert_eq!(
+        rsp.unwrap_err(),
+        format!(
+            "CannotDowngrade: stored 9.0.0, current {}",
+            env!("CARGO_PKG_VERSION")
+        ),
+        "Downgrades are refused"
+    );
+
+    deps.with_storage(|storage| {
+        storage
+            .set(
+                b"contract_info",
+                br#"{"contract":"crates.io:delorean_mint","version":"0.1.0"}"#,
+            )
+            .0?;
+        Ok(())
+    })
+    .unwrap();
+    let rsp: ContractResult<Response> = migrate(&mut deps, mock_env(), MigrateMsg::default());
+    assert_eq!(
+        rsp.unwrap_err(),
+        "WrongContract: expected crates.io:delorean_app, found crates.io:delorean_mint",
+        "Migrating from another contract is refused"
+    );
+}
+
+#[test]
+fn delorean_staking_test() {
+    let backend = mock_dependencies_with_custom_querier(&[coin(900000000u128, "uusd".to_string())]);
+    let admin_info = mock_info(&String::from("admin"), &[]);
+    let user1 = String::from("user1");
+    let nft_contract = mock_info(&String::from("xx2"), &[]);
+    let mut gold = tier_info("NFT 2", 75000000u128, "user2");
+    gold.ticket_weight = 3;
+    let config = Config {
+        mint_contract: "xx1".to_string(),
+        nft_contract: "xx2".to_string(),
+        nfts: vec![tier_info("NFT 1", 150000000u128, "user1"), gold],
+        unstake_cooldown: MIN_UNSTAKE_COOLDOWN,
+        prize_share: 0,
+        round_duration: 0,
+        keeper_reward: Uint128::zero(),
+    };
+
+    let (instance_options, memory_limit) = mock_instance_options();
+    let mut deps = Instance::from_code(WASM, backend, instance_options, memory_limit).unwrap();
+    let rsp: ContractResult<Response> = instantiate(
+        &mut deps,
+        mock_env(),
+        admin_info.clone(),
+        InstantiateMsg {
+            config,
+            admin: None,
+            roles: vec![],
+        },
+    );
+    assert_eq!(rsp.is_err(), false);
+
+    let rsp: ContractResult<Response> = execute(
+        &mut deps,
+        mock_env(),
+        mock_info(&user1, &[coin(75000000u128, "uusd")]),
+        ExecuteMsg::Mint { nft_type: 2 },
+    );
+    let rsp = rsp.unwrap();
+    assert_eq!(rsp.messages[0].reply_on, ReplyOn::Success);
+    let rsp: ContractResult<Response> = reply(
+        &mut deps,
+        mock_env(),
+        Reply {
+            id: rsp.messages[0].id,
+            result: ContractResult::Ok(SubMsgExecutionResponse {
+                events: vec![Event::new("wasm")
+                    .add_attribute("_contract_address", "xx1")
+                    .add_attribute("token_id", "7")],
+                data: None,
+            }),
+        },
+    );
+    assert_eq!(rsp.is_err(), false, "Mint reply records the tier");
+
+    let stake = |token_id: &str| {
+        ExecuteMsg::ReceiveNft(Cw721ReceiveMsg {
+            sender: user1.clone(),
+            token_id: token_id.to_string(),
+            msg: Binary::default(),
+        })
+    };
+    let rsp: ContractResult<Response> =
+        execute(&mut deps, mock_env(), mock_info(&user1, &[]), stake("7"));
+    assert_eq!(rsp.unwrap_err(), "NotNftContract: user1");
+    let rsp: ContractResult<Response> =
+        execute(&mut deps, mock_env(), nft_contract.clone(), stake("7"));
+    assert_eq!(rsp.is_err(), false);
+
+    let rsp = query(
+        &mut deps,
+        mock_env(),
+        QueryMsg::StakedTokens {
+            owner: user1.clone(),
+            start_after: None,
+            limit: None,
+        },
+    )
+    .unwrap();
+    let rsp: StakedTokensResponse = from_binary(&rsp).unwrap();
+    assert_eq!(rsp.tickets, 3, "Tickets follow the tier weight");
+    assert_eq!(rsp.tokens[0].token_id, "7");
+    assert_eq!(
+        rsp.tokens[0].unlock_time,
+        rsp.tokens[0].staked_at + MIN_UNSTAKE_COOLDOWN
+    );
+
+    let rsp: ContractResult<Response> = execute(
+        &mut deps,
+        mock_env(),
+        mock_info(&user1, &[]),
+        ExecuteMsg::Degen {
+            burn_nft_id: "7".to_string(),
+        },
+    );
+    assert_eq!(
+        rsp.unwrap_err(),
+        "TokenStaked: 7",
+        "Staked nfts cannot be burned"
+    );
+
+    let unstake = ExecuteMsg::UnstakeNft {
+        token_id: "7".to_string(),
+    };
+    let rsp: ContractResult<Response> = execute(
+        &mut deps,
+        mock_env(),
+        mock_info(&String::from("user2"), &[]),
+        unstake.clone(),
+    );
+    assert_eq!(rsp.unwrap_err(), "NotTokenOwner: user2 did not stake 7");
+    let rsp: ContractResult<Response> = execute(
+        &mut deps,
+        mock_env(),
+        mock_info(&user1, &[]),
+        unstake.clone(),
+    );
+    assert_eq!(
+        rsp.unwrap_err(),
+        format!(
+            "StakeLocked: 7 unlocks at {}",
+            mock_env().block.time.nanos() / 1_000_000_000 + MIN_UNSTAKE_COOLDOWN
+        )
+    );
+
+    let rsp: ContractResult<Response> = execute(
+        &mut deps,
+        mock_env(),
+        admin_info.clone(),
+        ExecuteMsg::StartRound {
+            round_info: RoundInfo {
+                active: true,
+                start_date: 10,
+                end_date: 20,
+                name: "Day 1".to_string(),
+                ticket_price: None,
+            },
+        },
+    );
+    assert_eq!(rsp.is_err(), false);
+
+    let mut env = mock_env();
+    env.block.time = env.block.time.plus_seconds(MIN_UNSTAKE_COOLDOWN);
+    let rsp: ContractResult<Response> =
+        execute(&mut deps, env.clone(), mock_info(&user1, &[]), unstake);
+    let rsp = rsp.unwrap();
+    assert_eq!(rsp.messages.len(), 1, "Nft is sent back");
+    assert_eq!(
+        events(&rsp, "nft-unstaked"),
+        vec![vec![
+            ("round_id", "1"),
+            ("wallet", "user1"),
+            ("token_id", "7"),
+            ("nft_type", "2"),
+            ("tickets", "3"),
+            ("wallet_tickets", "0"),
+            ("total_tickets", "0"),
+        ]]
+    );
+
+    // the same nft staked from another wallet enters nothing this round
+    let rsp: ContractResult<Response> = execute(
+        &mut deps,
+        env,
+        nft_contract.clone(),
+        ExecuteMsg::ReceiveNft(Cw721ReceiveMsg {
+            sender: "user2".to_string(),
+            token_id: "7".to_string(),
+            msg: Binary::default(),
+        }),
+    );
+    let rsp = rsp.unwrap();
+    assert_eq!(rsp.events.len(), 1, "No tickets-issued event");
+    assert_eq!(rsp.events[0].ty, "nft-staked");
+
+    let rsp = query(
+        &mut deps,
+        mock_env(),
+        QueryMsg::Tickets { round_id: Some(0) },
+    )
+    .unwrap();
+    let rsp: TicketsResponse = from_binary(&rsp).unwrap();
+    assert_eq!(rsp.total_tickets, 3, "Closed round keeps its ticket count");
+    let rsp = query(&mut deps, mock_env(), QueryMsg::Tickets { round_id: None }).unwrap();
+    let rsp: TicketsResponse = from_binary(&rsp).unwrap();
+    assert_eq!(
+        rsp,
+        TicketsResponse {
+            round_id: 1,
+            total_tickets: 0,
+        }
+    );
+
+    let rsp: ContractResult<Response> = execute(
+        &mut deps,
+        mock_env(),
+        admin_info,
+        ExecuteMsg::UpdateConfig {
+            config: ConfigUpdate {
+                unstake_cooldown: Some(0),
+                ..ConfigUpdate::default()
+            },
+        },
+    );
+    assert_eq!(rsp.unwrap_err(), "InvalidConfig: unstake_cooldown");
+}
+
+#[test]
+fn delorean_tickets_test() {
+    let backend = mock_dependencies_with_custom_querier(&[coin(900000000u128, "uusd".to_string())]);
+    let admin_info = mock_info(&String::from("admin"), &[]);
+    let user1 = String::from("user1");
+    let user2 = String::from("user2");
+    let config = Config {
+        mint_contract: "xx1".to_string(),
+        nft_contract: "xx2".to_string(),
+        nfts: vec![tier_info("NFT 1", 150000000u128, "user1")],
+        unstake_cooldown: MIN_UNSTAKE_COOLDOWN,
+        prize_share: 0,
+        round_duration: 0,
+        keeper_reward: Uint128::zero(),
+    };
+
+    let (instance_options, memory_limit) = mock_instance_options();
+    let mut deps = Instance::from_code(WASM, backend, instance_options, memory_limit).unwrap();
+    let rsp: ContractResult<Response> = instantiate(
+        &mut deps,
+        mock_env(),
+        admin_info.clone(),
+        InstantiateMsg {
+            config,
+            admin: None,
+            roles: vec![],
+        },
+    );
+    assert_eq!(rsp.is_err(), false);
+
+    let buy = ExecuteMsg::BuyTickets { count: 2 };
+    let rsp: ContractResult<Response> = execute(
+        &mut deps,
+        mock_env(),
+        mock_info(&user1, &[coin(2000u128, "uusd")]),
+        buy.clone(),
+    );
+    assert_eq!(
+        rsp.unwrap_err(),
+        "TicketSalesClosed",
+        "No tickets before a priced round"
+    );
+
+    let rsp: ContractResult<Response> = execute(
+        &mut deps,
+        mock_env(),
+        admin_info.clone(),
+        ExecuteMsg::StartRound {
+            round_info: RoundInfo {
+                active: true,
+                start_date: 10,
+                end_date: 20,
+                name: "Day 1".to_string(),
+                ticket_price: Some(coin(1000u128, "uusd")),
+            },
+        },
+    );
+    assert_eq!(rsp.is_err(), false);
+
+    let rsp: ContractResult<Response> = execute(
+        &mut deps,
+        mock_env(),
+        mock_info(&user1, &[coin(1000u128, "uusd")]),
+        buy.clone(),
+    );
+    assert_eq!(
+        rsp.unwrap_err(),
+        "WrongAmount: expected 2000, received 1000"
+    );
+    let rsp: ContractResult<Response> = execute(
+        &mut deps,
+        mock_env(),
+        mock_info(&user1, &[coin(2000u128, "uusd")]),
+        buy,
+    );
+    assert_eq!(
+        events(&rsp.unwrap(), "tickets-issued"),
+        vec![vec![
+            ("round_id", "1"),
+            ("wallet", "user1"),
+            ("source", "purchase"),
+            ("count", "2"),
+            ("first_index", "0"),
+            ("round_tickets", "2"),
+        ]]
+    );
+    let rsp: ContractResult<Response> = execute(
+        &mut deps,
+        mock_env(),
+        mock_info(&user2, &[]),
+        ExecuteMsg::Degen {
+            burn_nft_id: "5".to_string(),
+        },
+    );
+    assert_eq!(rsp.is_err(), false, "Burns earn tickets");
+
+    let rsp = query(&mut deps, mock_env(), QueryMsg::Tickets { round_id: None }).unwrap();
+    let rsp: TicketsResponse = from_binary(&rsp).unwrap();
+    assert_eq!(rsp.round_id, 1);
+    assert_eq!(rsp.total_tickets, 3);
+
+    let rsp = query(
+        &mut deps,
+        mock_env(),
+        QueryMsg::WalletTickets {
+            address: user1.clone(),
+            round_id: None,
+        },
+    )
+    .unwrap();
+    let rsp: WalletTicketsResponse = from_binary(&rsp).unwrap();
+    assert_eq!(rsp.tickets, 2);
+
+    let owners: Vec<(String, TicketSource)> = (0..3)
+        .map(|index| {
+            let rsp = query(
+                &mut deps,
+                mock_env(),
+                QueryMsg::TicketOwner {
+                    round_id: None,
+                    index,
+                },
+            )
+            .unwrap();
+            let rsp: TicketOwnerResponse = from_binary(&rsp).unwrap();
+            (rsp.wallet, rsp.source)
+        })
+        .collect();
+    assert_eq!(
+        owners,
+        vec![
+            (user1.clone(), TicketSource::Purchase),
+            (user1, TicketSource::Purchase),
+            (user2, TicketSource::Burn),
+        ],
+        "Ticket indexes follow the order tickets were issued"
+    );
+    let rsp = query(
+        &mut deps,
+        mock_env(),
+        QueryMsg::TicketOwner {
+            round_id: None,
+            index: 3,
+        },
+    );
+    assert_eq!(rsp.is_err(), true, "Index past the round total");
+
+    let rsp: ContractResult<Response> = execute(
+        &mut deps,
+        mock_env(),
+        admin_info.clone(),
+        ExecuteMsg::CloseRound {},
+    );
+    assert_eq!(rsp.is_err(), false);
+    let rsp: ContractResult<Response> = execute(
+        &mut deps,
+        mock_env(),
+        mock_info(&String::from("user2"), &[]),
+        ExecuteMsg::Degen {
+            burn_nft_id: "6".to_string(),
+        },
+    );
+    assert_eq!(rsp.unwrap_err(), "RoundNotOpen: round 1 is closed");
+
+    // once the next round closes, round 1's prize has rolled into it
+    for msg in [
+        ExecuteMsg::DistributeRevenue {},
+        ExecuteMsg::StartRound {
+            round_info: RoundInfo {
+                active: true,
+                start_date: 20,
+                end_date: 30,
+                name: "Day 2".to_string(),
+                ticket_price: None,
+            },
+        },
+        ExecuteMsg::CloseRound {},
+    ] {
+        let rsp: ContractResult<Response> = execute(&mut deps, mock_env(), admin_info.clone(), msg);
+        assert_eq!(rsp.is_err(), false);
+    }
+    let rsp: ContractResult<Response> = execute(
+        &mut deps,
+        mock_env(),
+        admin_info,
+        ExecuteMsg::DrawWinner {
+            round_id: 1,
+            claim_end_time: 0,
+        },
+    );
+    assert_eq!(
+        rsp.unwrap_err(),
+        "StaleRound: round 1, only round 2 can be drawn"
+    );
+}
+
+#[test]
+fn delorean_prize_pool_test() {
+    let backend = mock_dependencies_with_custom_querier(&[coin(900000000u128, "uusd".to_string())]);
+    let admin_info = mock_info(&String::from("admin"), &[]);
+    let user1_info = mock_info(&String::from("user1"), &[]);
+    let config = Config {
+        mint_contract: "xx1".to_string(),
+        nft_contract: "xx2".to_string(),
+        nfts: vec![tier_info("NFT 1", 150000000u128, "user2")],
+        unstake_cooldown: MIN_UNSTAKE_COOLDOWN,
+        prize_share: 1000u32, //10.00
+        round_duration: 0,
+        keeper_reward: Uint128::zero(),
+    };
+    let round = RoundInfo {
+        active: true,
+        start_date: 10,
+        end_date: 20,
+        name: "Day 1".to_string(),
+        ticket_price: None,
+    };
+    let winner = WinnerInfo {
+        winner_address: "user1".to_string(),
+        winner_amount: coin(200000000u128, "uusd"),
+        claimed: false,
+        claim_end_time: (mock_env().block.time.nanos() / 1_000_000_000) + 1000,
+    };
+
+    let (instance_options, memory_limit) = mock_instance_options();
+    let mut deps = Instance::from_code(WASM, backend, instance_options, memory_limit).unwrap();
+    let rsp: ContractResult<Response> = instantiate(
+        &mut deps,
+        mock_env(),
+        admin_info.clone(),
+        InstantiateMsg {
+            config,
+            admin: None,
+            roles: vec![],
+        },
+    );
+    assert_eq!(rsp.is_err(), false);
+
+    let rsp: ContractResult<Response> = execute(
+        &mut deps,
+        mock_env(),
+        mock_info(&String::from("treasury"), &[coin(100000000u128, "uusd")]),
+        ExecuteMsg::FundPrizePool {},
+    );
+    assert_eq!(rsp.is_err(), false);
+
+    let rsp = query(
+        &mut deps,
+        mock_env(),
+        QueryMsg::SimulateDistributeRevenue {},
+    )
+    .unwrap();
+    let rsp: SimulateDistributeRevenueResponse = from_binary(&rsp).unwrap();
+    assert_eq!(
+        rsp.prize_cut,
+        Uint128::from(80000000u128),
+        "Cut comes from the balance left after the funded pool"
+    );
+    assert_eq!(
+        rsp.deposits[0].payout.gross.amount,
+        Uint128::from(720000000u128)
+    );
+
+    let rsp: ContractResult<Response> = execute(
+        &mut deps,
+        mock_env(),
+        admin_info.clone(),
+        ExecuteMsg::StartRound {
+            round_info: round.clone(),
+        },
+    );
+    assert_eq!(rsp.is_err(), false);
+    for msg in [ExecuteMsg::CloseRound {}, ExecuteMsg::DistributeRevenue {}] {
+        let rsp: ContractResult<Response> = execute(&mut deps, mock_env(), admin_info.clone(), msg);
+        assert_eq!(rsp.is_err(), false);
+    }
+
+    let rsp = query(&mut deps, mock_env(), QueryMsg::PrizePool {}).unwrap();
+    let rsp: PrizePoolResponse = from_binary(&rsp).unwrap();
+    assert_eq!(
+        rsp.pool,
+        PrizePool {
+            open: Uint128::zero(),
+            locked: Uint128::from(180000000u128),
+            reserved: Uint128::zero(),
+        }
+    );
+
+    let rsp: ContractResult<Response> = execute(
+        &mut deps,
+        mock_env(),
+        admin_info.clone(),
+        ExecuteMsg::WinnerUpdate {
+            winner: winner.clone(),
+        },
+    );
+    assert_eq!(
+        rsp.unwrap_err(),
+        "PrizeExceedsPool: 200000000 exceeds locked pool 180000000"
+    );
+    let rsp: ContractResult<Response> = execute(
+        &mut deps,
+        mock_env(),
+        admin_info.clone(),
+        ExecuteMsg::WinnerUpdate {
+            winner: WinnerInfo {
+                winner_amount: coin(150000000u128, "uusd"),
+                ..winner
+            },
+        },
+    );
+    assert_eq!(rsp.is_err(), false);
+
+    let rsp = query(&mut deps, mock_env(), QueryMsg::Reserves {}).unwrap();
+    let rsp: ReservesResponse = from_binary(&rsp).unwrap();
+    assert_eq!(
+        (rsp.reserved, rsp.held, rsp.shares_owed, rsp.free),
+        (
+            Uint128::from(150000000u128),
+            Uint128::from(180000000u128),
+            Uint128::from(720000000u128),
+            Uint128::zero()
+        ),
+        "Winner amount moves from the locked pool into the reserve"
+    );
+
+    let rsp: ContractResult<Response> = execute(
+        &mut deps,
+        mock_env(),
+        admin_info.clone(),
+        ExecuteMsg::WinnerUpdate {
+            winner: WinnerInfo {
+                winner_address: "user2".to_string(),
+                winner_amount: coin(10000000u128, "uusd"),
+                claimed: false,
+                claim_end_time: 0,
+            },
+        },
+    );
+    assert_eq!(
+        rsp.unwrap_err(),
+        format!(
+            "PrizeStillOpen: user1 can claim until {}",
+            mock_env().block.time.nanos() / 1_000_000_000 + 1000
+        ),
+        "An unclaimed winner cannot be replaced"
+    );
+
+    let rsp: ContractResult<Response> = execute(
+        &mut deps,
+        mock_env(),
+        user1_info,
+        ExecuteMsg::ClaimPrize {
+            burn_nft_id: "1".to_string(),
+        },
+    );
+    assert_eq!(rsp.is_err(), false);
+
+    let rsp: ContractResult<Response> = execute(
+        &mut deps,
+        mock_env(),
+        admin_info.clone(),
+        ExecuteMsg::ClaimShare {
+            recipient: "user2".to_string(),
+        },
+    );
+    assert_eq!(rsp.is_err(), false);
+    for msg in [
+        ExecuteMsg::StartRound {
+            round_info: round.clone(),
+        },
+        ExecuteMsg::CloseRound {},
+        ExecuteMsg::DistributeRevenue {},
+    ] {
+        let rsp: ContractResult<Response> = execute(&mut deps, mock_env(), admin_info.clone(), msg);
+        assert_eq!(rsp.is_err(), false);
+    }
+    let rsp = query(&mut deps, mock_env(), QueryMsg::PrizePool {}).unwrap();
+    let rsp: PrizePoolResponse = from_binary(&rsp).unwrap();
+    assert_eq!(
+        rsp.pool.locked,
+        Uint128::from(117000000u128),
+        "What the winner left rolls into the next pool"
+    );
+}
+
+#[test]
+fn delorean_tick_test() {
+    let backend = mock_dependencies_with_custom_querier(&[coin(900000000u128, "uusd".to_string())]);
+    let admin_info = mock_info(&String::from("admin"), &[]);
+    let keeper_info = mock_info(&String::from("keeper"), &[]);
+    let user1 = String::from("user1");
+    let now = mock_env().block.time.nanos() / 1_000_000_000;
+    let config = Config {
+        mint_contract: "xx1".to_string(),
+        nft_contract: "xx2".to_string(),
+        nfts: vec![tier_info("NFT 1", 150000000u128, "user2")],
+        unstake_cooldown: MIN_UNSTAKE_COOLDOWN,
+        prize_share: 1000u32, //10.00
+        round_duration: 100,
+        keeper_reward: Uint128::from(1000000u128),
+    };
+
+    let (instance_options, memory_limit) = mock_instance_options();
+    let mut deps = Instance::from_code(WASM, backend, instance_options, memory_limit).unwrap();
+    let rsp: ContractResult<Response> = instantiate(
+        &mut deps,
+        mock_env(),
+        admin_info.clone(),
+        InstantiateMsg {
+            config,
+            admin: None,
+            roles: vec![],
+        },
+    );
+    assert_eq!(rsp.is_err(), false);
+
+    let rsp: ContractResult<Response> = execute(
+        &mut deps,
+        mock_env(),
+        keeper_info.clone(),
+        ExecuteMsg::Tick {},
+    );
+    assert_eq!(rsp.unwrap_err(), "NoRound");
+
+    let rsp: ContractResult<Response> = execute(
+        &mut deps,
+        mock_env(),
+        admin_info.clone(),
+        ExecuteMsg::StartRound {
+            round_info: RoundInfo {
+                active: true,
+                start_date: now,
+                end_date: now + 50,
+                name: "Day 1".to_string(),
+                ticket_price: Some(coin(1000u128, "uusd")),
+            },
+        },
+    );
+    assert_eq!(rsp.is_err(), false);
+    let secret = "round 1 secret";
+    let rsp: ContractResult<Response> = execute(
+        &mut deps,
+        mock_env(),
+        admin_info.clone(),
+        ExecuteMsg::CommitDrawSeed {
+            round_id: 1,
+            commitment: Binary::from(Sha256::digest(secret.as_bytes()).as_slice()),
+        },
+    );
+    assert_eq!(rsp.is_err(), false);
+    let buy = ExecuteMsg::BuyTickets { count: 2 };
+    let rsp: ContractResult<Response> = execute(
+        &mut deps,
+        mock_env(),
+        mock_info(&user1, &[coin(2000u128, "uusd")]),
+        buy.clone(),
+    );
+    assert_eq!(rsp.is_err(), false);
+
+    let reveal = |secret: &str| ExecuteMsg::RevealDrawSeed {
+        round_id: 1,
+        secret: secret.to_string(),
+    };
+    for msg in [ExecuteMsg::Tick {}, reveal(secret)] {
+        let rsp: ContractResult<Response> =
+            execute(&mut deps, mock_env(), keeper_info.clone(), msg);
+        assert_eq!(
+            rsp.unwrap_err(),
+            format!("RoundNotEnded: ends at {}", now + 50)
+        );
+    }
+
+    // two periods go by without a tick, the seed is revealed in the meantime
+    let mut env = mock_env();
+    env.block.time = env.block.time.plus_seconds(250);
+    let rsp: ContractResult<Response> =
+        execute(&mut deps, env.clone(), keeper_info.clone(), reveal("guess"));
+    assert_eq!(
+        rsp.unwrap_err(),
+        "DrawSeedMismatch: secret does not match the commitment for round 1"
+    );
+    let rsp: ContractResult<Response> =
+        execute(&mut deps, env.clone(), keeper_info.clone(), reveal(secret));
+    assert_eq!(
+        events(&rsp.unwrap(), "draw-seed-revealed"),
+        vec![vec![("round_id", "1"), ("secret", secret)]]
+    );
+    let rsp: ContractResult<Response> = execute(
+        &mut deps,
+        env.clone(),
+        mock_info(&user1, &[coin(2000u128, "uusd")]),
+        buy,
+    );
+    assert_eq!(
+        rsp.unwrap_err(),
+        "TicketSalesClosed",
+        "A revealed seed stops the round's tickets"
+    );
+
+    let rsp: ContractResult<Response> = execute(
+        &mut deps,
+        env.clone(),
+        keeper_info.clone(),
+        ExecuteMsg::Tick {},
+    );
+    let rsp = rsp.unwrap();
+    assert_eq!(
+        rsp.messages[0].msg,
+        CosmosMsg::Bank(BankMsg::Send {
+            to_address: "keeper".to_string(),
+            amount: vec![coin(1000000u128, "uusd")],
+        }),
+        "Keeper is paid for the tick"
+    );
+    let types: Vec<String> = rsp.events.iter().map(|event| event.ty.clone()).collect();
+    assert_eq!(
+        types,
+        vec![
+            "round-closed",
+            "prize-pool-locked",
+            "revenue-distributed",
+            "share-credited",
+            "winner-set",
+            "round-started",
+            "keeper-rewarded",
+        ]
+    );
+    let claim_end_time = (now + 350).to_string();
+    assert_eq!(
+        events(&rsp, "winner-set"),
+        vec![vec![
+            ("round_id", "1"),
+            ("wallet", "user1"),
+            ("amount", "89900000"),
+            ("denom", "uusd"),
+            ("claim_end_time", claim_end_time.as_str()),
+            ("reserved", "89900000"),
+            ("released", "0"),
+            ("drawn_round_id", "1"),
+            ("ticket", "1"),
+        ]],
+        "Tick draws the round it closes"
+    );
+    assert_eq!(
+        events(&rsp, "round-closed"),
+        vec![vec![("round_id", "1"), ("tickets", "2")]]
+    );
+    assert_eq!(
+        events(&rsp, "keeper-rewarded"),
+        vec![vec![
+            ("round_id", "1"),
+            ("keeper", "keeper"),
+            ("amount", "1000000"),
+            ("denom", "uusd"),
+        ]]
+    );
+
+    // the operator draws by hand only what Tick could not, the keeper cannot
+    let draw = ExecuteMsg::DrawWinner {
+        round_id: 1,
+        claim_end_time: now + 350,
+    };
+    let rsp: ContractResult<Response> =
+        execute(&mut deps, env.clone(), keeper_info.clone(), draw.clone());
+    assert_eq!(
+        rsp.unwrap_err(),
+        "Unauthorized: keeper is not round_operator"
+    );
+    let rsp: ContractResult<Response> = execute(
+        &mut deps,
+        env.clone(),
+        admin_info.clone(),
+        ExecuteMsg::DrawWinner {
+            round_id: 2,
+            claim_end_time: now + 350,
+        },
+    );
+    assert_eq!(rsp.unwrap_err(), "RoundNotClosed: round 2 is still open");
+    let rsp: ContractResult<Response> = execute(&mut deps, env.clone(), admin_info.clone(), draw);
+    assert_eq!(rsp.unwrap_err(), "RoundAlreadyDrawn: round 1");
+
+    let rsp = query(&mut deps, env.clone(), QueryMsg::Winners {}).unwrap();
+    let rsp: WinnersResponse = from_binary(&rsp).unwrap();
+    assert_eq!(
+        rsp.winner,
+        Some(WinnerInfo {
+            winner_address: user1,
+            winner_amount: coin(89900000u128, "uusd"),
+            claimed: false,
+            claim_end_time: now + 350,
+        }),
+        "Only ticket holder wins the cut of what the keeper left"
+    );
+    let rsp = query(&mut deps, env.clone(), QueryMsg::RoundInfo {}).unwrap();
+    let rsp: RoundInfoResponse = from_binary(&rsp).unwrap();
+    assert_eq!(rsp.round_id, 2);
+    assert_eq!(
+        rsp.round,
+        Some(RoundInfo {
+            active: true,
+            start_date: now + 250,
+            end_date: now + 350,
+            name: "Round 2".to_string(),
+            ticket_price: Some(coin(1000u128, "uusd")),
+        }),
+        "Next round stays on the cadence"
+    );
+
+    let rsp: ContractResult<Response> = execute(
+        &mut deps,
+        env.clone(),
+        keeper_info.clone(),
+        ExecuteMsg::Tick {},
+    );
+    assert_eq!(
+        rsp.unwrap_err(),
+        format!("RoundNotEnded: ends at {}", now + 350)
+    );
+
+    let rsp: ContractResult<Response> = execute(
+        &mut deps,
+        env.clone(),
+        admin_info,
+        ExecuteMsg::UpdateConfig {
+            config: ConfigUpdate {
+                round_duration: Some(0),
+                ..ConfigUpdate::default()
+            },
+        },
+    );
+    assert_eq!(rsp.is_err(), false);
+    env.block.time = env.block.time.plus_seconds(100);
+    let rsp: ContractResult<Response> = execute(&mut deps, env, keeper_info, ExecuteMsg::Tick {});
+    assert_eq!(rsp.unwrap_err(), "AutomationDisabled");
+}
+
+#[test]
+fn delorean_tick_multi_tier_test() {
+    let backend = mock_dependencies_with_custom_querier(&[coin(900000000u128, "uusd".to_string())]);
+    let admin_info = mock_info(&String::from("admin"), &[]);
+    let keeper_info = mock_info(&String::from("keeper"), &[]);
+    let now = mock_env().block.time.nanos() / 1_000_000_000;
+    let config = Config {
+        mint_contract: "xx1".to_string(),
+        nft_contract: "xx2".to_string(),
+        nfts: vec![
+            tier_info("NFT 1", 150000000u128, "user1"),
+            tier_info("NFT 2", 75000000u128, "user2"),
+        ],
+        unstake_cooldown: MIN_UNSTAKE_COOLDOWN,
+        prize_share: 0,
+        round_duration: 100,
+        keeper_reward: Uint128::from(850000000u128),
+    };
+
+    let (instance_options, memory_limit) = mock_instance_options();
+    let mut deps = Instance::from_code(WASM, backend, instance_options, memory_limit).unwrap();
+    let rsp: ContractResult<Response> = instantiate(
+        &mut deps,
+        mock_env(),
+        admin_info.clone(),
+        InstantiateMsg {
+            config,
+            admin: None,
+            roles: vec![],
+        },
+    );
+    assert_eq!(rsp.is_err(), false);
+
+    let rsp: ContractResult<Response> = execute(
+        &mut deps,
+        mock_env(),
+        admin_info,
+        ExecuteMsg::StartRound {
+            round_info: RoundInfo {
+                active: true,
+                start_date: now,
+                end_date: now + 50,
+                name: "Day 1".to_string(),
+                ticket_price: None,
+            },
+        },
+    );
+    assert_eq!(rsp.is_err(), false);
+    let rsp: ContractResult<Response> = execute(
+        &mut deps,
+        mock_env(),
+        mock_info(&String::from("user3"), &[coin(75000000u128, "uusd")]),
+        ExecuteMsg::Mint { nft_type: 2 },
+    );
+    assert_eq!(rsp.is_err(), false);
+
+    // the second tier holds 75000000 of the balance, leaving the first tier
+    // less than the configured reward
+    let mut env = mock_env();
+    env.block.time = env.block.time.plus_seconds(100);
+    let rsp: ContractResult<Response> = execute(&mut deps, env, keeper_info, ExecuteMsg::Tick {});
+    let rsp = rsp.unwrap();
+    assert_eq!(
+        rsp.messages[0].msg,
+        CosmosMsg::Bank(BankMsg::Send {
+            to_address: "keeper".to_string(),
+            amount: vec![coin(825000000u128, "uusd")],
+        }),
+        "Reward is capped at the first tier's revenue"
+    );
+
+    let rsp = query(
+        &mut deps,
+        mock_env(),
+        QueryMsg::SharesOwed {
+            start_after: None,
+            limit: None,
+        },
+    )
+    .unwrap();
+    let rsp: SharesOwedResponse = from_binary(&rsp).unwrap();
+    let owed: Vec<(String, u128)> = rsp
+        .shares
+        .into_iter()
+        .map(|share| (share.recipient, share.amount.u128()))
+        .collect();
+    assert_eq!(
+        owed,
+        vec![("user2".to_string(), 75000000u128)],
+        "Second tier keeps its revenue"
     );
 }
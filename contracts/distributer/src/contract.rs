@@ -1,29 +1,59 @@
-use crate::errors::{ContractError, Unauthorized};
+use crate::errors::ContractError;
+use crate::migrations::{migrate_store, set_version, CONTRACT_NAME, CONTRACT_VERSION};
 use crate::msg::{
-    ClaimStatusResponse, ConfigResponse, ExecuteMsg, InstantiateMsg, MigrateMsg, QueryMsg,
+    AdjustmentEntry, AdjustmentsResponse, AllClaimStatusResponse, ClaimStatusResponse,
+    ConfigResponse, ConfigUpdate, Cw721ExecuteMsg, Cw721ReceiveMsg, DepositEntry, DepositsResponse,
+    ExecuteMsg, InstantiateMsg, MigrateMsg, Payout, QueryMsg, ShareClaimStatus,
+    SimulatePayoutsResponse, StakerResponse, StakingStateResponse, TotalsResponse,
+};
+use crate::ownership::{
+    assert_admin, assert_role, get_ownership, get_role_members, init_ownership, try_accept_admin,
+    try_grant_role, try_propose_new_admin, try_renounce_admin, try_revoke_role, Role,
+};
+use crate::state::{
+    AdjustmentKind, AdjustmentRecord, Config, DepositRecord, FundShare, PendingAdjustment,
+    StakedToken, ADJUSTMENTS, ADJUSTMENT_COUNT, CLAIM_STATE, CONFIG, DEPOSITS, DEPOSITS_BY_SENDER,
+    DEPOSIT_COUNT, FUND_STATE, LAST_CLAIM, MIN_UNSTAKE_COOLDOWN, PENDING_ADJUSTMENTS,
+    STAKED_TOKENS, STAKERS, STAKING_STATE, TOTAL_CLAIMED, VESTED_CLAIMED,
 };
-use crate::state::{Config, CLAIM_STATE, CONFIG, FUND_STATE};
 use cosmwasm_std::{
-    coin, entry_point, to_binary, Addr, BankMsg, Coin, Deps, DepsMut, Env, MessageInfo,
-    QueryResponse, Response, StdResult, Uint128,
+    attr, coin, entry_point, to_binary, Addr, Attribute, BankMsg, Coin, Decimal, Deps, DepsMut,
+    Env, Event, MessageInfo, Order, QueryResponse, Response, StdError, StdResult, Storage, Uint128,
+    WasmMsg,
 };
-use moneymarket::querier::deduct_tax;
+use cw_storage_plus::Bound;
+use delorean_common::deposit::assert_depositor;
+use delorean_common::errors::invalid_config;
+use delorean_common::events::{instantiate_event, json_attribute};
+use delorean_common::payout::payout;
+use std::convert::TryInto;
 
 pub fn instantiate(
-    deps: DepsMut,
+    mut deps: DepsMut,
     _env: Env,
-    _info: MessageInfo,
+    info: MessageInfo,
     msg: InstantiateMsg,
 ) -> Result<Response, ContractError> {
+    let admin = msg.admin.clone().unwrap_or_else(|| info.sender.to_string());
+    let event = instantiate_event(
+        CONTRACT_NAME,
+        CONTRACT_VERSION,
+        &admin,
+        &msg.roles,
+        &msg.config,
+    )?;
+    init_ownership(deps.branch(), admin, msg.roles)?;
+    validate_config(deps.as_ref(), &msg.config)?;
     CONFIG.save(deps.storage, &msg.config)?;
     let i: Uint128 = Uint128::from(0u32);
     FUND_STATE.save(deps.storage, &i)?;
-    Ok(Response::default())
+    set_version(deps.storage)?;
+    Ok(Response::default().add_event(event))
 }
 
 #[cfg_attr(not(feature = "library"), entry_point)]
-pub fn migrate(_deps: DepsMut, _env: Env, _msg: MigrateMsg) -> Result<Response, ContractError> {
-    Ok(Response::default())
+pub fn migrate(deps: DepsMut, _env: Env, _msg: MigrateMsg) -> Result<Response, ContractError> {
+    migrate_store(deps)
 }
 
 pub fn execute(
@@ -33,13 +63,35 @@ pub fn execute(
     msg: ExecuteMsg,
 ) -> Result<Response, ContractError> {
     match msg {
-        ExecuteMsg::ConfigUpdate { config } => try_config_update(deps, env, info, config),
+        ExecuteMsg::UpdateConfig { config } => try_update_config(deps, env, info, config),
 
-        ExecuteMsg::Deposit {} => try_deposit(deps, env, info),
-        ExecuteMsg::Claim {} => try_claim(deps, env, info),
-        ExecuteMsg::AdminClaimUpdate { wallet, amount } => {
-            try_claim_update(deps, env, info, wallet, amount)
+        ExecuteMsg::ProposeNewAdmin { new_admin } => {
+            Ok(try_propose_new_admin(deps, env, info, new_admin)?)
         }
+
+        ExecuteMsg::AcceptAdmin {} => Ok(try_accept_admin(deps, env, info)?),
+
+        ExecuteMsg::RenounceAdmin {} => Ok(try_renounce_admin(deps, env, info)?),
+
+        ExecuteMsg::GrantRole { role, address } => {
+            Ok(try_grant_role(deps, env, info, role, address)?)
+        }
+
+        ExecuteMsg::RevokeRole { role, address } => {
+            Ok(try_revoke_role(deps, env, info, role, address)?)
+        }
+
+        ExecuteMsg::Deposit { memo } => try_deposit(deps, env, info, memo),
+        ExecuteMsg::Claim {} => try_claim(deps, env, info),
+        ExecuteMsg::AdjustClaim {
+            wallet,
+            kind,
+            amount,
+            reason,
+        } => try_adjust_claim(deps, env, info, wallet, kind, amount, reason),
+        ExecuteMsg::ReceiveNft(receive) => try_stake_nft(deps, env, info, receive),
+        ExecuteMsg::UnstakeNft { token_id } => try_unstake_nft(deps, env, info, token_id),
+        ExecuteMsg::ClaimStakingRewards {} => try_claim_staking_rewards(deps, env, info),
     }
 }
 
@@ -50,98 +102,553 @@ fn transfer_funds(to: &Addr, cns: Vec<Coin>) -> BankMsg {
     };
 }
 
-pub fn try_config_update(
+pub fn try_update_config(
     deps: DepsMut,
     _env: Env,
     info: MessageInfo,
-    new_config: Config,
+    update: ConfigUpdate,
 ) -> Result<Response, ContractError> {
-    let mut _config = CONFIG.load(deps.storage)?;
-    if info.sender != _config.admin {
-        return Err(Unauthorized {}.build());
+    assert_role(deps.storage, &info.sender, Role::ConfigManager)?;
+    let mut config = CONFIG.load(deps.storage)?;
+    let mut changes: Vec<Attribute> = vec![];
+
+    if let Some(shares) = update.shares {
+        changes.push(attr("shares", json_attribute(&shares)?));
+        config.shares = shares;
+    }
+    if let Some(deposit_policy) = update.deposit_policy {
+        changes.push(attr("deposit_policy", json_attribute(&deposit_policy)?));
+        config.deposit_policy = deposit_policy;
     }
-    CONFIG.save(deps.storage, &new_config)?;
+    if let Some(nft_staking) = update.nft_staking {
+        // staked tokens are returned through the configured contract
+        let total_staked = STAKING_STATE
+            .may_load(deps.storage)?
+            .unwrap_or_default()
+            .total_staked;
+        let nft_contract = config.nft_staking.as_ref().map(|s| &s.nft_contract);
+        if total_staked > 0 && nft_staking.as_ref().map(|s| &s.nft_contract) != nft_contract {
+            return Err(invalid_config("nft_staking").into());
+        }
+        changes.push(attr("nft_staking", json_attribute(&nft_staking)?));
+        config.nft_staking = nft_staking;
+    }
+
+    validate_config(deps.as_ref(), &config)?;
+    CONFIG.save(deps.storage, &config)?;
+    Ok(Response::default()
+        .add_attribute("action", "update_config")
+        .add_attributes(changes.clone())
+        .add_event(Event::new("config-updated").add_attributes(changes)))
+}
 
-    Ok(Response::default())
+fn validate_config(deps: Deps, config: &Config) -> Result<(), ContractError> {
+    let mut total_share: u32 = 0;
+    for (i, fund_share) in config.shares.iter().enumerate() {
+        deps.api
+            .addr_validate(&fund_share.address)
+            .map_err(|_| invalid_config("shares"))?;
+        // claims are tracked per wallet, one entry each
+        if config.shares[..i]
+            .iter()
+            .any(|other| other.address == fund_share.address)
+        {
+            return Err(invalid_config("shares").into());
+        }
+        if let Some(vesting) = &fund_share.vesting {
+            if vesting.cliff > vesting.duration {
+                return Err(invalid_config("shares").into());
+            }
+        }
+        total_share += fund_share.share;
+    }
+    if total_share > 10000 {
+        return Err(invalid_config("shares").into());
+    }
+    if let Some(nft_staking) = &config.nft_staking {
+        deps.api
+            .addr_validate(&nft_staking.nft_contract)
+            .map_err(|_| invalid_config("nft_staking"))?;
+        if total_share + nft_staking.share > 10000
+            || nft_staking.unstake_cooldown < MIN_UNSTAKE_COOLDOWN
+        {
+            return Err(invalid_config("nft_staking").into());
+        }
+    }
+    for depositor in &config.deposit_policy.allowlist {
+        deps.api
+            .addr_validate(depositor)
+            .map_err(|_| invalid_config("deposit_policy"))?;
+    }
+    Ok(())
 }
 
-pub fn try_claim_update(
+pub fn try_adjust_claim(
     deps: DepsMut,
     _env: Env,
     info: MessageInfo,
     wallet: String,
+    kind: AdjustmentKind,
     amount: Uint128,
+    reason: String,
 ) -> Result<Response, ContractError> {
-    let mut _config = CONFIG.load(deps.storage)?;
-    if info.sender != _config.admin {
-        return Err(Unauthorized {}.build());
+    assert_admin(deps.storage, &info.sender)?;
+    let reason_length = reason.trim().chars().count();
+    if reason_length == 0 || reason_length > MAX_MEMO_LENGTH as usize {
+        return Err(ContractError::InvalidReason {
+            max: MAX_MEMO_LENGTH,
+        });
+    }
+    if amount.is_zero() {
+        return Err(ContractError::ZeroAdjustment {});
+    }
+    let config = CONFIG.load(deps.storage)?;
+    let fund_share = match config.shares.into_iter().find(|s| s.address == wallet) {
+        Some(fund_share) => fund_share,
+        None => return Err(ContractError::NotShareholder { wallet }),
+    };
+    let funds = FUND_STATE.load(deps.storage)?;
+    if kind == AdjustmentKind::Debit {
+        let claimable =
+            entitlement(deps.as_ref(), &fund_share, funds, block_time(&_env))?.claimable;
+        if amount > claimable {
+            return Err(ContractError::DebitExceedsClaimable {
+                wallet,
+                claimable,
+                amount,
+            });
+        }
+    }
+
+    let mut pending = PENDING_ADJUSTMENTS
+        .may_load(deps.storage, &wallet)?
+        .unwrap_or_default();
+    pending.apply(&kind, amount);
+    PENDING_ADJUSTMENTS.save(deps.storage, &wallet, &pending)?;
+    if kind == AdjustmentKind::Credit {
+        assert_solvent(deps.as_ref(), &_env)?;
     }
-    CLAIM_STATE.save(deps.storage, &wallet.to_string(), &amount)?;
 
-    Ok(Response::default())
+    let id = ADJUSTMENT_COUNT.may_load(deps.storage)?.unwrap_or_default() + 1;
+    ADJUSTMENT_COUNT.save(deps.storage, &id)?;
+    let record = AdjustmentRecord {
+        wallet,
+        kind,
+        amount,
+        reason,
+        admin: info.sender.to_string(),
+        height: _env.block.height,
+        time: block_time(&_env),
+    };
+    ADJUSTMENTS.save(deps.storage, id.into(), &record)?;
+
+    Ok(Response::default()
+        .add_attribute("action", "adjust_claim")
+        .add_event(
+            Event::new("claim-adjusted")
+                .add_attribute("adjustment_id", id.to_string())
+                .add_attribute("wallet", record.wallet)
+                .add_attribute("kind", record.kind.as_str())
+                .add_attribute("amount", record.amount)
+                .add_attribute("reason", record.reason)
+                .add_attribute("admin", record.admin),
+        ))
 }
 
-pub fn try_claim(deps: DepsMut, _env: Env, info: MessageInfo) -> Result<Response, ContractError> {
-    let wallet = info.sender.clone();
+/// Credits can promise more than the contract holds
+fn assert_solvent(deps: Deps, env: &Env) -> Result<(), ContractError> {
+    let config = CONFIG.load(deps.storage)?;
+    let funds = FUND_STATE.load(deps.storage)?;
+    let liability = outstanding_liability(deps, &config, funds, block_time(env))?;
+    let balance = deps
+        .querier
+        .query_balance(&env.contract.address, "uusd")?
+        .amount;
+    if liability > balance {
+        return Err(ContractError::LiabilityExceedsBalance { liability, balance });
+    }
+    Ok(())
+}
+
+/// Everything still owed to the shares, unvested included
+fn outstanding_liability(
+    deps: Deps,
+    config: &Config,
+    funds: Uint128,
+    time: u64,
+) -> StdResult<Uint128> {
+    let mut liability = Uint128::zero();
+    for fund_share in &config.shares {
+        let entitlement = entitlement(deps, fund_share, funds, time)?;
+        liability += entitlement.claimable + entitlement.unvested;
+    }
+    if let Some(state) = STAKING_STATE.may_load(deps.storage)? {
+        liability += state.funded - state.paid;
+    }
+    Ok(liability)
+}
 
+fn block_time(env: &Env) -> u64 {
+    env.block.time.nanos() / 1_000_000_000
+}
+
+/// Spreads the staking part of a deposit over the staked tokens
+fn fund_stakers(storage: &mut dyn Storage, config: &Config, amount: Uint128) -> StdResult<()> {
+    let nft_staking = match &config.nft_staking {
+        Some(nft_staking) => nft_staking,
+        None => return Ok(()),
+    };
+    let mut state = STAKING_STATE.may_load(storage)?.unwrap_or_default();
+    let rewards = nft_staking.get_share(amount);
+    state.funded += rewards;
+    let to_distribute = rewards + state.undistributed;
+    if state.total_staked == 0 {
+        state.undistributed = to_distribute;
+    } else {
+        state.reward_index =
+            state.reward_index + Decimal::from_ratio(to_distribute, state.total_staked);
+        state.undistributed = Uint128::zero();
+    }
+    STAKING_STATE.save(storage, &state)
+}
+
+pub fn try_stake_nft(
+    deps: DepsMut,
+    env: Env,
+    info: MessageInfo,
+    receive: Cw721ReceiveMsg,
+) -> Result<Response, ContractError> {
     let config = CONFIG.load(deps.storage)?;
-    let mut found = None;
-    for fund_share in config.shares {
-        if fund_share.address == wallet {
-            found = Some(fund_share);
-            break;
+    let nft_staking = config
+        .nft_staking
+        .ok_or(ContractError::StakingDisabled {})?;
+    if info.sender != nft_staking.nft_contract {
+        return Err(ContractError::WrongNftContract {
+            contract: info.sender.to_string(),
+        });
+    }
+    let wallet = deps.api.addr_validate(&receive.sender)?;
+
+    let mut state = STAKING_STATE.may_load(deps.storage)?.unwrap_or_default();
+    let mut staker = STAKERS
+        .may_load(deps.storage, wallet.as_str())?
+        .unwrap_or_default();
+    staker.settle(state.reward_index);
+    staker.staked += 1;
+    state.total_staked += 1;
+    STAKERS.save(deps.storage, wallet.as_str(), &staker)?;
+    STAKING_STATE.save(deps.storage, &state)?;
+    let staked = StakedToken {
+        owner: wallet.to_string(),
+        staked_at: block_time(&env),
+    };
+    STAKED_TOKENS.save(deps.storage, &receive.token_id, &staked)?;
+
+    Ok(Response::default()
+        .add_attribute("action", "stake_nft")
+        .add_event(
+            Event::new("nft-staked")
+                .add_attribute("wallet", wallet)
+                .add_attribute("token_id", receive.token_id)
+                .add_attribute("staked", staker.staked.to_string())
+                .add_attribute("total_staked", state.total_staked.to_string()),
+        ))
+}
+
+pub fn try_unstake_nft(
+    deps: DepsMut,
+    env: Env,
+    info: MessageInfo,
+    token_id: String,
+) -> Result<Response, ContractError> {
+    let staked = match STAKED_TOKENS.may_load(deps.storage, &token_id)? {
+        Some(staked) if staked.owner == info.sender.as_str() => staked,
+        _ => {
+            return Err(ContractError::NotTokenOwner {
+                sender: info.sender.to_string(),
+                token_id,
+            })
         }
+    };
+    let config = CONFIG.load(deps.storage)?;
+    let nft_staking = config
+        .nft_staking
+        .ok_or(ContractError::StakingDisabled {})?;
+    let unlock_time = staked.unlock_time(nft_staking.unstake_cooldown);
+    if block_time(&env) < unlock_time {
+        return Err(ContractError::StakeLocked {
+            token_id,
+            unlock_time,
+        });
     }
 
-    if found == None {
-        return Err(ContractError::NotFound {});
+    let mut state = STAKING_STATE.load(deps.storage)?;
+    let mut staker = STAKERS.load(deps.storage, info.sender.as_str())?;
+    staker.settle(state.reward_index);
+    staker.staked -= 1;
+    state.total_staked -= 1;
+    STAKERS.save(deps.storage, info.sender.as_str(), &staker)?;
+    STAKING_STATE.save(deps.storage, &state)?;
+    STAKED_TOKENS.remove(deps.storage, &token_id);
+
+    let msg = Cw721ExecuteMsg::TransferNft {
+        recipient: info.sender.to_string(),
+        token_id: token_id.clone(),
+    };
+    Ok(Response::default()
+        .add_message(WasmMsg::Execute {
+            contract_addr: nft_staking.nft_contract,
+            msg: to_binary(&msg)?,
+            funds: vec![],
+        })
+        .add_attribute("action", "unstake_nft")
+        .add_event(
+            Event::new("nft-unstaked")
+                .add_attribute("wallet", info.sender)
+                .add_attribute("token_id", token_id)
+                .add_attribute("staked", staker.staked.to_string())
+                .add_attribute("total_staked", state.total_staked.to_string()),
+        ))
+}
+
+pub fn try_claim_staking_rewards(
+    deps: DepsMut,
+    _env: Env,
+    info: MessageInfo,
+) -> Result<Response, ContractError> {
+    let mut state = STAKING_STATE.may_load(deps.storage)?.unwrap_or_default();
+    let mut staker = STAKERS
+        .may_load(deps.storage, info.sender.as_str())?
+        .unwrap_or_default();
+    staker.settle(state.reward_index);
+    let rewards = staker.pending_rewards;
+    if rewards.is_zero() {
+        return Err(ContractError::NothingToClaim {});
+    }
+    staker.pending_rewards = Uint128::zero();
+    state.paid += rewards;
+    if staker.staked == 0 {
+        STAKERS.remove(deps.storage, info.sender.as_str());
     } else {
-        let funds = FUND_STATE.load(deps.storage)?;
-        let claimable_ust;
-        let claimed_ust = CLAIM_STATE.may_load(deps.storage, &wallet.to_string())?;
-        let mut claimable: Uint128 = funds.clone();
-        match claimed_ust {
-            None => {}
-            Some(claimed_ust) => claimable = claimable - claimed_ust,
+        STAKERS.save(deps.storage, info.sender.as_str(), &staker)?;
+    }
+    STAKING_STATE.save(deps.storage, &state)?;
+    let total_claimed = TOTAL_CLAIMED.may_load(deps.storage)?.unwrap_or_default();
+    TOTAL_CLAIMED.save(deps.storage, &(total_claimed + rewards))?;
+
+    let payout = payout(
+        deps.as_ref(),
+        info.sender.to_string(),
+        coin(rewards.u128(), "uusd"),
+    )?;
+    Ok(Response::default()
+        .add_message(transfer_funds(&info.sender, vec![payout.net.clone()]))
+        .add_attribute("action", "claim_staking_rewards")
+        .add_event(
+            Event::new("staking-rewards-claimed")
+                .add_attribute("wallet", payout.recipient)
+                .add_attribute("amount", payout.net.amount)
+                .add_attribute("denom", payout.net.denom),
+        ))
+}
+
+/// A wallet's position at a point in time
+struct Entitlement {
+    /// Vested and not yet claimed, before adjustments
+    vested: Uint128,
+    unvested: Uint128,
+    /// Share of the funds paid out so far
+    claimed: Uint128,
+    /// Vested with pending adjustments applied
+    claimable: Uint128,
+    /// Debit left for later claims once this one is paid
+    remaining_debit: Uint128,
+}
+
+fn entitlement(
+    deps: Deps,
+    fund_share: &FundShare,
+    funds: Uint128,
+    time: u64,
+) -> StdResult<Entitlement> {
+    let mark = CLAIM_STATE
+        .may_load(deps.storage, &fund_share.address)?
+        .unwrap_or_default();
+    let accrued = fund_share.get_share(funds.saturating_sub(mark));
+    let vested_claimed = VESTED_CLAIMED
+        .may_load(deps.storage, &fund_share.address)?
+        .unwrap_or_default();
+    let vested_total = match &fund_share.vesting {
+        Some(vesting) => vesting.vested(accrued, time),
+        None => accrued,
+    };
+    let vested = vested_total.saturating_sub(vested_claimed);
+    let pending = PENDING_ADJUSTMENTS
+        .may_load(deps.storage, &fund_share.address)?
+        .unwrap_or_default();
+    let gross = vested + pending.credit;
+    Ok(Entitlement {
+        vested,
+        unvested: accrued - vested_total,
+        claimed: fund_share.get_share(mark) + vested_claimed,
+        claimable: gross.saturating_sub(pending.debit),
+        remaining_debit: pending.debit.saturating_sub(gross),
+    })
+}
+
+pub fn try_claim(deps: DepsMut, _env: Env, info: MessageInfo) -> Result<Response, ContractError> {
+    let wallet = info.sender.clone();
+    let (share, payout, entitlement) = match claim_payout(deps.as_ref(), &_env, wallet.as_str())? {
+        Some(claim) => claim,
+        None => {
+            return Err(ContractError::NotShareholder {
+                wallet: wallet.to_string(),
+            })
         }
-        let share = found.unwrap();
-        claimable_ust = share.get_share(claimable);
+    };
+    let funds = FUND_STATE.load(deps.storage)?;
+    if share.vesting.is_some() {
+        // the mark stays so the unvested rest keeps vesting
+        VESTED_CLAIMED.update(deps.storage, wallet.as_str(), |c| -> StdResult<_> {
+            Ok(c.unwrap_or_default() + entitlement.vested)
+        })?;
+    } else {
         CLAIM_STATE.save(deps.storage, &wallet.to_string(), &funds)?;
-
-        Ok(Response::new()
-            .add_attribute("action", "claim")
-            .add_attribute("ust", claimable_ust.to_string())
-            .add_attribute("wallet", wallet.clone().to_string())
-            .add_message(transfer_funds(
-                &wallet,
-                vec![deduct_tax(
-                    deps.as_ref(),
-                    coin(claimable_ust.u128(), "uusd"),
-                )?],
-            )))
+        VESTED_CLAIMED.remove(deps.storage, wallet.as_str());
     }
+    let remaining_debit = entitlement.remaining_debit;
+    if remaining_debit.is_zero() {
+        PENDING_ADJUSTMENTS.remove(deps.storage, wallet.as_str());
+    } else {
+        PENDING_ADJUSTMENTS.save(
+            deps.storage,
+            wallet.as_str(),
+            &PendingAdjustment {
+                credit: Uint128::zero(),
+                debit: remaining_debit,
+            },
+        )?;
+    }
+    LAST_CLAIM.save(deps.storage, wallet.as_str(), &block_time(&_env))?;
+    let total_claimed = TOTAL_CLAIMED.may_load(deps.storage)?.unwrap_or_default();
+    TOTAL_CLAIMED.save(deps.storage, &(total_claimed + payout.gross.amount))?;
+
+    Ok(Response::new()
+        .add_attribute("action", "claim")
+        .add_attribute("ust", payout.gross.amount.to_string())
+        .add_attribute("wallet", wallet.clone().to_string())
+        .add_event(
+            Event::new("share-claimed")
+                .add_attribute("wallet", wallet.to_string())
+                .add_attribute("share", share.share.to_string())
+                .add_attribute("amount", payout.net.amount)
+                .add_attribute("denom", payout.net.denom.clone())
+                .add_attribute("claimed_up_to", funds),
+        )
+        .add_message(transfer_funds(&wallet, vec![payout.net])))
 }
 
-pub fn try_deposit(deps: DepsMut, _env: Env, info: MessageInfo) -> Result<Response, ContractError> {
+/// What a claim by the wallet would pay out, None if it holds no share
+fn claim_payout(
+    deps: Deps,
+    env: &Env,
+    wallet: &str,
+) -> StdResult<Option<(FundShare, Payout, Entitlement)>> {
+    let config = CONFIG.load(deps.storage)?;
+    let share = match config.shares.into_iter().find(|s| s.address == wallet) {
+        Some(share) => share,
+        None => return Ok(None),
+    };
+    let funds = FUND_STATE.load(deps.storage)?;
+    let entitlement = entitlement(deps, &share, funds, block_time(env))?;
+    let payout = payout(
+        deps,
+        wallet.to_string(),
+        coin(entitlement.claimable.u128(), "uusd"),
+    )?;
+    Ok(Some((share, payout, entitlement)))
+}
+
+const MAX_MEMO_LENGTH: u32 = 128;
+
+pub fn try_deposit(
+    deps: DepsMut,
+    _env: Env,
+    info: MessageInfo,
+    memo: Option<String>,
+) -> Result<Response, ContractError> {
     let sent_funds = info.funds.clone();
 
     if sent_funds.is_empty() {
-        return Err(ContractError::EmptyBalance {});
+        return Err(ContractError::NoFundsSent {});
     }
     if &sent_funds[0].denom != "uusd" {
-        return Err(ContractError::EmptyBalance {});
+        return Err(ContractError::WrongDenom {
+            expected: "uusd".to_string(),
+            received: sent_funds[0].denom.clone(),
+        });
+    }
+    let config = CONFIG.load(deps.storage)?;
+    assert_depositor(&config.deposit_policy, &info.sender, sent_funds[0].amount)?;
+    if let Some(memo) = &memo {
+        if memo.chars().count() > MAX_MEMO_LENGTH as usize {
+            return Err(ContractError::MemoTooLong {
+                max: MAX_MEMO_LENGTH,
+            });
+        }
     }
     let mut amnt = FUND_STATE.load(deps.storage)?;
     amnt = amnt + &sent_funds[0].amount;
     FUND_STATE.save(deps.storage, &amnt)?;
+    fund_stakers(deps.storage, &config, sent_funds[0].amount)?;
+
+    let id = DEPOSIT_COUNT.may_load(deps.storage)?.unwrap_or_default() + 1;
+    DEPOSIT_COUNT.save(deps.storage, &id)?;
+    let record = DepositRecord {
+        sender: info.sender.to_string(),
+        amount: sent_funds[0].amount,
+        denom: sent_funds[0].denom.clone(),
+        height: _env.block.height,
+        time: block_time(&_env),
+        memo: memo.clone(),
+    };
+    DEPOSITS.save(deps.storage, id.into(), &record)?;
+    DEPOSITS_BY_SENDER.save(deps.storage, (info.sender.as_str(), id.into()), &true)?;
 
-    Ok(Response::default())
+    Ok(Response::default()
+        .add_attribute("action", "deposit")
+        .add_event(
+            Event::new("fund-deposited")
+                .add_attribute("deposit_id", id.to_string())
+                .add_attribute("sender", info.sender)
+                .add_attribute("amount", sent_funds[0].amount)
+                .add_attribute("denom", sent_funds[0].denom.clone())
+                .add_attribute("memo", memo.unwrap_or_default())
+                .add_attribute("total_funds", amnt),
+        ))
 }
 
 pub fn query(deps: Deps, env: Env, msg: QueryMsg) -> StdResult<QueryResponse> {
     match msg {
         QueryMsg::Config {} => get_config(deps, env),
+        QueryMsg::Ownership {} => get_ownership(deps, env),
+        QueryMsg::RoleMembers { role } => get_role_members(deps, env, role),
         QueryMsg::ClaimStatus { wallet } => get_claim_status(deps, env, wallet),
+        QueryMsg::AllClaimStatus { start_after, limit } => {
+            get_all_claim_status(deps, env, start_after, limit)
+        }
+        QueryMsg::Totals {} => get_totals(deps, env),
+        QueryMsg::Deposits {
+            sender,
+            start_after,
+            limit,
+        } => get_deposits(deps, env, sender, start_after, limit),
+        QueryMsg::Adjustments { start_after, limit } => {
+            get_adjustments(deps, env, start_after, limit)
+        }
+        QueryMsg::StakingState {} => get_staking_state(deps, env),
+        QueryMsg::Staker { address } => get_staker(deps, env, address),
+        QueryMsg::SimulateClaim { wallet } => get_simulate_claim(deps, env, wallet),
     }
 }
 
@@ -151,7 +658,7 @@ fn get_config(deps: Deps, _env: Env) -> StdResult<QueryResponse> {
     to_binary(&rsp)
 }
 
-fn get_claim_status(deps: Deps, _env: Env, wallet: String) -> StdResult<QueryResponse> {
+fn get_claim_status(deps: Deps, env: Env, wallet: String) -> StdResult<QueryResponse> {
     let config = CONFIG.load(deps.storage)?;
     let mut found = None;
     for fund_share in config.shares {
@@ -160,34 +667,186 @@ fn get_claim_status(deps: Deps, _env: Env, wallet: String) -> StdResult<QueryRes
             break;
         }
     }
-    let rsp: ClaimStatusResponse;
-    if found == None {
-        rsp = ClaimStatusResponse {
+    let rsp = match found {
+        None => ClaimStatusResponse {
             claimable_ust: Uint128::from(0u32),
+            vested_ust: Uint128::zero(),
+            unvested_ust: Uint128::zero(),
             claimed_ust: None,
             total_ust: Uint128::from(0u32),
             share: 0u32,
-        };
-    } else {
-        let funds = FUND_STATE.load(deps.storage)?;
-        let claimed_ust = CLAIM_STATE.may_load(deps.storage, &wallet)?;
-        let mut claimable: Uint128 = funds.clone();
-        let mut t: Uint128 = Uint128::zero();
-        match claimed_ust {
-            None => {}
-            Some(claimed_ust) => {
-                claimable = claimable - claimed_ust;
-                t = claimed_ust;
+        },
+        Some(share) => {
+            let funds = FUND_STATE.load(deps.storage)?;
+            let entitlement = entitlement(deps, &share, funds, block_time(&env))?;
+            ClaimStatusResponse {
+                claimable_ust: entitlement.claimable,
+                vested_ust: entitlement.vested,
+                unvested_ust: entitlement.unvested,
+                claimed_ust: Some(entitlement.claimed),
+                total_ust: funds,
+                share: share.share,
             }
         }
-        let share = found.unwrap();
-        rsp = ClaimStatusResponse {
-            claimable_ust: share.clone().get_share(claimable),
-            claimed_ust: Some(share.clone().get_share(t)),
-            total_ust: funds.clone(),
-            share: share.share,
-        };
-    }
+    };
 
     to_binary(&rsp)
 }
+
+const DEFAULT_LIMIT: u32 = 30;
+const MAX_LIMIT: u32 = 100;
+
+/// Shares are listed in config order
+fn get_all_claim_status(
+    deps: Deps,
+    env: Env,
+    start_after: Option<String>,
+    limit: Option<u32>,
+) -> StdResult<QueryResponse> {
+    let config = CONFIG.load(deps.storage)?;
+    let funds = FUND_STATE.load(deps.storage)?;
+    let limit = limit.unwrap_or(DEFAULT_LIMIT).min(MAX_LIMIT) as usize;
+    let start = match start_after {
+        Some(wallet) => config
+            .shares
+            .iter()
+            .position(|fund_share| fund_share.address == wallet)
+            .map_or(config.shares.len(), |i| i + 1),
+        None => 0,
+    };
+    let claims: StdResult<Vec<ShareClaimStatus>> = config
+        .shares
+        .into_iter()
+        .skip(start)
+        .take(limit)
+        .map(|fund_share| {
+            let entitlement = entitlement(deps, &fund_share, funds, block_time(&env))?;
+            Ok(ShareClaimStatus {
+                last_claim_time: LAST_CLAIM.may_load(deps.storage, &fund_share.address)?,
+                claimed_ust: entitlement.claimed,
+                claimable_ust: entitlement.claimable,
+                share: fund_share.share,
+                note: fund_share.note,
+                wallet: fund_share.address,
+            })
+        })
+        .collect();
+    to_binary(&AllClaimStatusResponse { claims: claims? })
+}
+
+fn get_totals(deps: Deps, env: Env) -> StdResult<QueryResponse> {
+    let config = CONFIG.load(deps.storage)?;
+    let funds = FUND_STATE.load(deps.storage)?;
+    let outstanding_liability = outstanding_liability(deps, &config, funds, block_time(&env))?;
+    let balance = deps
+        .querier
+        .query_balance(&env.contract.address, "uusd")?
+        .amount;
+    let rsp = TotalsResponse {
+        total_deposited: funds,
+        total_claimed: TOTAL_CLAIMED.may_load(deps.storage)?.unwrap_or_default(),
+        outstanding_liability,
+        balance,
+        solvent: outstanding_liability <= balance,
+    };
+    to_binary(&rsp)
+}
+
+fn ledger_id(key: Vec<u8>) -> StdResult<u64> {
+    let bytes: [u8; 8] = key
+        .as_slice()
+        .try_into()
+        .map_err(|_| StdError::generic_err("Invalid ledger key"))?;
+    Ok(u64::from_be_bytes(bytes))
+}
+
+/// Oldest first, optionally only the deposits of one sender
+fn get_deposits(
+    deps: Deps,
+    _env: Env,
+    sender: Option<String>,
+    start_after: Option<u64>,
+    limit: Option<u32>,
+) -> StdResult<QueryResponse> {
+    let limit = limit.unwrap_or(DEFAULT_LIMIT).min(MAX_LIMIT) as usize;
+    let start = start_after.map(Bound::exclusive_int);
+    let keys: Vec<Vec<u8>> = match sender {
+        Some(sender) => DEPOSITS_BY_SENDER
+            .prefix(&sender)
+            .keys(deps.storage, start, None, Order::Ascending)
+            .take(limit)
+            .collect(),
+        None => DEPOSITS
+            .keys(deps.storage, start, None, Order::Ascending)
+            .take(limit)
+            .collect(),
+    };
+    let deposits: StdResult<Vec<DepositEntry>> = keys
+        .into_iter()
+        .map(|key| {
+            let id = ledger_id(key)?;
+            Ok(DepositEntry {
+                id,
+                deposit: DEPOSITS.load(deps.storage, id.into())?,
+            })
+        })
+        .collect();
+    to_binary(&DepositsResponse {
+        deposits: deposits?,
+    })
+}
+
+fn get_adjustments(
+    deps: Deps,
+    _env: Env,
+    start_after: Option<u64>,
+    limit: Option<u32>,
+) -> StdResult<QueryResponse> {
+    let limit = limit.unwrap_or(DEFAULT_LIMIT).min(MAX_LIMIT) as usize;
+    let start = start_after.map(Bound::exclusive_int);
+    let adjustments: StdResult<Vec<AdjustmentEntry>> = ADJUSTMENTS
+        .range(deps.storage, start, None, Order::Ascending)
+        .take(limit)
+        .map(|item| {
+            let (key, adjustment) = item?;
+            Ok(AdjustmentEntry {
+                id: ledger_id(key)?,
+                adjustment,
+            })
+        })
+        .collect();
+    to_binary(&AdjustmentsResponse {
+        adjustments: adjustments?,
+    })
+}
+
+fn get_staking_state(deps: Deps, _env: Env) -> StdResult<QueryResponse> {
+    let config = CONFIG.load(deps.storage)?;
+    let rsp = StakingStateResponse {
+        nft_staking: config.nft_staking,
+        state: STAKING_STATE.may_load(deps.storage)?.unwrap_or_default(),
+    };
+    to_binary(&rsp)
+}
+
+fn get_staker(deps: Deps, _env: Env, address: String) -> StdResult<QueryResponse> {
+    let state = STAKING_STATE.may_load(deps.storage)?.unwrap_or_default();
+    let mut staker = STAKERS
+        .may_load(deps.storage, &address)?
+        .unwrap_or_default();
+    staker.settle(state.reward_index);
+    let rsp = StakerResponse {
+        address,
+        staked: staker.staked,
+        pending_rewards: staker.pending_rewards,
+    };
+    to_binary(&rsp)
+}
+
+fn get_simulate_claim(deps: Deps, env: Env, wallet: String) -> StdResult<QueryResponse> {
+    let payouts = match claim_payout(deps, &env, &wallet)? {
+        Some((_, payout, _)) => vec![payout],
+        None => vec![],
+    };
+    to_binary(&SimulatePayoutsResponse { payouts })
+}
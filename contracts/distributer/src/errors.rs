@@ -1,33 +1,58 @@
-use cosmwasm_std::StdError;
-use snafu::Snafu;
+use cosmwasm_std::{StdError, Uint128};
+use delorean_common::errors::CommonError;
+use thiserror::Error;
 
-#[derive(Snafu, Debug)]
-#[snafu(visibility = "pub(crate)")]
+#[derive(Error, Debug)]
 pub enum ContractError {
-    /// this is needed so we can use `bucket.load(...)?` and have it auto-converted to the custom error
-    #[snafu(display("StdError: {}", original))]
-    Std { original: StdError },
-    #[snafu(display("Unauthorized"))]
-    Unauthorized { backtrace: Option<snafu::Backtrace> },
+    #[error("{0}")]
+    Std(#[from] StdError),
 
-    #[snafu(display("EmptyBalance"))]
-    EmptyBalance {},
+    #[error("{0}")]
+    Common(#[from] CommonError),
 
-    #[snafu(display("InsufficientFund"))]
-    InsufficientFund {},
+    #[error("NoFundsSent")]
+    NoFundsSent {},
 
-    #[snafu(display("MintLimitReached"))]
-    MintLimitReached {},
+    #[error("WrongDenom: expected {expected}, received {received}")]
+    WrongDenom { expected: String, received: String },
 
-    #[snafu(display("NotFound"))]
-    NotFound {},
+    #[error("NotShareholder: {wallet}")]
+    NotShareholder { wallet: String },
 
-    #[snafu(display("PriceMismatch"))]
-    PriceMismatch {},
-}
+    #[error("MemoTooLong: at most {max} characters")]
+    MemoTooLong { max: u32 },
+
+    #[error("InvalidReason: 1 to {max} characters")]
+    InvalidReason { max: u32 },
+
+    #[error("ZeroAdjustment")]
+    ZeroAdjustment {},
+
+    #[error("DebitExceedsClaimable: {wallet} can claim {claimable}, debit {amount}")]
+    DebitExceedsClaimable {
+        wallet: String,
+        claimable: Uint128,
+        amount: Uint128,
+    },
+
+    #[error("StakingDisabled")]
+    StakingDisabled {},
+
+    #[error("WrongNftContract: {contract}")]
+    WrongNftContract { contract: String },
+
+    #[error("NotTokenOwner: {sender} did not stake {token_id}")]
+    NotTokenOwner { sender: String, token_id: String },
+
+    #[error("StakeLocked: {token_id} unlocks at {unlock_time}")]
+    StakeLocked { token_id: String, unlock_time: u64 },
+
+    #[error("NothingToClaim")]
+    NothingToClaim {},
 
-impl From<StdError> for ContractError {
-    fn from(original: StdError) -> Self {
-        Std { original }.build()
-    }
+    #[error("LiabilityExceedsBalance: liability {liability}, balance {balance}")]
+    LiabilityExceedsBalance {
+        liability: Uint128,
+        balance: Uint128,
+    },
 }
@@ -1,5 +1,7 @@
 pub mod contract;
+pub mod migrations;
 pub mod msg;
+pub mod ownership;
 pub mod state;
 
 pub mod errors;
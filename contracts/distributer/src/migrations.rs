@@ -0,0 +1,63 @@
+use crate::errors::ContractError;
+use crate::ownership::{init_ownership, Role};
+use crate::state::{
+    Config, DepositPolicy, FundShare, CLAIM_STATE, CONFIG, CONFIG_KEY, FUND_STATE, TOTAL_CLAIMED,
+};
+use cosmwasm_std::{DepsMut, Response, StdResult, Storage, Uint128};
+use cw2::set_contract_version;
+use cw_storage_plus::Item;
+use delorean_common::versioning::{assert_upgrade, migrate_response};
+use semver::Version;
+use serde::{Deserialize, Serialize};
+
+pub const CONTRACT_NAME: &str = concat!("crates.io:", env!("CARGO_PKG_NAME"));
+pub const CONTRACT_VERSION: &str = env!("CARGO_PKG_VERSION");
+
+/// Config layout from before the admin moved into ownership
+#[derive(Serialize, Deserialize)]
+struct LegacyConfig {
+    admin: String,
+    shares: Vec<FundShare>,
+}
+
+const LEGACY_CONFIG: Item<LegacyConfig> = Item::new(CONFIG_KEY);
+
+pub fn set_version(storage: &mut dyn Storage) -> StdResult<()> {
+    set_contract_version(storage, CONTRACT_NAME, CONTRACT_VERSION)
+}
+
+pub fn migrate_store(mut deps: DepsMut) -> Result<Response, ContractError> {
+    let stored = assert_upgrade(deps.storage, CONTRACT_NAME, CONTRACT_VERSION)?;
+    if stored < Version::new(0, 2, 0) {
+        migrate_from_legacy(deps.branch())?;
+    }
+
+    set_version(deps.storage)?;
+    Ok(migrate_response(CONTRACT_NAME, &stored, CONTRACT_VERSION))
+}
+
+/// Moves the admin into ownership, fund and claim state keep their layout
+fn migrate_from_legacy(mut deps: DepsMut) -> Result<(), ContractError> {
+    let legacy = LEGACY_CONFIG.load(deps.storage)?;
+    init_ownership::<Role>(deps.branch(), legacy.admin, vec![])?;
+    CONFIG.save(
+        deps.storage,
+        &Config {
+            shares: legacy.shares.clone(),
+            deposit_policy: DepositPolicy::default(),
+            nft_staking: None,
+        },
+    )?;
+
+    // claims before the counter existed, as far as the current shares tell
+    let funds = FUND_STATE.may_load(deps.storage)?.unwrap_or_default();
+    let mut total_claimed = Uint128::zero();
+    for fund_share in &legacy.shares {
+        let claimed = CLAIM_STATE
+            .may_load(deps.storage, &fund_share.address)?
+            .unwrap_or_default();
+        total_claimed += fund_share.get_share(claimed.min(funds));
+    }
+    TOTAL_CLAIMED.save(deps.storage, &total_claimed)?;
+    Ok(())
+}
@@ -1,29 +1,130 @@
 use std::string;
 
-use crate::state::Config;
-use cosmwasm_std::Uint128;
+use crate::ownership::{Role, RoleGrant};
+use crate::state::{
+    AdjustmentKind, AdjustmentRecord, Config, DepositPolicy, DepositRecord, FundShare, NftStaking,
+    StakingState,
+};
+use cosmwasm_std::{Binary, Uint128};
+pub use delorean_common::ownership::OwnershipResponse;
+pub use delorean_common::payout::Payout;
 use schemars::JsonSchema;
-use serde::{Deserialize, Serialize};
+use serde::{Deserialize, Deserializer, Serialize};
 
 #[derive(Serialize, Deserialize, Clone, Debug, PartialEq, JsonSchema)]
 pub struct InstantiateMsg {
     pub config: Config,
+    /// Defaults to the instantiating wallet
+    pub admin: Option<String>,
+    #[serde(default)]
+    pub roles: Vec<RoleGrant>,
 }
 
 #[derive(Serialize, Deserialize, Clone, Debug, PartialEq, JsonSchema)]
 #[serde(rename_all = "snake_case")]
 pub enum ExecuteMsg {
-    Deposit {},
-    ConfigUpdate { config: Config },
+    Deposit {
+        memo: Option<String>,
+    },
+    UpdateConfig {
+        config: ConfigUpdate,
+    },
+    ProposeNewAdmin {
+        new_admin: String,
+    },
+    AcceptAdmin {},
+    RenounceAdmin {},
+    GrantRole {
+        role: Role,
+        address: String,
+    },
+    RevokeRole {
+        role: Role,
+        address: String,
+    },
     Claim {},
-    AdminClaimUpdate { wallet: String, amount: Uint128 },
+    AdjustClaim {
+        wallet: String,
+        kind: AdjustmentKind,
+        amount: Uint128,
+        reason: String,
+    },
+    /// Stakes the sent nft, called by the nft contract
+    ReceiveNft(Cw721ReceiveMsg),
+    UnstakeNft {
+        token_id: String,
+    },
+    ClaimStakingRewards {},
+}
+
+#[derive(Serialize, Deserialize, Clone, Debug, PartialEq, JsonSchema)]
+pub struct Cw721ReceiveMsg {
+    pub sender: String,
+    pub token_id: String,
+    pub msg: Binary,
+}
+
+#[derive(Serialize, Deserialize, Clone, Debug, PartialEq, JsonSchema)]
+#[serde(rename_all = "snake_case")]
+pub enum Cw721ExecuteMsg {
+    TransferNft { recipient: String, token_id: String },
 }
 
 #[derive(Serialize, Deserialize, Clone, Debug, PartialEq, JsonSchema)]
 #[serde(rename_all = "snake_case")]
 pub enum QueryMsg {
-    ClaimStatus { wallet: String },
+    Ownership {},
+    RoleMembers {
+        role: Role,
+    },
+    ClaimStatus {
+        wallet: String,
+    },
+    AllClaimStatus {
+        start_after: Option<String>,
+        limit: Option<u32>,
+    },
+    Totals {},
+    Deposits {
+        sender: Option<String>,
+        start_after: Option<u64>,
+        limit: Option<u32>,
+    },
+    Adjustments {
+        start_after: Option<u64>,
+        limit: Option<u32>,
+    },
+    StakingState {},
+    Staker {
+        address: String,
+    },
     Config {},
+    SimulateClaim {
+        wallet: String,
+    },
+}
+
+/// Only the provided fields are changed
+#[derive(Serialize, Deserialize, Clone, Debug, PartialEq, JsonSchema, Default)]
+pub struct ConfigUpdate {
+    pub shares: Option<Vec<FundShare>>,
+    pub deposit_policy: Option<DepositPolicy>,
+    /// `null` turns staking rewards off
+    #[serde(
+        default,
+        skip_serializing_if = "Option::is_none",
+        deserialize_with = "nullable"
+    )]
+    pub nft_staking: Option<Option<NftStaking>>,
+}
+
+/// Tells a field sent as `null` apart from a missing one
+fn nullable<'de, T, D>(deserializer: D) -> Result<Option<Option<T>>, D::Error>
+where
+    T: Deserialize<'de>,
+    D: Deserializer<'de>,
+{
+    Option::<T>::deserialize(deserializer).map(Some)
 }
 
 #[derive(Serialize, Deserialize, Clone, Debug, PartialEq, JsonSchema)]
@@ -34,10 +135,82 @@ pub struct ConfigResponse {
     pub config: Config,
 }
 
+pub type RoleMembersResponse = delorean_common::ownership::RoleMembersResponse<Role>;
+
 #[derive(Serialize, Deserialize, Clone, Debug, PartialEq, JsonSchema)]
 pub struct ClaimStatusResponse {
     pub claimable_ust: Uint128,
+    /// Vested and not yet claimed, before adjustments
+    pub vested_ust: Uint128,
+    /// Accrued but still locked by the wallet's vesting
+    pub unvested_ust: Uint128,
     pub claimed_ust: Option<Uint128>,
     pub total_ust: Uint128,
     pub share: u32,
 }
+
+#[derive(Serialize, Deserialize, Clone, Debug, PartialEq, JsonSchema)]
+pub struct ShareClaimStatus {
+    pub wallet: String,
+    pub note: String,
+    pub share: u32,
+    pub claimed_ust: Uint128,
+    pub claimable_ust: Uint128,
+    pub last_claim_time: Option<u64>,
+}
+
+#[derive(Serialize, Deserialize, Clone, Debug, PartialEq, JsonSchema)]
+pub struct AllClaimStatusResponse {
+    pub claims: Vec<ShareClaimStatus>,
+}
+
+#[derive(Serialize, Deserialize, Clone, Debug, PartialEq, JsonSchema)]
+pub struct TotalsResponse {
+    pub total_deposited: Uint128,
+    pub total_claimed: Uint128,
+    /// Still claimable by the current shares
+    pub outstanding_liability: Uint128,
+    pub balance: Uint128,
+    /// Outstanding liability is covered by the balance
+    pub solvent: bool,
+}
+
+#[derive(Serialize, Deserialize, Clone, Debug, PartialEq, JsonSchema)]
+pub struct DepositEntry {
+    pub id: u64,
+    pub deposit: DepositRecord,
+}
+
+#[derive(Serialize, Deserialize, Clone, Debug, PartialEq, JsonSchema)]
+pub struct DepositsResponse {
+    pub deposits: Vec<DepositEntry>,
+}
+
+#[derive(Serialize, Deserialize, Clone, Debug, PartialEq, JsonSchema)]
+pub struct AdjustmentEntry {
+    pub id: u64,
+    pub adjustment: AdjustmentRecord,
+}
+
+#[derive(Serialize, Deserialize, Clone, Debug, PartialEq, JsonSchema)]
+pub struct AdjustmentsResponse {
+    pub adjustments: Vec<AdjustmentEntry>,
+}
+
+#[derive(Serialize, Deserialize, Clone, Debug, PartialEq, JsonSchema)]
+pub struct StakingStateResponse {
+    pub nft_staking: Option<NftStaking>,
+    pub state: StakingState,
+}
+
+#[derive(Serialize, Deserialize, Clone, Debug, PartialEq, JsonSchema)]
+pub struct StakerResponse {
+    pub address: String,
+    pub staked: u64,
+    pub pending_rewards: Uint128,
+}
+
+#[derive(Serialize, Deserialize, Clone, Debug, PartialEq, JsonSchema)]
+pub struct SimulatePayoutsResponse {
+    pub payouts: Vec<Payout>,
+}
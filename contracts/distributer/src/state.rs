@@ -1,16 +1,57 @@
 use schemars::JsonSchema;
 use serde::{Deserialize, Serialize};
 
-use cosmwasm_std::Uint128;
-use cw_storage_plus::{Item, Map};
+use cosmwasm_std::{Decimal, Uint128};
+use cw_storage_plus::{Item, Map, U64Key};
+pub use delorean_common::deposit::DepositPolicy;
 pub const CONFIG_KEY: &str = "config";
 pub const FUNDSTATE_KEY: &str = "fund_state";
 pub const CLAIMED_STATE_KEY: &str = "claim_state";
+pub const TOTAL_CLAIMED_KEY: &str = "total_claimed";
+pub const LAST_CLAIM_KEY: &str = "last_claim";
+pub const DEPOSIT_COUNT_KEY: &str = "deposit_count";
+pub const DEPOSITS_KEY: &str = "deposits";
+pub const DEPOSITS_BY_SENDER_KEY: &str = "deposits_by_sender";
+pub const PENDING_ADJUSTMENTS_KEY: &str = "pending_adjustments";
+pub const ADJUSTMENT_COUNT_KEY: &str = "adjustment_count";
+pub const ADJUSTMENTS_KEY: &str = "adjustments";
+pub const VESTED_CLAIMED_KEY: &str = "vested_claimed";
+pub const STAKING_STATE_KEY: &str = "staking_state";
+pub const STAKERS_KEY: &str = "stakers";
+pub const STAKED_TOKENS_KEY: &str = "staked_tokens";
+
+/// Shortest unstake_cooldown nft_staking may set, so a token staked just
+/// before a deposit cannot leave right after collecting its share
+pub const MIN_UNSTAKE_COOLDOWN: u64 = 86400;
 
 #[derive(Serialize, Deserialize, Clone, Debug, PartialEq, JsonSchema)]
 pub struct Config {
-    pub admin: String,
     pub shares: Vec<FundShare>,
+    #[serde(default)]
+    pub deposit_policy: DepositPolicy,
+    #[serde(default)]
+    pub nft_staking: Option<NftStaking>,
+}
+
+/// Part of every deposit split between the wallets staking nfts of
+/// nft_contract, one weight per staked token
+#[derive(Serialize, Deserialize, Clone, Debug, PartialEq, JsonSchema)]
+pub struct NftStaking {
+    pub nft_contract: String,
+    pub share: u32, // in decimal of 100, next to the fixed shares
+    /// Seconds a staked token stays locked before it can be unstaked
+    #[serde(default = "default_unstake_cooldown")]
+    pub unstake_cooldown: u64,
+}
+
+fn default_unstake_cooldown() -> u64 {
+    MIN_UNSTAKE_COOLDOWN
+}
+
+impl NftStaking {
+    pub fn get_share(&self, fund: Uint128) -> Uint128 {
+        fund.multiply_ratio(self.share, 10000u32)
+    }
 }
 
 #[derive(Serialize, Deserialize, Clone, Debug, PartialEq, JsonSchema)]
@@ -18,6 +59,8 @@ pub struct FundShare {
     pub address: String,
     pub note: String,
     pub share: u32, // in decimal of 100
+    #[serde(default)]
+    pub vesting: Option<Vesting>,
 }
 
 impl FundShare {
@@ -27,6 +70,142 @@ impl FundShare {
     }
 }
 
+/// Nothing vests before the cliff, then it vests linearly until
+/// start_time + duration. Times are in seconds.
+#[derive(Serialize, Deserialize, Clone, Debug, PartialEq, JsonSchema)]
+pub struct Vesting {
+    pub start_time: u64,
+    pub cliff: u64,
+    pub duration: u64,
+}
+
+impl Vesting {
+    pub fn vested(&self, amount: Uint128, time: u64) -> Uint128 {
+        if time < self.start_time + self.cliff {
+            return Uint128::zero();
+        }
+        let elapsed = time - self.start_time;
+        if elapsed >= self.duration {
+            return amount;
+        }
+        amount.multiply_ratio(elapsed, self.duration)
+    }
+}
+
+#[derive(Serialize, Deserialize, Clone, Debug, PartialEq, JsonSchema)]
+pub struct DepositRecord {
+    pub sender: String,
+    pub amount: Uint128,
+    pub denom: String,
+    pub height: u64,
+    pub time: u64,
+    pub memo: Option<String>,
+}
+
+#[derive(Serialize, Deserialize, Clone, Debug, PartialEq, JsonSchema)]
+#[serde(rename_all = "snake_case")]
+pub enum AdjustmentKind {
+    Credit,
+    Debit,
+}
+
+impl AdjustmentKind {
+    pub fn as_str(&self) -> &str {
+        match self {
+            AdjustmentKind::Credit => "credit",
+            AdjustmentKind::Debit => "debit",
+        }
+    }
+}
+
+#[derive(Serialize, Deserialize, Clone, Debug, PartialEq, JsonSchema)]
+pub struct AdjustmentRecord {
+    pub wallet: String,
+    pub kind: AdjustmentKind,
+    pub amount: Uint128,
+    pub reason: String,
+    pub admin: String,
+    pub height: u64,
+    pub time: u64,
+}
+
+/// Adjustments the wallet's next claim settles, at most one side is non zero
+#[derive(Serialize, Deserialize, Clone, Debug, PartialEq, JsonSchema, Default)]
+pub struct PendingAdjustment {
+    pub credit: Uint128,
+    pub debit: Uint128,
+}
+
+impl PendingAdjustment {
+    pub fn apply(&mut self, kind: &AdjustmentKind, amount: Uint128) {
+        let (credit, debit) = match kind {
+            AdjustmentKind::Credit => (self.credit + amount, self.debit),
+            AdjustmentKind::Debit => (self.credit, self.debit + amount),
+        };
+        self.credit = credit.saturating_sub(debit);
+        self.debit = debit.saturating_sub(credit);
+    }
+}
+
+#[derive(Serialize, Deserialize, Clone, Debug, PartialEq, JsonSchema, Default)]
+pub struct StakingState {
+    pub total_staked: u64,
+    /// Rewards per staked token since the first deposit
+    pub reward_index: Decimal,
+    /// Staking part of deposits made while nothing was staked, goes out
+    /// with the next deposit
+    pub undistributed: Uint128,
+    pub funded: Uint128,
+    pub paid: Uint128,
+}
+
+#[derive(Serialize, Deserialize, Clone, Debug, PartialEq, JsonSchema, Default)]
+pub struct StakerInfo {
+    pub staked: u64,
+    /// reward_index when the rewards were last settled
+    pub reward_index: Decimal,
+    pub pending_rewards: Uint128,
+}
+
+#[derive(Serialize, Deserialize, Clone, Debug, PartialEq, JsonSchema)]
+pub struct StakedToken {
+    pub owner: String,
+    pub staked_at: u64,
+}
+
+impl StakedToken {
+    pub fn unlock_time(&self, cooldown: u64) -> u64 {
+        self.staked_at + cooldown
+    }
+}
+
+impl StakerInfo {
+    pub fn settle(&mut self, reward_index: Decimal) {
+        let accrued = Uint128::from(self.staked) * (reward_index - self.reward_index);
+        self.pending_rewards += accrued;
+        self.reward_index = reward_index;
+    }
+}
+
 pub const CLAIM_STATE: Map<&str, Uint128> = Map::new(CLAIMED_STATE_KEY);
 pub const CONFIG: Item<Config> = Item::new(CONFIG_KEY);
 pub const FUND_STATE: Item<Uint128> = Item::new(FUNDSTATE_KEY);
+// gross amount paid out by claims
+pub const TOTAL_CLAIMED: Item<Uint128> = Item::new(TOTAL_CLAIMED_KEY);
+// wallet -> block time of its last claim
+pub const LAST_CLAIM: Map<&str, u64> = Map::new(LAST_CLAIM_KEY);
+// ids handed out so far, the first deposit gets 1
+pub const DEPOSIT_COUNT: Item<u64> = Item::new(DEPOSIT_COUNT_KEY);
+pub const DEPOSITS: Map<U64Key, DepositRecord> = Map::new(DEPOSITS_KEY);
+// (sender, deposit id) -> recorded, to page one sender's deposits
+pub const DEPOSITS_BY_SENDER: Map<(&str, U64Key), bool> = Map::new(DEPOSITS_BY_SENDER_KEY);
+pub const PENDING_ADJUSTMENTS: Map<&str, PendingAdjustment> = Map::new(PENDING_ADJUSTMENTS_KEY);
+// ids handed out so far, the first adjustment gets 1
+pub const ADJUSTMENT_COUNT: Item<u64> = Item::new(ADJUSTMENT_COUNT_KEY);
+pub const ADJUSTMENTS: Map<U64Key, AdjustmentRecord> = Map::new(ADJUSTMENTS_KEY);
+// wallet -> paid out of its vested accrual since its claimed-up-to mark,
+// vesting shares keep their mark when claiming
+pub const VESTED_CLAIMED: Map<&str, Uint128> = Map::new(VESTED_CLAIMED_KEY);
+pub const STAKING_STATE: Item<StakingState> = Item::new(STAKING_STATE_KEY);
+pub const STAKERS: Map<&str, StakerInfo> = Map::new(STAKERS_KEY);
+pub const STAKED_TOKENS: Map<&str, StakedToken> = Map::new(STAKED_TOKENS_KEY);
@@ -1,17 +1,39 @@
-use cosmwasm_std::{coin, from_binary, Attribute, Binary, ContractResult, Response, Uint128};
+use cosmwasm_std::{
+    coin, from_binary, Attribute, BankMsg, Binary, ContractResult, CosmosMsg, Response, Uint128,
+};
 use cosmwasm_vm::testing::{
     execute, instantiate, migrate, mock_env, mock_info, mock_instance_options, query,
 };
 use cosmwasm_vm::Instance;
 use delorean_distributer::msg::{
-    ClaimStatusResponse, ConfigResponse, ExecuteMsg, InstantiateMsg, MigrateMsg, QueryMsg,
+    AdjustmentsResponse, AllClaimStatusResponse, ClaimStatusResponse, ConfigResponse, ConfigUpdate,
+    Cw721ReceiveMsg, DepositEntry, DepositsResponse, ExecuteMsg, InstantiateMsg, MigrateMsg,
+    QueryMsg, ShareClaimStatus, SimulatePayoutsResponse, StakerResponse, TotalsResponse,
+};
+use delorean_distributer::state::{
+    AdjustmentKind, Config, DepositPolicy, DepositRecord, FundShare, NftStaking, Vesting,
+    MIN_UNSTAKE_COOLDOWN,
 };
-use delorean_distributer::state::{Config, FundShare};
 use mock_tax::mock_dependencies::mock_dependencies_with_custom_querier;
 
 static WASM: &[u8] =
     include_bytes!("../../../target/wasm32-unknown-unknown/release/delorean_distributer.wasm");
 
+/// Attributes of each event of type `ty`, in emission order
+fn events<'a>(rsp: &'a Response, ty: &str) -> Vec<Vec<(&'a str, &'a str)>> {
+    rsp.events
+        .iter()
+        .filter(|event| event.ty == ty)
+        .map(|event| {
+            event
+                .attributes
+                .iter()
+                .map(|attr| (attr.key.as_str(), attr.value.as_str()))
+                .collect()
+        })
+        .collect()
+}
+
 #[test]
 fn delorean_distributer_test() {
     let backend = mock_dependencies_with_custom_querier(&[]);
@@ -27,22 +49,27 @@ fn delorean_distributer_test() {
             address: user1.clone(),
             note: "".to_string(),
             share: 2000u32, //20.00
+            vesting: None,
         },
         FundShare {
             address: user2.clone(),
             note: "".to_string(),
             share: 4000u32, //40.00
+            vesting: None,
         },
     ]
     .to_vec();
 
     let config = Config {
-        admin: admin.clone(),
         shares: shares.clone(),
+        deposit_policy: DepositPolicy::default(),
+        nft_staking: None,
     };
 
     let instatiate_msg = InstantiateMsg {
         config: config.clone(),
+        admin: None,
+        roles: vec![],
     };
 
     let (instance_options, memory_limit) = mock_instance_options();
@@ -55,20 +82,94 @@ fn delorean_distributer_test() {
 
     assert_eq!(config_rsp.config, config);
 
-    let update_config_msg = ExecuteMsg::ConfigUpdate {
-        config: config.clone(),
+    let update_config_msg = ExecuteMsg::UpdateConfig {
+        config: ConfigUpdate {
+            shares: Some(config.shares.clone()),
+            ..ConfigUpdate::default()
+        },
     };
     let rsp: ContractResult<Response> =
         execute(&mut deps, mock_env(), user1_info.clone(), update_config_msg);
     assert_eq!(rsp.is_err(), true);
 
+    let mut oversized_shares = config.shares.clone();
+    oversized_shares[0].share = 9000u32;
+    let rsp: ContractResult<Response> = execute(
+        &mut deps,
+        mock_env(),
+        admin_info.clone(),
+        ExecuteMsg::UpdateConfig {
+            config: ConfigUpdate {
+                shares: Some(oversized_shares),
+                ..ConfigUpdate::default()
+            },
+        },
+    );
+    assert_eq!(
+        rsp.unwrap_err(),
+        "InvalidConfig: shares",
+        "Shares above 100% should be rejected"
+    );
+
+    let rsp: ContractResult<Response> = execute(
+        &mut deps,
+        mock_env(),
+        admin_info.clone(),
+        ExecuteMsg::UpdateConfig {
+            config: ConfigUpdate {
+                deposit_policy: Some(DepositPolicy {
+                    allowlist: vec![admin.clone(), user1.clone()],
+                    min_deposit: Uint128::from(1_000_000u128),
+                    closed: false,
+                }),
+                ..ConfigUpdate::default()
+            },
+        },
+    );
+    assert_eq!(
+        events(&rsp.unwrap(), "config-updated"),
+        vec![vec![(
+            "deposit_policy",
+            r#"{"allowlist":["admin","user1"],"min_deposit":"1000000","closed":false}"#
+        )]]
+    );
+
+    let rsp: ContractResult<Response> = execute(
+        &mut deps,
+        mock_env(),
+        mock_info(&user2, &[coin(100_000_000u128, "uusd")].to_vec()),
+        ExecuteMsg::Deposit { memo: None },
+    );
+    assert_eq!(rsp.unwrap_err(), "DepositorNotAllowed: user2");
+
+    let rsp: ContractResult<Response> = execute(
+        &mut deps,
+        mock_env(),
+        mock_info(&user1, &[coin(10u128, "uusd")].to_vec()),
+        ExecuteMsg::Deposit { memo: None },
+    );
+    assert_eq!(
+        rsp.unwrap_err(),
+        "DepositTooSmall: user1 sent 10, minimum 1000000"
+    );
+
     let rsp: ContractResult<Response> = execute(
         &mut deps,
         mock_env(),
         mock_info(&user1, &[coin(100_000_000u128, "uusd")].to_vec()),
-        ExecuteMsg::Deposit {},
+        ExecuteMsg::Deposit { memo: None },
+    );
+    assert_eq!(
+        events(&rsp.unwrap(), "fund-deposited"),
+        vec![vec![
+            ("deposit_id", "1"),
+            ("sender", "user1"),
+            ("amount", "100000000"),
+            ("denom", "uusd"),
+            ("memo", ""),
+            ("total_funds", "100000000"),
+        ]]
     );
-    assert_eq!(rsp.is_err(), false);
 
     let rsp: ContractResult<Response> = migrate(&mut deps, mock_env(), MigrateMsg {});
     assert_eq!(rsp.is_err(), false);
@@ -84,6 +185,8 @@ fn delorean_distributer_test() {
     let rsp: ClaimStatusResponse = from_binary(&rsp).unwrap();
     let expected = ClaimStatusResponse {
         claimable_ust: Uint128::from(20_000_000u128),
+        vested_ust: Uint128::from(20_000_000u128),
+        unvested_ust: Uint128::zero(),
         claimed_ust: Some(Uint128::zero()),
         total_ust: Uint128::from(100_000_000u128),
         share: 2000u32,
@@ -105,6 +208,8 @@ fn delorean_distributer_test() {
     let rsp: ClaimStatusResponse = from_binary(&rsp).unwrap();
     let expected = ClaimStatusResponse {
         claimable_ust: Uint128::from(40_000_000u128),
+        vested_ust: Uint128::from(40_000_000u128),
+        unvested_ust: Uint128::zero(),
         claimed_ust: Some(Uint128::zero()),
         total_ust: Uint128::from(100_000_000u128),
         share: 4000u32,
@@ -115,6 +220,21 @@ fn delorean_distributer_test() {
         expected
     );
 
+    let rsp: Binary = query(
+        &mut deps,
+        mock_env(),
+        QueryMsg::SimulateClaim {
+            wallet: user1.clone(),
+        },
+    )
+    .unwrap();
+    let rsp: SimulatePayoutsResponse = from_binary(&rsp).unwrap();
+    assert_eq!(rsp.payouts.len(), 1);
+    let payout = rsp.payouts[0].clone();
+    assert_eq!(payout.recipient, user1);
+    assert_eq!(payout.gross.amount, Uint128::from(20_000_000u128));
+    assert_eq!(payout.gross.amount, payout.tax.amount + payout.net.amount);
+
     let rsp: ContractResult<Response> = execute(
         &mut deps,
         mock_env(),
@@ -122,9 +242,28 @@ fn delorean_distributer_test() {
         ExecuteMsg::Claim {},
     );
     assert_eq!(rsp.is_err(), false, "User 1 should be able to claim");
+    let rsp = rsp.unwrap();
+    assert_eq!(
+        rsp.messages[0].msg,
+        CosmosMsg::Bank(BankMsg::Send {
+            to_address: payout.recipient,
+            amount: vec![payout.net],
+        }),
+        "Claim should send what SimulateClaim showed"
+    );
+    assert_eq!(
+        events(&rsp, "share-claimed"),
+        vec![vec![
+            ("wallet", "user1"),
+            ("share", "2000"),
+            ("amount", "20000000"),
+            ("denom", "uusd"),
+            ("claimed_up_to", "100000000"),
+        ]]
+    );
 
     assert_eq!(
-        rsp.unwrap().attributes.clone(),
+        rsp.attributes.clone(),
         [
             Attribute {
                 key: "action".to_string(),
@@ -154,6 +293,8 @@ fn delorean_distributer_test() {
     let rsp: ClaimStatusResponse = from_binary(&rsp).unwrap();
     let expected = ClaimStatusResponse {
         claimable_ust: Uint128::from(0u32),
+        vested_ust: Uint128::from(0u32),
+        unvested_ust: Uint128::zero(),
         claimed_ust: Some(Uint128::from(20_000_000u128)),
         total_ust: Uint128::from(100_000_000u128),
         share: 2000u32,
@@ -168,10 +309,39 @@ fn delorean_distributer_test() {
         &mut deps,
         mock_env(),
         mock_info(&admin, &[coin(100_000_000u128, "uusd")].to_vec()),
-        ExecuteMsg::Deposit {},
+        ExecuteMsg::Deposit {
+            memo: Some("round 12".to_string()),
+        },
     );
     assert_eq!(rsp.is_err(), false);
 
+    let rsp: Binary = query(
+        &mut deps,
+        mock_env(),
+        QueryMsg::Deposits {
+            sender: Some(admin.clone()),
+            start_after: None,
+            limit: None,
+        },
+    )
+    .unwrap();
+    let rsp: DepositsResponse = from_binary(&rsp).unwrap();
+    assert_eq!(
+        rsp.deposits,
+        vec![DepositEntry {
+            id: 2,
+            deposit: DepositRecord {
+                sender: admin.clone(),
+                amount: Uint128::from(100_000_000u128),
+                denom: "uusd".to_string(),
+                height: mock_env().block.height,
+                time: mock_env().block.time.nanos() / 1_000_000_000,
+                memo: Some("round 12".to_string()),
+            },
+        }],
+        "Deposits should be filtered by sender"
+    );
+
     let rsp: Binary = query(
         &mut deps,
         mock_env(),
@@ -183,6 +353,8 @@ fn delorean_distributer_test() {
     let rsp: ClaimStatusResponse = from_binary(&rsp).unwrap();
     let expected = ClaimStatusResponse {
         claimable_ust: Uint128::from(20_000_000u128),
+        vested_ust: Uint128::from(20_000_000u128),
+        unvested_ust: Uint128::zero(),
         claimed_ust: Some(Uint128::from(20_000_000u128)),
         total_ust: Uint128::from(200_000_000u128),
         share: 2000u32,
@@ -204,6 +376,8 @@ fn delorean_distributer_test() {
     let rsp: ClaimStatusResponse = from_binary(&rsp).unwrap();
     let expected = ClaimStatusResponse {
         claimable_ust: Uint128::from(80_000_000u128),
+        vested_ust: Uint128::from(80_000_000u128),
+        unvested_ust: Uint128::zero(),
         claimed_ust: Some(Uint128::from(0u128)),
         total_ust: Uint128::from(200_000_000u128),
         share: 4000u32,
@@ -257,6 +431,8 @@ fn delorean_distributer_test() {
     let rsp: ClaimStatusResponse = from_binary(&rsp).unwrap();
     let expected = ClaimStatusResponse {
         claimable_ust: Uint128::from(0u128),
+        vested_ust: Uint128::from(0u128),
+        unvested_ust: Uint128::zero(),
         claimed_ust: Some(Uint128::from(40_000_000u128)),
         total_ust: Uint128::from(200_000_000u128),
         share: 2000u32,
@@ -266,4 +442,412 @@ fn delorean_distributer_test() {
         "Claimable UST of user 2 after 2nd deposit should match rsp={:?}",
         expected
     );
+
+    let rsp: Binary = query(
+        &mut deps,
+        mock_env(),
+        QueryMsg::AllClaimStatus {
+            start_after: Some(user1.clone()),
+            limit: None,
+        },
+    )
+    .unwrap();
+    let rsp: AllClaimStatusResponse = from_binary(&rsp).unwrap();
+    assert_eq!(
+        rsp.claims,
+        vec![ShareClaimStatus {
+            wallet: user2.clone(),
+            note: "".to_string(),
+            share: 4000u32,
+            claimed_ust: Uint128::zero(),
+            claimable_ust: Uint128::from(80_000_000u128),
+            last_claim_time: None,
+        }]
+    );
+
+    // the mock bank holds nothing, so the liability is not covered
+    let rsp: Binary = query(&mut deps, mock_env(), QueryMsg::Totals {}).unwrap();
+    let rsp: TotalsResponse = from_binary(&rsp).unwrap();
+    assert_eq!(
+        rsp,
+        TotalsResponse {
+            total_deposited: Uint128::from(200_000_000u128),
+            total_claimed: Uint128::from(40_000_000u128),
+            outstanding_liability: Uint128::from(80_000_000u128),
+            balance: Uint128::zero(),
+            solvent: false,
+        }
+    );
+
+    let rsp: ContractResult<Response> = execute(
+        &mut deps,
+        mock_env(),
+        admin_info.clone(),
+        ExecuteMsg::AdjustClaim {
+            wallet: user2.clone(),
+            kind: AdjustmentKind::Debit,
+            amount: Uint128::from(90_000_000u128),
+            reason: "double counted deposit".to_string(),
+        },
+    );
+    assert_eq!(
+        rsp.unwrap_err(),
+        "DebitExceedsClaimable: user2 can claim 80000000, debit 90000000"
+    );
+
+    let rsp: ContractResult<Response> = execute(
+        &mut deps,
+        mock_env(),
+        admin_info.clone(),
+        ExecuteMsg::AdjustClaim {
+            wallet: user2.clone(),
+            kind: AdjustmentKind::Debit,
+            amount: Uint128::from(20_000_000u128),
+            reason: "double counted deposit".to_string(),
+        },
+    );
+    assert_eq!(rsp.is_err(), false, "Admin should be able to debit");
+    assert_eq!(
+        events(&rsp.unwrap(), "claim-adjusted"),
+        vec![vec![
+            ("adjustment_id", "1"),
+            ("wallet", "user2"),
+            ("kind", "debit"),
+            ("amount", "20000000"),
+            ("reason", "double counted deposit"),
+            ("admin", "admin"),
+        ]]
+    );
+
+    let rsp: Binary = query(
+        &mut deps,
+        mock_env(),
+        QueryMsg::ClaimStatus {
+            wallet: user2.clone(),
+        },
+    )
+    .unwrap();
+    let rsp: ClaimStatusResponse = from_binary(&rsp).unwrap();
+    assert_eq!(rsp.claimable_ust, Uint128::from(60_000_000u128));
+
+    // the mock bank holds nothing, so no credit is covered
+    let rsp: ContractResult<Response> = execute(
+        &mut deps,
+        mock_env(),
+        admin_info.clone(),
+        ExecuteMsg::AdjustClaim {
+            wallet: user1.clone(),
+            kind: AdjustmentKind::Credit,
+            amount: Uint128::from(5_000_000u128),
+            reason: "missed payout".to_string(),
+        },
+    );
+    assert_eq!(
+        rsp.unwrap_err(),
+        "LiabilityExceedsBalance: liability 65000000, balance 0"
+    );
+
+    let rsp: Binary = query(
+        &mut deps,
+        mock_env(),
+        QueryMsg::Adjustments {
+            start_after: None,
+            limit: None,
+        },
+    )
+    .unwrap();
+    let rsp: AdjustmentsResponse = from_binary(&rsp).unwrap();
+    assert_eq!(rsp.adjustments.len(), 1, "Only the debit should be logged");
+    assert_eq!(rsp.adjustments[0].adjustment.admin, admin);
+}
+
+#[test]
+fn distributer_vesting_test() {
+    let backend = mock_dependencies_with_custom_querier(&[]);
+    let admin = String::from("admin");
+    let user1 = String::from("user1");
+    let start_time = mock_env().block.time.nanos() / 1_000_000_000;
+
+    let config = Config {
+        shares: vec![FundShare {
+            address: user1.clone(),
+            note: "team".to_string(),
+            share: 5000u32,
+            vesting: Some(Vesting {
+                start_time,
+                cliff: 100,
+                duration: 1000,
+            }),
+        }],
+        deposit_policy: DepositPolicy::default(),
+        nft_staking: None,
+    };
+    let (instance_options, memory_limit) = mock_instance_options();
+    let mut deps = Instance::from_code(WASM, backend, instance_options, memory_limit).unwrap();
+    let rsp: ContractResult<Response> = instantiate(
+        &mut deps,
+        mock_env(),
+        mock_info(&admin, &[]),
+        InstantiateMsg {
+            config,
+            admin: None,
+            roles: vec![],
+        },
+    );
+    assert_eq!(rsp.is_err(), false);
+
+    let rsp: ContractResult<Response> = execute(
+        &mut deps,
+        mock_env(),
+        mock_info(&admin, &[coin(100_000_000u128, "uusd")]),
+        ExecuteMsg::Deposit { memo: None },
+    );
+    assert_eq!(rsp.is_err(), false);
+
+    let mut env = mock_env();
+    env.block.time = env.block.time.plus_seconds(50);
+    let rsp = query(
+        &mut deps,
+        env,
+        QueryMsg::ClaimStatus {
+            wallet: user1.clone(),
+        },
+    )
+    .unwrap();
+    let rsp: ClaimStatusResponse = from_binary(&rsp).unwrap();
+    assert_eq!(
+        rsp.vested_ust,
+        Uint128::zero(),
+        "Nothing vests before the cliff"
+    );
+    assert_eq!(rsp.unvested_ust, Uint128::from(50_000_000u128));
+
+    let mut env = mock_env();
+    env.block.time = env.block.time.plus_seconds(500);
+    let rsp: ContractResult<Response> = execute(
+        &mut deps,
+        env.clone(),
+        mock_info(&user1, &[]),
+        ExecuteMsg::Claim {},
+    );
+    assert_eq!(rsp.unwrap().attributes[1].value, "25000000");
+
+    let rsp = query(
+        &mut deps,
+        env,
+        QueryMsg::ClaimStatus {
+            wallet: user1.clone(),
+        },
+    )
+    .unwrap();
+    let rsp: ClaimStatusResponse = from_binary(&rsp).unwrap();
+    assert_eq!(
+        rsp,
+        ClaimStatusResponse {
+            claimable_ust: Uint128::zero(),
+            vested_ust: Uint128::zero(),
+            unvested_ust: Uint128::from(25_000_000u128),
+            claimed_ust: Some(Uint128::from(25_000_000u128)),
+            total_ust: Uint128::from(100_000_000u128),
+            share: 5000u32,
+        }
+    );
+
+    let mut env = mock_env();
+    env.block.time = env.block.time.plus_seconds(1000);
+    let rsp: ContractResult<Response> =
+        execute(&mut deps, env, mock_info(&user1, &[]), ExecuteMsg::Claim {});
+    assert_eq!(
+        rsp.unwrap().attributes[1].value,
+        "25000000",
+        "The rest is claimable once fully vested"
+    );
+}
+
+#[test]
+fn distributer_nft_staking_test() {
+    let backend = mock_dependencies_with_custom_querier(&[]);
+    let admin = String::from("admin");
+    let nft = String::from("nft");
+    let user1 = String::from("user1");
+    let user2 = String::from("user2");
+
+    let config = Config {
+        shares: vec![],
+        deposit_policy: DepositPolicy::default(),
+        nft_staking: Some(NftStaking {
+            nft_contract: nft.clone(),
+            share: 1000u32, //10.00
+            unstake_cooldown: MIN_UNSTAKE_COOLDOWN,
+        }),
+    };
+    let (instance_options, memory_limit) = mock_instance_options();
+    let mut deps = Instance::from_code(WASM, backend, instance_options, memory_limit).unwrap();
+    let mut unlocked = config.clone();
+    unlocked.nft_staking.as_mut().unwrap().unstake_cooldown = 0;
+    let rsp: ContractResult<Response> = instantiate(
+        &mut deps,
+        mock_env(),
+        mock_info(&admin, &[]),
+        InstantiateMsg {
+            config: unlocked,
+            admin: None,
+            roles: vec![],
+        },
+    );
+    assert_eq!(rsp.unwrap_err(), "InvalidConfig: nft_staking");
+
+    let rsp: ContractResult<Response> = instantiate(
+        &mut deps,
+        mock_env(),
+        mock_info(&admin, &[]),
+        InstantiateMsg {
+            config,
+            admin: None,
+            roles: vec![],
+        },
+    );
+    assert_eq!(rsp.is_err(), false);
+
+    let stake = |sender: &String, token_id: &str| {
+        ExecuteMsg::ReceiveNft(Cw721ReceiveMsg {
+            sender: sender.clone(),
+            token_id: token_id.to_string(),
+            msg: Binary::default(),
+        })
+    };
+    let rsp: ContractResult<Response> = execute(
+        &mut deps,
+        mock_env(),
+        mock_info(&user1, &[]),
+        stake(&user1, "1"),
+    );
+    assert_eq!(rsp.unwrap_err(), "WrongNftContract: user1");
+    let rsp: ContractResult<Response> = execute(
+        &mut deps,
+        mock_env(),
+        mock_info(&nft, &[]),
+        stake(&user1, "1"),
+    );
+    assert_eq!(
+        events(&rsp.unwrap(), "nft-staked"),
+        vec![vec![
+            ("wallet", "user1"),
+            ("token_id", "1"),
+            ("staked", "1"),
+            ("total_staked", "1"),
+        ]]
+    );
+
+    let deposit_info = mock_info(&admin, &[coin(100_000_000u128, "uusd")]);
+    let rsp: ContractResult<Response> = execute(
+        &mut deps,
+        mock_env(),
+        deposit_info.clone(),
+        ExecuteMsg::Deposit { memo: None },
+    );
+    assert_eq!(rsp.is_err(), false);
+
+    let rsp: ContractResult<Response> = execute(
+        &mut deps,
+        mock_env(),
+        mock_info(&nft, &[]),
+        stake(&user2, "2"),
+    );
+    assert_eq!(rsp.is_err(), false);
+    let rsp: ContractResult<Response> = execute(
+        &mut deps,
+        mock_env(),
+        deposit_info,
+        ExecuteMsg::Deposit { memo: None },
+    );
+    assert_eq!(rsp.is_err(), false);
+
+    let rsp = query(
+        &mut deps,
+        mock_env(),
+        QueryMsg::Staker {
+            address: user1.clone(),
+        },
+    )
+    .unwrap();
+    let rsp: StakerResponse = from_binary(&rsp).unwrap();
+    assert_eq!(
+        rsp.pending_rewards,
+        Uint128::from(15_000_000u128),
+        "Rewards are split by staked tokens at deposit time"
+    );
+
+    let rsp: ContractResult<Response> = execute(
+        &mut deps,
+        mock_env(),
+        mock_info(&user2, &[]),
+        ExecuteMsg::UnstakeNft {
+            token_id: "1".to_string(),
+        },
+    );
+    assert_eq!(rsp.unwrap_err(), "NotTokenOwner: user2 did not stake 1");
+
+    let rsp: ContractResult<Response> = execute(
+        &mut deps,
+        mock_env(),
+        mock_info(&user1, &[]),
+        ExecuteMsg::UnstakeNft {
+            token_id: "1".to_string(),
+        },
+    );
+    let unlock_time = mock_env().block.time.nanos() / 1_000_000_000 + MIN_UNSTAKE_COOLDOWN;
+    assert_eq!(
+        rsp.unwrap_err(),
+        format!("StakeLocked: 1 unlocks at {}", unlock_time)
+    );
+
+    let mut env = mock_env();
+    env.block.time = env.block.time.plus_seconds(MIN_UNSTAKE_COOLDOWN);
+    let rsp: ContractResult<Response> = execute(
+        &mut deps,
+        env,
+        mock_info(&user1, &[]),
+        ExecuteMsg::UnstakeNft {
+            token_id: "1".to_string(),
+        },
+    );
+    let rsp = rsp.unwrap();
+    assert_eq!(rsp.messages.len(), 1, "Token is sent back");
+    assert_eq!(
+        events(&rsp, "nft-unstaked"),
+        vec![vec![
+            ("wallet", "user1"),
+            ("token_id", "1"),
+            ("staked", "0"),
+            ("total_staked", "1"),
+        ]]
+    );
+
+    let rsp: ContractResult<Response> = execute(
+        &mut deps,
+        mock_env(),
+        mock_info(&user1, &[]),
+        ExecuteMsg::ClaimStakingRewards {},
+    );
+    assert_eq!(
+        rsp.is_err(),
+        false,
+        "Rewards stay claimable after unstaking"
+    );
+    assert_eq!(
+        events(&rsp.unwrap(), "staking-rewards-claimed"),
+        vec![vec![
+            ("wallet", "user1"),
+            ("amount", "15000000"),
+            ("denom", "uusd"),
+        ]]
+    );
+    let rsp: ContractResult<Response> = execute(
+        &mut deps,
+        mock_env(),
+        mock_info(&user1, &[]),
+        ExecuteMsg::ClaimStakingRewards {},
+    );
+    assert_eq!(rsp.unwrap_err(), "NothingToClaim");
 }
@@ -1,28 +1,57 @@
-use std::ops::Div;
-
-use crate::errors::{ContractError, Unauthorized};
+use crate::errors::ContractError;
+use crate::migrations::{migrate_store, set_version, CONTRACT_NAME, CONTRACT_VERSION};
 use crate::msg::{
-    ConfigResponse, ExecuteMsg, FundDepositExecuteMsg, InstantiateMsg, MigrateMsg, NftExecuteMsg,
-    QueryMsg, StatusResponse,
+    ConfigResponse, ConfigUpdate, CurrentPriceResponse, ExecuteMsg, FundDepositExecuteMsg,
+    InstantiateMsg, MigrateMsg, NftExecuteMsg, Payout, QueryMsg, RevealStatusResponse,
+    SimulatePayoutsResponse, StatusResponse, TokenTraitsResponse, TraitPoolsResponse, TraitSupply,
+    TraitSupplyResponse,
+};
+use crate::ownership::{
+    assert_role, get_ownership, get_role_members, has_role, init_ownership, is_admin,
+    try_accept_admin, try_grant_role, try_propose_new_admin, try_renounce_admin, try_revoke_role,
+    unauthorized, Role,
+};
+use crate::state::{
+    Config, Metadata, MintStatus, MintedMetadata, RevealState, TokenTraits, Trait, TraitPool,
+    BEACON, CONFIG, MINTED_METADATA, MINTSTATUS, REVEAL, TOKEN_TRAITS, TRAIT_POOLS, TRAIT_SUPPLY,
 };
-use crate::state::{Config, Metadata, MintStatus, CONFIG, MINTSTATUS};
 use cosmwasm_std::{
-    coin, entry_point, to_binary, Coin, Deps, DepsMut, Env, MessageInfo, QueryResponse, Response,
-    StdResult, SubMsg, WasmMsg,
+    attr, coin, entry_point, to_binary, Attribute, Binary, Coin, Deps, DepsMut, Env, Event,
+    MessageInfo, Order, QueryResponse, Response, StdError, StdResult, Storage, SubMsg, Uint128,
+    WasmMsg,
 };
-use moneymarket::querier::deduct_tax;
+use delorean_common::errors::invalid_config;
+use delorean_common::events::{instantiate_event, json_attribute};
+use delorean_common::payout::payout;
+use sha2::{Digest, Sha256};
 
 pub fn instantiate(
-    deps: DepsMut,
+    mut deps: DepsMut,
     _env: Env,
-    _info: MessageInfo,
+    info: MessageInfo,
     msg: InstantiateMsg,
 ) -> Result<Response, ContractError> {
-    let status = MintStatus { mint_count: 0 };
+    let admin = msg.admin.clone().unwrap_or_else(|| info.sender.to_string());
+    let event = instantiate_event(
+        CONTRACT_NAME,
+        CONTRACT_VERSION,
+        &admin,
+        &msg.roles,
+        &msg.config,
+    )?;
+    init_ownership(deps.branch(), admin, msg.roles)?;
+
+    let status = MintStatus {
+        mint_count: 0,
+        reserved_count: 0,
+        paused: false,
+    };
 
+    validate_config(deps.as_ref(), &msg.config, &status)?;
     CONFIG.save(deps.storage, &msg.config)?;
     MINTSTATUS.save(deps.storage, &status)?;
-    Ok(Response::default())
+    set_version(deps.storage)?;
+    Ok(Response::default().add_event(event))
 }
 
 pub fn execute(
@@ -32,7 +61,23 @@ pub fn execute(
     msg: ExecuteMsg,
 ) -> Result<Response, ContractError> {
     match msg {
-        ExecuteMsg::ConfigUpdate { config } => try_config_update(deps, env, info, config),
+        ExecuteMsg::UpdateConfig { config } => try_update_config(deps, env, info, config),
+
+        ExecuteMsg::ProposeNewAdmin { new_admin } => {
+            Ok(try_propose_new_admin(deps, env, info, new_admin)?)
+        }
+
+        ExecuteMsg::AcceptAdmin {} => Ok(try_accept_admin(deps, env, info)?),
+
+        ExecuteMsg::RenounceAdmin {} => Ok(try_renounce_admin(deps, env, info)?),
+
+        ExecuteMsg::GrantRole { role, address } => {
+            Ok(try_grant_role(deps, env, info, role, address)?)
+        }
+
+        ExecuteMsg::RevokeRole { role, address } => {
+            Ok(try_revoke_role(deps, env, info, role, address)?)
+        }
 
         ExecuteMsg::Mint {} => try_mint(deps, env, info),
 
@@ -43,6 +88,26 @@ pub fn execute(
         } => try_secure_mint(deps, env, info, owner, token_uri, extension),
 
         ExecuteMsg::MoveFunds {} => try_move_funds(deps, env, info),
+
+        ExecuteMsg::Pause {} => try_set_paused(deps, env, info, true),
+
+        ExecuteMsg::Unpause {} => try_set_paused(deps, env, info, false),
+
+        ExecuteMsg::SetTraitPool { pool } => try_set_trait_pool(deps, env, info, pool),
+
+        ExecuteMsg::RemoveTraitPool { trait_type } => {
+            try_remove_trait_pool(deps, env, info, trait_type)
+        }
+
+        ExecuteMsg::UpdateBeacon { beacon } => try_update_beacon(deps, env, info, beacon),
+
+        ExecuteMsg::CommitProvenance { provenance_hash } => {
+            try_commit_provenance(deps, env, info, provenance_hash)
+        }
+
+        ExecuteMsg::Reveal {} => try_reveal(deps, env, info),
+
+        ExecuteMsg::SyncRevealed { limit } => try_sync_revealed(deps, env, info, limit),
     }
 }
 
@@ -56,6 +121,28 @@ fn deposit_funds(contract: String, cns: Vec<Coin>) -> Result<SubMsg, ContractErr
     Ok(exec)
 }
 
+const DEFAULT_SYNC_LIMIT: u32 = 30;
+const MAX_SYNC_LIMIT: u32 = 100;
+
+fn update_nft_metadata(
+    token_id: String,
+    nft_contract: String,
+    extension: Metadata,
+    token_uri: String,
+) -> Result<SubMsg, ContractError> {
+    let msg = NftExecuteMsg::UpdateMetadata {
+        token_id,
+        token_uri,
+        extension,
+    };
+    let exec = SubMsg::new(WasmMsg::Execute {
+        contract_addr: nft_contract,
+        msg: to_binary(&msg)?,
+        funds: vec![],
+    });
+    Ok(exec)
+}
+
 fn mint_nft(
     to: String,
     token_id: String,
@@ -77,82 +164,613 @@ fn mint_nft(
     Ok(exec)
 }
 
-pub fn try_config_update(
+pub fn try_update_config(
     deps: DepsMut,
     _env: Env,
     info: MessageInfo,
-    new_config: Config,
+    update: ConfigUpdate,
 ) -> Result<Response, ContractError> {
-    let mut _config = CONFIG.load(deps.storage)?;
-    if info.sender != _config.admin {
-        return Err(Unauthorized {}.build());
+    assert_role(deps.storage, &info.sender, Role::ConfigManager)?;
+    let stored = CONFIG.load(deps.storage)?;
+    let mut config = stored.clone();
+    let mut changes: Vec<Attribute> = vec![];
+
+    if let Some(nft_contract) = update.nft_contract {
+        changes.push(attr("nft_contract", &nft_contract));
+        config.nft_contract = nft_contract;
+    }
+    if let Some(nft_metadata) = update.nft_metadata {
+        changes.push(attr("nft_metadata", json_attribute(&nft_metadata)?));
+        config.nft_metadata = nft_metadata;
+    }
+    if let Some(shares) = update.shares {
+        changes.push(attr("shares", json_attribute(&shares)?));
+        config.shares = shares;
+    }
+    if let Some(price) = update.price {
+        changes.push(attr("price", price.to_string()));
+        config.price = price;
+    }
+    if let Some(pricing) = update.pricing {
+        changes.push(attr("pricing", json_attribute(&pricing)?));
+        config.pricing = pricing;
+    }
+    if let Some(price_tolerance) = update.price_tolerance {
+        changes.push(attr("price_tolerance", price_tolerance));
+        config.price_tolerance = price_tolerance;
     }
-    CONFIG.save(deps.storage, &new_config)?;
+    if let Some(mint_limit) = update.mint_limit {
+        changes.push(attr("mint_limit", mint_limit.to_string()));
+        config.mint_limit = mint_limit;
+    }
+    if let Some(reserved_supply) = update.reserved_supply {
+        changes.push(attr("reserved_supply", reserved_supply.to_string()));
+        config.reserved_supply = reserved_supply;
+    }
+    if let Some(start_time) = update.start_time {
+        changes.push(attr("start_time", json_attribute(&start_time)?));
+        config.start_time = start_time;
+    }
+    if let Some(end_time) = update.end_time {
+        changes.push(attr("end_time", json_attribute(&end_time)?));
+        config.end_time = end_time;
+    }
+    if let Some(token_id_prefix) = update.token_id_prefix {
+        changes.push(attr("token_id_prefix", json_attribute(&token_id_prefix)?));
+        config.token_id_prefix = token_id_prefix;
+    }
+    if let Some(base_uri) = update.base_uri {
+        changes.push(attr("base_uri", json_attribute(&base_uri)?));
+        config.base_uri = base_uri;
+    }
+    if let Some(placeholder_metadata) = update.placeholder_metadata {
+        changes.push(attr(
+            "placeholder_metadata",
+            json_attribute(&placeholder_metadata)?,
+        ));
+        config.placeholder_metadata = placeholder_metadata;
+    }
+    if let Some(placeholder_uri) = update.placeholder_uri {
+        changes.push(attr("placeholder_uri", json_attribute(&placeholder_uri)?));
+        config.placeholder_uri = placeholder_uri;
+    }
+
+    let status = MINTSTATUS.load(deps.storage)?;
+    validate_config(deps.as_ref(), &config, &status)?;
+    let reveal = REVEAL.may_load(deps.storage)?.unwrap_or_default();
+    if reveal.provenance_hash.is_some() {
+        assert_provenance_fields(&stored, &config)?;
+    }
+    CONFIG.save(deps.storage, &config)?;
 
-    Ok(Response::default())
+    Ok(Response::default()
+        .add_attribute("action", "update_config")
+        .add_attributes(changes.clone())
+        .add_event(Event::new("config-updated").add_attributes(changes)))
 }
 
-pub fn try_move_funds(
+/// The committed hash covers what final_metadata reads, so those fields stay put
+fn assert_provenance_fields(stored: &Config, config: &Config) -> Result<(), ContractError> {
+    let locked = |field: &str| ContractError::ConfigLocked {
+        field: field.to_string(),
+    };
+    if config.mint_limit != stored.mint_limit {
+        return Err(locked("mint_limit"));
+    }
+    if config.token_id_prefix != stored.token_id_prefix {
+        return Err(locked("token_id_prefix"));
+    }
+    if config.base_uri != stored.base_uri {
+        return Err(locked("base_uri"));
+    }
+    if config.nft_metadata != stored.nft_metadata {
+        return Err(locked("nft_metadata"));
+    }
+    Ok(())
+}
+
+fn validate_config(deps: Deps, config: &Config, status: &MintStatus) -> Result<(), ContractError> {
+    deps.api
+        .addr_validate(&config.nft_contract)
+        .map_err(|_| invalid_config("nft_contract"))?;
+
+    let mut total_share: u32 = 0;
+    for fund_share in &config.shares {
+        deps.api
+            .addr_validate(&fund_share.address)
+            .map_err(|_| invalid_config("shares"))?;
+        total_share += fund_share.share;
+    }
+    if total_share > 10000 {
+        return Err(invalid_config("shares").into());
+    }
+
+    if config.price.denom.is_empty() {
+        return Err(invalid_config("price").into());
+    }
+    if !config.pricing.is_valid() {
+        return Err(ContractError::InvalidPricing {});
+    }
+    // can't go below what has already been minted
+    if config.mint_limit < status.mint_count {
+        return Err(invalid_config("mint_limit").into());
+    }
+    if config.reserved_supply > config.mint_limit
+        || config.reserved_supply < status.reserved_count
+        || config.sale_supply() < status.sale_count()
+    {
+        return Err(invalid_config("reserved_supply").into());
+    }
+    if let (Some(start_time), Some(end_time)) = (config.start_time, config.end_time) {
+        if start_time >= end_time {
+            return Err(invalid_config("end_time").into());
+        }
+    }
+    Ok(())
+}
+
+pub fn try_set_paused(
+    deps: DepsMut,
+    _env: Env,
+    info: MessageInfo,
+    paused: bool,
+) -> Result<Response, ContractError> {
+    assert_role(deps.storage, &info.sender, Role::ConfigManager)?;
+    MINTSTATUS.update(deps.storage, |mut status| -> StdResult<_> {
+        status.paused = paused;
+        Ok(status)
+    })?;
+
+    Ok(Response::default()
+        .add_attribute("action", if paused { "pause" } else { "unpause" })
+        .add_event(Event::new("mint-paused").add_attribute("paused", paused.to_string())))
+}
+
+pub fn try_set_trait_pool(
+    deps: DepsMut,
+    _env: Env,
+    info: MessageInfo,
+    pool: TraitPool,
+) -> Result<Response, ContractError> {
+    assert_role(deps.storage, &info.sender, Role::ConfigManager)?;
+    if pool.trait_type.is_empty()
+        || pool.options.is_empty()
+        || pool.options.iter().any(|o| o.weight == 0)
+    {
+        return Err(ContractError::InvalidTraitPool {
+            trait_type: pool.trait_type,
+        });
+    }
+    TRAIT_POOLS.save(deps.storage, &pool.trait_type, &pool)?;
+
+    Ok(Response::default()
+        .add_attribute("action", "set_trait_pool")
+        .add_attribute("trait_type", pool.trait_type.clone())
+        .add_event(
+            Event::new("trait-pool-set")
+                .add_attribute("trait_type", pool.trait_type.clone())
+                .add_attribute("options", json_attribute(&pool.options)?),
+        ))
+}
+
+pub fn try_remove_trait_pool(
+    deps: DepsMut,
+    _env: Env,
+    info: MessageInfo,
+    trait_type: String,
+) -> Result<Response, ContractError> {
+    assert_role(deps.storage, &info.sender, Role::ConfigManager)?;
+    if TRAIT_POOLS.may_load(deps.storage, &trait_type)?.is_none() {
+        return Err(ContractError::TraitPoolNotFound { trait_type });
+    }
+    TRAIT_POOLS.remove(deps.storage, &trait_type);
+
+    Ok(Response::default()
+        .add_attribute("action", "remove_trait_pool")
+        .add_attribute("trait_type", trait_type.clone())
+        .add_event(Event::new("trait-pool-removed").add_attribute("trait_type", trait_type)))
+}
+
+pub fn try_update_beacon(
     deps: DepsMut,
     _env: Env,
     info: MessageInfo,
+    beacon: String,
 ) -> Result<Response, ContractError> {
+    assert_role(deps.storage, &info.sender, Role::Minter)?;
+    BEACON.save(deps.storage, &beacon)?;
+
+    Ok(Response::default()
+        .add_attribute("action", "update_beacon")
+        .add_event(Event::new("beacon-updated").add_attribute("beacon", beacon)))
+}
+
+/// Mixes the last beacon value with block data and the mint counter, so the
+/// roll is deterministic for a given block but differs for every token.
+fn random_seed(beacon: &str, env: &Env, serial: u32, salt: &[u8]) -> u64 {
+    let digest = Sha256::new()
+        .chain(beacon.as_bytes())
+        .chain(env.block.chain_id.as_bytes())
+        .chain(env.block.height.to_be_bytes())
+        .chain(env.block.time.nanos().to_be_bytes())
+        .chain(serial.to_be_bytes())
+        .chain(salt)
+        .finalize();
+    let mut seed = [0u8; 8];
+    seed.copy_from_slice(&digest[0..8]);
+    u64::from_be_bytes(seed)
+}
+
+/// Rolls every registered trait pool for the token
+fn assign_traits(
+    storage: &mut dyn Storage,
+    env: &Env,
+    serial: u32,
+    token_id: &str,
+) -> Result<Vec<Trait>, ContractError> {
+    let pools: StdResult<Vec<_>> = TRAIT_POOLS
+        .range(storage, None, None, Order::Ascending)
+        .collect();
+    let pools = pools?;
+    if pools.is_empty() {
+        return Ok(vec![]);
+    }
+    let beacon = BEACON.may_load(storage)?.unwrap_or_default();
+
+    let mut traits: Vec<Trait> = vec![];
+    let mut rarity_score: u64 = 0;
+    for (_, pool) in pools {
+        let total_weight = pool.total_weight();
+        let seed = random_seed(&beacon, env, serial, pool.trait_type.as_bytes());
+        let option = pool.pick(seed % total_weight);
+
+        rarity_score += total_weight * 100 / option.weight as u64;
+        TRAIT_SUPPLY.update(
+            storage,
+            (&pool.trait_type, &option.value),
+            |count| -> StdResult<_> { Ok(count.unwrap_or_default() + 1) },
+        )?;
+        traits.push(Trait {
+            display_type: None,
+            trait_type: pool.trait_type.clone(),
+            value: option.value.clone(),
+        });
+    }
+
+    TOKEN_TRAITS.save(
+        storage,
+        token_id,
+        &TokenTraits {
+            traits: traits.clone(),
+            rarity_score,
+        },
+    )?;
+    Ok(traits)
+}
+
+/// Final metadata of a minted serial, shifted by the reveal offset when revealed.
+/// SecureMint tokens keep the metadata they were minted with.
+fn final_metadata(
+    storage: &dyn Storage,
+    config: &Config,
+    serial: u32,
+) -> Result<(Metadata, String), ContractError> {
+    let token_id = config.token_id(serial);
+    let (mut metadata, token_uri) = match MINTED_METADATA.may_load(storage, &token_id)? {
+        Some(minted) => (minted.extension, minted.token_uri),
+        None => {
+            let reveal = REVEAL.may_load(storage)?.unwrap_or_default();
+            let revealed_id = config.token_id(reveal.revealed_serial(serial, config.mint_limit));
+            (
+                config.nft_metadata.render(&revealed_id),
+                config.token_uri(&revealed_id),
+            )
+        }
+    };
+    if let Some(token_traits) = TOKEN_TRAITS.may_load(storage, &token_id)? {
+        add_traits(&mut metadata, token_traits.traits);
+    }
+    Ok((metadata, token_uri))
+}
+
+/// True while minted tokens show the placeholder, their traits stay private until then
+fn placeholder_showing(storage: &dyn Storage, config: &Config) -> StdResult<bool> {
+    let reveal = REVEAL.may_load(storage)?.unwrap_or_default();
+    Ok(config.placeholder_metadata.is_some() && !reveal.is_revealed())
+}
+
+/// What a mint sends to the nft contract, the real metadata is pushed by SyncRevealed
+fn mint_metadata(
+    storage: &dyn Storage,
+    config: &Config,
+    serial: u32,
+) -> Result<(Metadata, String), ContractError> {
+    match &config.placeholder_metadata {
+        Some(placeholder) if placeholder_showing(storage, config)? => Ok((
+            placeholder.render(&config.token_id(serial)),
+            config.placeholder_uri.clone().unwrap_or_default(),
+        )),
+        _ => final_metadata(storage, config, serial),
+    }
+}
+
+fn add_traits(metadata: &mut Metadata, traits: Vec<Trait>) {
+    let mut attributes = metadata.attributes.take().unwrap_or_default();
+    attributes.extend(traits);
+    metadata.attributes = Some(attributes);
+}
+
+fn traits_event(token_id: &str, traits: &[Trait]) -> StdResult<Event> {
+    Ok(Event::new("traits-assigned")
+        .add_attribute("token_id", token_id)
+        .add_attribute("traits", json_attribute(&traits)?))
+}
+
+pub fn try_commit_provenance(
+    deps: DepsMut,
+    _env: Env,
+    info: MessageInfo,
+    provenance_hash: String,
+) -> Result<Response, ContractError> {
+    assert_role(deps.storage, &info.sender, Role::ConfigManager)?;
+    let mut reveal = REVEAL.may_load(deps.storage)?.unwrap_or_default();
+    if reveal.is_revealed() {
+        return Err(ContractError::AlreadyRevealed {});
+    }
+    // every mint after this feeds the entropy the offset is drawn from
+    if MINTSTATUS.load(deps.storage)?.mint_count > 0 {
+        return Err(ContractError::ProvenanceLocked {});
+    }
+    reveal.provenance_hash = Some(provenance_hash.clone());
+    REVEAL.save(deps.storage, &reveal)?;
+
+    Ok(Response::default()
+        .add_attribute("action", "commit_provenance")
+        .add_attribute("provenance_hash", provenance_hash.clone())
+        .add_event(
+            Event::new("provenance-committed").add_attribute("provenance_hash", provenance_hash),
+        ))
+}
+
+pub fn try_reveal(deps: DepsMut, env: Env, info: MessageInfo) -> Result<Response, ContractError> {
+    assert_role(deps.storage, &info.sender, Role::ConfigManager)?;
+    let config = CONFIG.load(deps.storage)?;
+    let mut reveal = REVEAL.may_load(deps.storage)?.unwrap_or_default();
+    if reveal.is_revealed() {
+        return Err(ContractError::AlreadyRevealed {});
+    }
+    let provenance_hash = match &reveal.provenance_hash {
+        None => return Err(ContractError::ProvenanceNotCommitted {}),
+        Some(provenance_hash) => provenance_hash.clone(),
+    };
+    let mintstatus = MINTSTATUS.load(deps.storage)?;
+    let time = env.block.time.nanos() / 1_000_000_000;
+    let ended = matches!(config.end_time, Some(end_time) if time >= end_time);
+    if !ended && mintstatus.mint_count < config.mint_limit {
+        return Err(ContractError::SaleNotOver {});
+    }
+
+    let offset = reveal_offset(&reveal, &provenance_hash, config.mint_limit);
+    reveal.offset = Some(offset);
+    REVEAL.save(deps.storage, &reveal)?;
+
+    Ok(Response::default()
+        .add_attribute("action", "reveal")
+        .add_attribute("offset", offset.to_string())
+        .add_event(
+            Event::new("collection-revealed")
+                .add_attribute("provenance_hash", provenance_hash)
+                .add_attribute("offset", offset.to_string()),
+        ))
+}
+
+/// Only the commitment and the mints that followed it go in, so the reveal
+/// block and the beacon have no say over the offset
+fn reveal_offset(reveal: &RevealState, provenance_hash: &str, mint_limit: u32) -> u32 {
+    let digest = Sha256::new()
+        .chain(provenance_hash.as_bytes())
+        .chain(reveal.entropy.as_slice())
+        .finalize();
+    let mut seed = [0u8; 8];
+    seed.copy_from_slice(&digest[0..8]);
+    (u64::from_be_bytes(seed) % mint_limit.max(1) as u64) as u32
+}
+
+/// Chains a mint into the reveal entropy
+fn record_mint(storage: &mut dyn Storage, env: &Env, serial: u32, owner: &str) -> StdResult<()> {
+    let mut reveal = REVEAL.may_load(storage)?.unwrap_or_default();
+    if reveal.is_revealed() {
+        return Ok(());
+    }
+    let digest = Sha256::new()
+        .chain(reveal.entropy.as_slice())
+        .chain(serial.to_be_bytes())
+        .chain(owner.as_bytes())
+        .chain(env.block.height.to_be_bytes())
+        .chain(env.block.time.nanos().to_be_bytes())
+        .finalize();
+    reveal.entropy = Binary::from(digest.as_slice());
+    REVEAL.save(storage, &reveal)
+}
+
+pub fn try_sync_revealed(
+    deps: DepsMut,
+    _env: Env,
+    info: MessageInfo,
+    limit: Option<u32>,
+) -> Result<Response, ContractError> {
+    assert_role(deps.storage, &info.sender, Role::Minter)?;
     let config = CONFIG.load(deps.storage)?;
-    if info.sender != config.admin {
-        return Err(Unauthorized {}.build());
+    let mut reveal = REVEAL.may_load(deps.storage)?.unwrap_or_default();
+    if !reveal.is_revealed() {
+        return Err(ContractError::NotRevealed {});
     }
-    let balance = deps.querier.query_balance(&_env.contract.address, "uusd")?;
+    let mintstatus = MINTSTATUS.load(deps.storage)?;
+    let limit = limit.unwrap_or(DEFAULT_SYNC_LIMIT).min(MAX_SYNC_LIMIT);
+    let end = mintstatus.mint_count.min(reveal.synced_count + limit);
+    let event = Event::new("metadata-synced")
+        .add_attribute("from_serial", (reveal.synced_count + 1).to_string())
+        .add_attribute("to_serial", end.to_string());
+
     let mut msgs: Vec<SubMsg> = vec![];
-    for fund_share in config.shares {
-        let amount = fund_share.get_share(balance.clone().amount).u128();
-        msgs.push(deposit_funds(
-            fund_share.address.clone(),
-            vec![deduct_tax(deps.as_ref(), coin(amount, "uusd"))?],
+    // trait events held back while the placeholder showed go out with the sync
+    let mut trait_events: Vec<Event> = vec![];
+    for serial in (reveal.synced_count + 1)..=end {
+        let token_id = config.token_id(serial);
+        let (extension, token_uri) = final_metadata(deps.storage, &config, serial)?;
+        if config.placeholder_metadata.is_some() {
+            if let Some(token_traits) = TOKEN_TRAITS.may_load(deps.storage, &token_id)? {
+                trait_events.push(traits_event(&token_id, &token_traits.traits)?);
+            }
+        }
+        msgs.push(update_nft_metadata(
+            token_id,
+            config.nft_contract.clone(),
+            extension,
+            token_uri,
         )?);
     }
+    reveal.synced_count = end;
+    REVEAL.save(deps.storage, &reveal)?;
 
     Ok(Response::default()
         .add_submessages(msgs)
-        .add_attribute("action", "move_funds"))
+        .add_attribute("action", "sync_revealed")
+        .add_attribute("synced_count", end.to_string())
+        .add_event(event)
+        .add_events(trait_events))
+}
+
+pub fn try_move_funds(
+    deps: DepsMut,
+    _env: Env,
+    info: MessageInfo,
+) -> Result<Response, ContractError> {
+    assert_role(deps.storage, &info.sender, Role::TreasuryOperator)?;
+    let config = CONFIG.load(deps.storage)?;
+    let payouts = move_funds_payouts(deps.as_ref(), &_env, &config)?;
+    let mut msgs: Vec<SubMsg> = vec![];
+    let mut events: Vec<Event> = vec![];
+    for (fund_share, payout) in config.shares.iter().zip(payouts) {
+        events.push(
+            Event::new("share-distributed")
+                .add_attribute("wallet", payout.recipient.clone())
+                .add_attribute("note", fund_share.note.clone())
+                .add_attribute("share", fund_share.share.to_string())
+                .add_attribute("amount", payout.net.amount)
+                .add_attribute("denom", payout.net.denom.clone()),
+        );
+        msgs.push(deposit_funds(payout.recipient, vec![payout.net])?);
+    }
+
+    Ok(Response::default()
+        .add_submessages(msgs)
+        .add_attribute("action", "move_funds")
+        .add_events(events))
+}
+
+/// One payout per share, in config order
+fn move_funds_payouts(deps: Deps, env: &Env, config: &Config) -> StdResult<Vec<Payout>> {
+    let balance = deps.querier.query_balance(&env.contract.address, "uusd")?;
+    config
+        .shares
+        .iter()
+        .map(|fund_share| {
+            let amount = fund_share.get_share(balance.amount).u128();
+            payout(deps, fund_share.address.clone(), coin(amount, "uusd"))
+        })
+        .collect()
 }
 
 pub fn try_mint(deps: DepsMut, _env: Env, info: MessageInfo) -> Result<Response, ContractError> {
     let config = CONFIG.load(deps.storage)?;
+    let mut mintstatus = MINTSTATUS.load(deps.storage)?;
+    if mintstatus.paused {
+        return Err(ContractError::Paused {});
+    }
+    let time = _env.block.time.nanos() / 1_000_000_000;
+    if let Some(start_time) = config.start_time {
+        if time < start_time {
+            return Err(ContractError::SaleNotStarted { start_time });
+        }
+    }
+    if let Some(end_time) = config.end_time {
+        if time >= end_time {
+            return Err(ContractError::SaleEnded { end_time });
+        }
+    }
+
     let sent_funds = info.funds.clone();
-    let mint_count: u32;
+    let mint_count: u32 = 1;
     if sent_funds.is_empty() {
-        return Err(ContractError::EmptyBalance {});
+        return Err(ContractError::NoFundsSent {});
     }
 
-    if config.price.denom != sent_funds[0].denom {
-        return Err(ContractError::InsufficientFund {});
+    // price is computed at execution time, the tolerance covers a price that
+    // moved after the buyer signed and nothing sent is refunded
+    let price = config.current_price(time, mintstatus.mint_count);
+    if price.denom != sent_funds[0].denom {
+        return Err(ContractError::WrongDenom {
+            expected: price.denom,
+            received: sent_funds[0].denom.clone(),
+        });
     }
-    mint_count = sent_funds[0].amount.div(config.price.amount).u128() as u32;
-    if mint_count != 1 {
-        return Err(ContractError::InsufficientFund {});
+    if sent_funds[0].amount < price.amount {
+        return Err(ContractError::InsufficientFunds {
+            required: price.amount,
+            received: sent_funds[0].amount,
+        });
+    }
+    let max = price.amount + config.price_tolerance;
+    if sent_funds[0].amount > max {
+        return Err(ContractError::Overpaid {
+            max,
+            received: sent_funds[0].amount,
+        });
     }
-    let mut mintstatus = MINTSTATUS.load(deps.storage)?;
 
-    // limit check
-    if mintstatus.mint_count + mint_count > config.mint_limit {
-        return Err(ContractError::MintLimitReached {});
+    // limit check, the reserved allocation is not for sale
+    if mintstatus.sale_count() + mint_count > config.sale_supply() {
+        return Err(ContractError::MintLimitReached {
+            limit: config.sale_supply(),
+        });
     }
-    let token_id = mintstatus.mint_count + mint_count;
-    mintstatus.mint_count = token_id;
+    mintstatus.mint_count = mintstatus.mint_count + mint_count;
+    let token_id = config.token_id(mintstatus.mint_count);
+
+    let traits = assign_traits(deps.storage, &_env, mintstatus.mint_count, &token_id)?;
+    record_mint(
+        deps.storage,
+        &_env,
+        mintstatus.mint_count,
+        info.sender.as_str(),
+    )?;
+
+    let (metadata, token_uri) = mint_metadata(deps.storage, &config, mintstatus.mint_count)?;
 
     let submsg = mint_nft(
         info.sender.clone().to_string(),
-        token_id.clone().to_string(),
+        token_id.clone(),
         config.nft_contract.clone(),
-        &config.nft_metadata,
-        "".to_string(),
+        &metadata,
+        token_uri,
     )?;
 
     MINTSTATUS.save(deps.storage, &mintstatus)?;
-    Ok(Response::default()
+    let mut rsp = Response::default()
         .add_submessage(submsg)
         .add_attribute("action", "mint_nft_1")
-        .add_attribute("token_id", token_id.clone().to_string()))
+        .add_attribute("token_id", token_id.clone())
+        .add_event(
+            mint_event(&token_id, info.sender.as_str(), "sale", &mintstatus)
+                .add_attribute("amount", sent_funds[0].amount)
+                .add_attribute("denom", price.denom)
+                .add_attribute("price", price.amount),
+        );
+    if !traits.is_empty() && !placeholder_showing(deps.storage, &config)? {
+        rsp = rsp.add_event(traits_event(&token_id, &traits)?);
+    }
+    Ok(rsp)
 }
 
 pub fn try_secure_mint(
@@ -164,37 +782,103 @@ pub fn try_secure_mint(
     extension: Metadata,
 ) -> Result<Response, ContractError> {
     let config = CONFIG.load(deps.storage)?;
-    if !(info.sender == config.admin || info.sender == config.minter) {
-        return Err(Unauthorized {}.build());
+    let is_team_mint = is_admin(deps.storage, &info.sender)?;
+    if !is_team_mint && !has_role(deps.storage, &info.sender, Role::Minter)? {
+        return Err(unauthorized(&info.sender, "admin or minter").into());
     }
 
     let mut mintstatus = MINTSTATUS.load(deps.storage)?;
-    let token_id = mintstatus.mint_count + 1;
-    mintstatus.mint_count = token_id;
+    if mintstatus.paused {
+        return Err(ContractError::Paused {});
+    }
+
+    // admin mints come out of the team reservation, minter mints out of the sale supply
+    if is_team_mint {
+        if mintstatus.reserved_count + 1 > config.reserved_supply {
+            return Err(ContractError::ReservedLimitReached {
+                limit: config.reserved_supply,
+            });
+        }
+        mintstatus.reserved_count += 1;
+    } else if mintstatus.sale_count() + 1 > config.sale_supply() {
+        return Err(ContractError::MintLimitReached {
+            limit: config.sale_supply(),
+        });
+    }
+    mintstatus.mint_count = mintstatus.mint_count + 1;
+    let token_id = config.token_id(mintstatus.mint_count);
+
+    // explicit uri wins, otherwise fall back to the configured base uri
+    let token_uri = if token_uri.is_empty() {
+        config.token_uri(&token_id)
+    } else {
+        token_uri
+    };
 
     MINTSTATUS.save(deps.storage, &mintstatus)?;
+    // team and minter tokens roll the same trait pools as sale tokens
+    let traits = assign_traits(deps.storage, &_env, mintstatus.mint_count, &token_id)?;
+    record_mint(deps.storage, &_env, mintstatus.mint_count, &owner)?;
 
-    Ok(Response::default()
+    MINTED_METADATA.save(
+        deps.storage,
+        &token_id,
+        &MintedMetadata {
+            extension: extension.render(&token_id),
+            token_uri,
+        },
+    )?;
+    let (metadata, token_uri) = mint_metadata(deps.storage, &config, mintstatus.mint_count)?;
+
+    let kind = if is_team_mint { "team" } else { "minter" };
+    let mut rsp = Response::default()
         .add_submessage(mint_nft(
             owner.clone(),
-            token_id.clone().to_string(),
+            token_id.clone(),
             config.nft_contract.clone(),
-            &extension,
+            &metadata,
             token_uri,
         )?)
         .add_attribute("action", "secure_mint_nft")
-        .add_attribute("token_id", token_id.clone().to_string()))
+        .add_attribute("token_id", token_id.clone())
+        .add_event(
+            mint_event(&token_id, &owner, kind, &mintstatus)
+                .add_attribute("amount", Uint128::zero())
+                .add_attribute("denom", config.price.denom.clone()),
+        );
+    if !traits.is_empty() && !placeholder_showing(deps.storage, &config)? {
+        rsp = rsp.add_event(traits_event(&token_id, &traits)?);
+    }
+    Ok(rsp)
+}
+
+/// Shared by every mint path, `kind` is sale, team or minter
+fn mint_event(token_id: &str, owner: &str, kind: &str, status: &MintStatus) -> Event {
+    Event::new("delorean-mint")
+        .add_attribute("token_id", token_id)
+        .add_attribute("owner", owner)
+        .add_attribute("kind", kind)
+        .add_attribute("mint_count", status.mint_count.to_string())
+        .add_attribute("reserved_count", status.reserved_count.to_string())
 }
 
 #[cfg_attr(not(feature = "library"), entry_point)]
-pub fn migrate(_deps: DepsMut, _env: Env, _msg: MigrateMsg) -> Result<Response, ContractError> {
-    Ok(Response::default())
+pub fn migrate(deps: DepsMut, _env: Env, _msg: MigrateMsg) -> Result<Response, ContractError> {
+    migrate_store(deps)
 }
 
 pub fn query(deps: Deps, env: Env, msg: QueryMsg) -> StdResult<QueryResponse> {
     match msg {
         QueryMsg::Status {} => get_status(deps, env),
         QueryMsg::Config {} => get_config(deps, env),
+        QueryMsg::Ownership {} => get_ownership(deps, env),
+        QueryMsg::RoleMembers { role } => get_role_members(deps, env, role),
+        QueryMsg::TraitPools {} => get_trait_pools(deps, env),
+        QueryMsg::TraitSupply {} => get_trait_supply(deps, env),
+        QueryMsg::TokenTraits { token_id } => get_token_traits(deps, env, token_id),
+        QueryMsg::RevealStatus {} => get_reveal_status(deps, env),
+        QueryMsg::CurrentPrice {} => get_current_price(deps, env),
+        QueryMsg::SimulateMoveFunds {} => get_simulate_move_funds(deps, env),
     }
 }
 
@@ -209,3 +893,77 @@ fn get_config(deps: Deps, _env: Env) -> StdResult<QueryResponse> {
     let rsp = ConfigResponse { config: state };
     to_binary(&rsp)
 }
+
+fn get_trait_pools(deps: Deps, _env: Env) -> StdResult<QueryResponse> {
+    let pools: StdResult<Vec<_>> = TRAIT_POOLS
+        .range(deps.storage, None, None, Order::Ascending)
+        .map(|item| item.map(|(_, pool)| pool))
+        .collect();
+    let rsp = TraitPoolsResponse { pools: pools? };
+    to_binary(&rsp)
+}
+
+fn assert_traits_public(deps: Deps) -> StdResult<()> {
+    let config = CONFIG.load(deps.storage)?;
+    if placeholder_showing(deps.storage, &config)? {
+        return Err(StdError::generic_err(
+            "TraitsHidden: traits stay private until the reveal",
+        ));
+    }
+    Ok(())
+}
+
+fn get_trait_supply(deps: Deps, _env: Env) -> StdResult<QueryResponse> {
+    assert_traits_public(deps)?;
+    let pools: StdResult<Vec<_>> = TRAIT_POOLS
+        .range(deps.storage, None, None, Order::Ascending)
+        .collect();
+    let mut supply: Vec<TraitSupply> = vec![];
+    for (_, pool) in pools? {
+        for option in pool.options {
+            let count = TRAIT_SUPPLY
+                .may_load(deps.storage, (&pool.trait_type, &option.value))?
+                .unwrap_or_default();
+            supply.push(TraitSupply {
+                trait_type: pool.trait_type.clone(),
+                value: option.value,
+                weight: option.weight,
+                count,
+            });
+        }
+    }
+    let rsp = TraitSupplyResponse { supply };
+    to_binary(&rsp)
+}
+
+fn get_token_traits(deps: Deps, _env: Env, token_id: String) -> StdResult<QueryResponse> {
+    assert_traits_public(deps)?;
+    let token_traits = TOKEN_TRAITS.may_load(deps.storage, &token_id)?;
+    let rsp = TokenTraitsResponse { token_traits };
+    to_binary(&rsp)
+}
+
+fn get_reveal_status(deps: Deps, _env: Env) -> StdResult<QueryResponse> {
+    let reveal = REVEAL.may_load(deps.storage)?.unwrap_or_default();
+    let rsp = RevealStatusResponse { reveal };
+    to_binary(&rsp)
+}
+
+fn get_current_price(deps: Deps, env: Env) -> StdResult<QueryResponse> {
+    let config = CONFIG.load(deps.storage)?;
+    let status = MINTSTATUS.load(deps.storage)?;
+    let time = env.block.time.nanos() / 1_000_000_000;
+    let rsp = CurrentPriceResponse {
+        price: config.current_price(time, status.mint_count),
+        pricing: config.pricing,
+    };
+    to_binary(&rsp)
+}
+
+fn get_simulate_move_funds(deps: Deps, env: Env) -> StdResult<QueryResponse> {
+    let config = CONFIG.load(deps.storage)?;
+    let rsp = SimulatePayoutsResponse {
+        payouts: move_funds_payouts(deps, &env, &config)?,
+    };
+    to_binary(&rsp)
+}
@@ -1,33 +1,69 @@
-use cosmwasm_std::StdError;
-use snafu::Snafu;
+use cosmwasm_std::{StdError, Uint128};
+use delorean_common::errors::CommonError;
+use thiserror::Error;
 
-#[derive(Snafu, Debug)]
-#[snafu(visibility = "pub(crate)")]
+#[derive(Error, Debug)]
 pub enum ContractError {
-    /// this is needed so we can use `bucket.load(...)?` and have it auto-converted to the custom error
-    #[snafu(display("StdError: {}", original))]
-    Std { original: StdError },
-    #[snafu(display("Unauthorized"))]
-    Unauthorized { backtrace: Option<snafu::Backtrace> },
+    #[error("{0}")]
+    Std(#[from] StdError),
 
-    #[snafu(display("EmptyBalance"))]
-    EmptyBalance {},
+    #[error("{0}")]
+    Common(#[from] CommonError),
 
-    #[snafu(display("InsufficientFund"))]
-    InsufficientFund {},
+    #[error("NoFundsSent")]
+    NoFundsSent {},
 
-    #[snafu(display("MintLimitReached"))]
-    MintLimitReached {},
+    #[error("WrongDenom: expected {expected}, received {received}")]
+    WrongDenom { expected: String, received: String },
 
-    #[snafu(display("NotFound"))]
-    NotFound {},
+    #[error("InsufficientFunds: required {required}, received {received}")]
+    InsufficientFunds {
+        required: Uint128,
+        received: Uint128,
+    },
 
-    #[snafu(display("PriceMismatch"))]
-    PriceMismatch {},
-}
+    #[error("Overpaid: at most {max}, received {received}")]
+    Overpaid { max: Uint128, received: Uint128 },
+
+    #[error("InvalidPricing")]
+    InvalidPricing {},
+
+    #[error("MintLimitReached: {limit}")]
+    MintLimitReached { limit: u32 },
+
+    #[error("ReservedLimitReached: {limit}")]
+    ReservedLimitReached { limit: u32 },
+
+    #[error("Paused")]
+    Paused {},
+
+    #[error("SaleNotStarted: starts at {start_time}")]
+    SaleNotStarted { start_time: u64 },
+
+    #[error("SaleEnded: ended at {end_time}")]
+    SaleEnded { end_time: u64 },
+
+    #[error("InvalidTraitPool: {trait_type}")]
+    InvalidTraitPool { trait_type: String },
+
+    #[error("TraitPoolNotFound: {trait_type}")]
+    TraitPoolNotFound { trait_type: String },
+
+    #[error("AlreadyRevealed")]
+    AlreadyRevealed {},
+
+    #[error("NotRevealed")]
+    NotRevealed {},
+
+    #[error("ProvenanceNotCommitted")]
+    ProvenanceNotCommitted {},
+
+    #[error("ProvenanceLocked: minting has started")]
+    ProvenanceLocked {},
+
+    #[error("ConfigLocked: {field} is fixed by the provenance hash")]
+    ConfigLocked { field: String },
 
-impl From<StdError> for ContractError {
-    fn from(original: StdError) -> Self {
-        Std { original }.build()
-    }
+    #[error("SaleNotOver")]
+    SaleNotOver {},
 }
@@ -0,0 +1,73 @@
+use crate::errors::ContractError;
+use crate::ownership::{init_ownership, Role, RoleGrant};
+use crate::state::{Config, FundShare, Metadata, PricingMode, CONFIG};
+use cosmwasm_std::{Coin, DepsMut, Response, StdResult, Storage, Uint128};
+use cw2::set_contract_version;
+use cw_storage_plus::Item;
+use delorean_common::versioning::{assert_upgrade, migrate_response};
+use semver::Version;
+use serde::{Deserialize, Serialize};
+
+pub const CONTRACT_NAME: &str = concat!("crates.io:", env!("CARGO_PKG_NAME"));
+pub const CONTRACT_VERSION: &str = env!("CARGO_PKG_VERSION");
+
+const LEGACY_CONFIG_KEY: &str = "config";
+
+/// Config layout from before the admin and minter moved into ownership
+#[derive(Serialize, Deserialize)]
+struct LegacyConfig {
+    admin: String,
+    minter: String,
+    nft_contract: String,
+    nft_metadata: Metadata,
+    shares: Vec<FundShare>,
+    price: Coin,
+    mint_limit: u32,
+}
+
+const LEGACY_CONFIG: Item<LegacyConfig> = Item::new(LEGACY_CONFIG_KEY);
+
+pub fn set_version(storage: &mut dyn Storage) -> StdResult<()> {
+    set_contract_version(storage, CONTRACT_NAME, CONTRACT_VERSION)
+}
+
+pub fn migrate_store(mut deps: DepsMut) -> Result<Response, ContractError> {
+    let stored = assert_upgrade(deps.storage, CONTRACT_NAME, CONTRACT_VERSION)?;
+    if stored < Version::new(0, 2, 0) {
+        migrate_from_legacy(deps.branch())?;
+    }
+
+    set_version(deps.storage)?;
+    Ok(migrate_response(CONTRACT_NAME, &stored, CONTRACT_VERSION))
+}
+
+/// Moves the admin and minter into ownership, new settings start at their
+/// neutral values: fixed price, no reserve, no sale window and no templates
+fn migrate_from_legacy(mut deps: DepsMut) -> Result<(), ContractError> {
+    let legacy = LEGACY_CONFIG.load(deps.storage)?;
+    let roles = vec![RoleGrant {
+        role: Role::Minter,
+        address: legacy.minter,
+    }];
+    init_ownership(deps.branch(), legacy.admin, roles)?;
+    CONFIG.save(
+        deps.storage,
+        &Config {
+            nft_contract: legacy.nft_contract,
+            nft_metadata: legacy.nft_metadata,
+            shares: legacy.shares,
+            price: legacy.price,
+            pricing: PricingMode::Fixed {},
+            price_tolerance: Uint128::zero(),
+            mint_limit: legacy.mint_limit,
+            reserved_supply: 0,
+            start_time: None,
+            end_time: None,
+            token_id_prefix: None,
+            base_uri: None,
+            placeholder_metadata: None,
+            placeholder_uri: None,
+        },
+    )?;
+    Ok(())
+}
@@ -1,17 +1,40 @@
-use crate::state::{Config, Metadata, MintStatus};
+use crate::ownership::{Role, RoleGrant};
+use crate::state::{
+    Config, FundShare, Metadata, MintStatus, PricingMode, RevealState, TokenTraits, TraitPool,
+};
+use cosmwasm_std::{Coin, Uint128};
+pub use delorean_common::ownership::OwnershipResponse;
+pub use delorean_common::payout::Payout;
 use schemars::JsonSchema;
-use serde::{Deserialize, Serialize};
+use serde::{Deserialize, Deserializer, Serialize};
 
 #[derive(Serialize, Deserialize, Clone, Debug, PartialEq, JsonSchema)]
 pub struct InstantiateMsg {
     pub config: Config,
+    /// Defaults to the instantiating wallet
+    pub admin: Option<String>,
+    #[serde(default)]
+    pub roles: Vec<RoleGrant>,
 }
 
 #[derive(Serialize, Deserialize, Clone, Debug, PartialEq, JsonSchema)]
 #[serde(rename_all = "snake_case")]
 pub enum ExecuteMsg {
-    ConfigUpdate {
-        config: Config,
+    UpdateConfig {
+        config: ConfigUpdate,
+    },
+    ProposeNewAdmin {
+        new_admin: String,
+    },
+    AcceptAdmin {},
+    RenounceAdmin {},
+    GrantRole {
+        role: Role,
+        address: String,
+    },
+    RevokeRole {
+        role: Role,
+        address: String,
     },
     Mint {},
     SecureMint {
@@ -20,6 +43,84 @@ pub enum ExecuteMsg {
         extension: Metadata,
     },
     MoveFunds {},
+    Pause {},
+    Unpause {},
+    SetTraitPool {
+        pool: TraitPool,
+    },
+    RemoveTraitPool {
+        trait_type: String,
+    },
+    UpdateBeacon {
+        beacon: String,
+    },
+    CommitProvenance {
+        provenance_hash: String,
+    },
+    Reveal {},
+    /// Pushes the revealed metadata of the next `limit` minted tokens to the nft contract
+    SyncRevealed {
+        limit: Option<u32>,
+    },
+}
+
+/// Every field is optional, only the provided ones are changed. For the optional
+/// config fields `null` clears the value and a missing field leaves it untouched.
+#[derive(Serialize, Deserialize, Clone, Debug, PartialEq, JsonSchema, Default)]
+pub struct ConfigUpdate {
+    pub nft_contract: Option<String>,
+    pub nft_metadata: Option<Metadata>,
+    pub shares: Option<Vec<FundShare>>,
+    pub price: Option<Coin>,
+    pub pricing: Option<PricingMode>,
+    pub price_tolerance: Option<Uint128>,
+    pub mint_limit: Option<u32>,
+    pub reserved_supply: Option<u32>,
+    #[serde(
+        default,
+        skip_serializing_if = "Option::is_none",
+        deserialize_with = "nullable"
+    )]
+    pub start_time: Option<Option<u64>>,
+    #[serde(
+        default,
+        skip_serializing_if = "Option::is_none",
+        deserialize_with = "nullable"
+    )]
+    pub end_time: Option<Option<u64>>,
+    #[serde(
+        default,
+        skip_serializing_if = "Option::is_none",
+        deserialize_with = "nullable"
+    )]
+    pub token_id_prefix: Option<Option<String>>,
+    #[serde(
+        default,
+        skip_serializing_if = "Option::is_none",
+        deserialize_with = "nullable"
+    )]
+    pub base_uri: Option<Option<String>>,
+    #[serde(
+        default,
+        skip_serializing_if = "Option::is_none",
+        deserialize_with = "nullable"
+    )]
+    pub placeholder_metadata: Option<Option<Metadata>>,
+    #[serde(
+        default,
+        skip_serializing_if = "Option::is_none",
+        deserialize_with = "nullable"
+    )]
+    pub placeholder_uri: Option<Option<String>>,
+}
+
+/// Tells a field sent as `null` apart from a missing one
+fn nullable<'de, T, D>(deserializer: D) -> Result<Option<Option<T>>, D::Error>
+where
+    T: Deserialize<'de>,
+    D: Deserializer<'de>,
+{
+    Option::<T>::deserialize(deserializer).map(Some)
 }
 
 #[derive(Serialize, Deserialize, Clone, Debug, PartialEq, JsonSchema)]
@@ -39,6 +140,11 @@ pub enum NftExecuteMsg {
         /// Any custom extension used by this contract
         extension: Metadata,
     },
+    UpdateMetadata {
+        token_id: String,
+        token_uri: String,
+        extension: Metadata,
+    },
 }
 
 #[derive(Serialize, Deserialize, Clone, Debug, PartialEq, JsonSchema)]
@@ -53,6 +159,21 @@ pub enum QueryMsg {
     /// Config returns the stored configuration state. Returns State
     Status {},
     Config {},
+    Ownership {},
+    RoleMembers {
+        role: Role,
+    },
+    TraitPools {},
+    /// Fails while minted tokens show the placeholder, so are TokenTraits
+    TraitSupply {},
+    TokenTraits {
+        token_id: String,
+    },
+    /// The offset is the first 8 bytes of `sha256(provenance_hash || entropy)`
+    /// read big endian, modulo mint_limit
+    RevealStatus {},
+    CurrentPrice {},
+    SimulateMoveFunds {},
 }
 
 #[derive(Serialize, Deserialize, Clone, Debug, PartialEq, JsonSchema)]
@@ -64,3 +185,44 @@ pub struct StatusResponse {
 pub struct ConfigResponse {
     pub config: Config,
 }
+
+pub type RoleMembersResponse = delorean_common::ownership::RoleMembersResponse<Role>;
+
+#[derive(Serialize, Deserialize, Clone, Debug, PartialEq, JsonSchema)]
+pub struct TraitPoolsResponse {
+    pub pools: Vec<TraitPool>,
+}
+
+#[derive(Serialize, Deserialize, Clone, Debug, PartialEq, JsonSchema)]
+pub struct TraitSupply {
+    pub trait_type: String,
+    pub value: String,
+    pub weight: u32,
+    pub count: u32,
+}
+
+#[derive(Serialize, Deserialize, Clone, Debug, PartialEq, JsonSchema)]
+pub struct TraitSupplyResponse {
+    pub supply: Vec<TraitSupply>,
+}
+
+#[derive(Serialize, Deserialize, Clone, Debug, PartialEq, JsonSchema)]
+pub struct TokenTraitsResponse {
+    pub token_traits: Option<TokenTraits>,
+}
+
+#[derive(Serialize, Deserialize, Clone, Debug, PartialEq, JsonSchema)]
+pub struct RevealStatusResponse {
+    pub reveal: RevealState,
+}
+
+#[derive(Serialize, Deserialize, Clone, Debug, PartialEq, JsonSchema)]
+pub struct CurrentPriceResponse {
+    pub price: Coin,
+    pub pricing: PricingMode,
+}
+
+#[derive(Serialize, Deserialize, Clone, Debug, PartialEq, JsonSchema)]
+pub struct SimulatePayoutsResponse {
+    pub payouts: Vec<Payout>,
+}
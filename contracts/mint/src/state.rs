@@ -1,25 +1,92 @@
 use schemars::JsonSchema;
 use serde::{Deserialize, Serialize};
 
-use cosmwasm_std::{Coin, Uint128};
-use cw_storage_plus::Item;
+use cosmwasm_std::{Binary, Coin, Uint128};
+use cw_storage_plus::{Item, Map};
 const CONFIG_KEY: &str = "config";
 const MINT_STATUS_KEY: &str = "mint_status";
+const TRAIT_POOLS_KEY: &str = "trait_pools";
+const TRAIT_SUPPLY_KEY: &str = "trait_supply";
+const TOKEN_TRAITS_KEY: &str = "token_traits";
+const BEACON_KEY: &str = "beacon";
+const REVEAL_KEY: &str = "reveal";
+const MINTED_METADATA_KEY: &str = "minted_metadata";
 
 #[derive(Serialize, Deserialize, Clone, Debug, PartialEq, JsonSchema)]
 pub struct Config {
-    pub admin: String,
-    pub minter: String,
     pub nft_contract: String,
     pub nft_metadata: Metadata,
     pub shares: Vec<FundShare>,
+    /// Fixed price, its denom is used by every pricing mode
     pub price: Coin,
+    pub pricing: PricingMode,
+    /// How much a buyer may send above the current price, kept as part of the payment
+    #[serde(default)]
+    pub price_tolerance: Uint128,
+    /// Hard cap on the collection, binds Mint and SecureMint
     pub mint_limit: u32,
+    /// Part of mint_limit kept for team mints done by the admin through SecureMint,
+    /// SecureMint from a minter comes out of the sale supply
+    pub reserved_supply: u32,
+    /// Public sale window in seconds, open ended when unset
+    pub start_time: Option<u64>,
+    pub end_time: Option<u64>,
+    /// Prepended to the mint counter to build the token id, e.g. `DLR-` gives `DLR-1`
+    pub token_id_prefix: Option<String>,
+    /// When set, token_uri is `base_uri + token_id + ".json"`
+    pub base_uri: Option<String>,
+    /// Minted in place of nft_metadata until the collection is revealed
+    pub placeholder_metadata: Option<Metadata>,
+    pub placeholder_uri: Option<String>,
+}
+
+#[derive(Serialize, Deserialize, Clone, Debug, PartialEq, JsonSchema)]
+#[serde(rename_all = "snake_case")]
+pub enum PricingMode {
+    Fixed {},
+    /// Drops by `decay` every `interval` seconds after `start_time`, down to `floor_price`
+    DutchAuction {
+        start_time: u64,
+        start_price: Uint128,
+        floor_price: Uint128,
+        decay: Uint128,
+        interval: u64,
+    },
+    /// `base_price + increment * mint_count`
+    BondingCurve {
+        base_price: Uint128,
+        increment: Uint128,
+    },
+}
+
+impl PricingMode {
+    pub fn is_valid(&self) -> bool {
+        match self {
+            PricingMode::Fixed {} => true,
+            PricingMode::DutchAuction {
+                start_price,
+                floor_price,
+                interval,
+                ..
+            } => *interval > 0 && floor_price <= start_price,
+            PricingMode::BondingCurve { .. } => true,
+        }
+    }
 }
 
 #[derive(Serialize, Deserialize, Clone, Debug, PartialEq, JsonSchema)]
 pub struct MintStatus {
     pub mint_count: u32,
+    #[serde(default)]
+    pub reserved_count: u32,
+    #[serde(default)]
+    pub paused: bool,
+}
+
+impl MintStatus {
+    pub fn sale_count(&self) -> u32 {
+        self.mint_count - self.reserved_count
+    }
 }
 
 #[derive(Serialize, Deserialize, Clone, Debug, PartialEq, JsonSchema)]
@@ -50,6 +117,60 @@ pub struct Metadata {
     pub youtube_url: Option<String>,
 }
 
+#[derive(Serialize, Deserialize, Clone, Debug, PartialEq, JsonSchema)]
+pub struct TraitOption {
+    pub value: String,
+    pub weight: u32,
+}
+
+#[derive(Serialize, Deserialize, Clone, Debug, PartialEq, JsonSchema)]
+pub struct TraitPool {
+    pub trait_type: String,
+    pub options: Vec<TraitOption>,
+}
+
+#[derive(Serialize, Deserialize, Clone, Debug, PartialEq, JsonSchema)]
+pub struct TokenTraits {
+    pub traits: Vec<Trait>,
+    /// Sum of the inverse probabilities of every assigned trait, in decimal of 100
+    pub rarity_score: u64,
+}
+
+/// Metadata a SecureMint was given, synced in place of the config template
+#[derive(Serialize, Deserialize, Clone, Debug, PartialEq, JsonSchema)]
+pub struct MintedMetadata {
+    pub extension: Metadata,
+    pub token_uri: String,
+}
+
+#[derive(Serialize, Deserialize, Clone, Debug, PartialEq, JsonSchema, Default)]
+pub struct RevealState {
+    /// Hash of the final metadata set, committed before the first mint
+    pub provenance_hash: Option<String>,
+    /// sha256 chain over every mint's serial, owner, block height and time
+    #[serde(default)]
+    pub entropy: Binary,
+    pub offset: Option<u32>,
+    /// Number of minted tokens already updated on the nft contract
+    pub synced_count: u32,
+}
+
+impl RevealState {
+    pub fn is_revealed(&self) -> bool {
+        self.offset.is_some()
+    }
+
+    /// Serial of the final metadata assigned to a minted serial
+    pub fn revealed_serial(&self, serial: u32, collection_size: u32) -> u32 {
+        match self.offset {
+            None => serial,
+            Some(offset) => {
+                ((serial as u64 - 1 + offset as u64) % collection_size as u64) as u32 + 1
+            }
+        }
+    }
+}
+
 impl FundShare {
     pub fn get_share(&self, fund: Uint128) -> Uint128 {
         let d = fund.clone();
@@ -57,5 +178,111 @@ impl FundShare {
     }
 }
 
+impl TraitPool {
+    pub fn total_weight(&self) -> u64 {
+        self.options.iter().map(|o| o.weight as u64).sum()
+    }
+
+    /// Walks the cumulative weights, `roll` must be lower than total_weight
+    pub fn pick(&self, roll: u64) -> &TraitOption {
+        let mut cumulative: u64 = 0;
+        for option in &self.options {
+            cumulative += option.weight as u64;
+            if roll < cumulative {
+                return option;
+            }
+        }
+        &self.options[self.options.len() - 1]
+    }
+}
+
+pub const TOKEN_ID_PLACEHOLDER: &str = "{id}";
+
+impl Config {
+    pub fn token_id(&self, serial: u32) -> String {
+        match &self.token_id_prefix {
+            None => serial.to_string(),
+            Some(prefix) => format!("{}{}", prefix, serial),
+        }
+    }
+
+    /// Price of the next token at `time` (seconds) after `mint_count` tokens
+    pub fn current_price(&self, time: u64, mint_count: u32) -> Coin {
+        let amount = match &self.pricing {
+            PricingMode::Fixed {} => self.price.amount,
+            PricingMode::DutchAuction {
+                start_time,
+                start_price,
+                floor_price,
+                decay,
+                interval,
+            } => {
+                let steps = time.saturating_sub(*start_time) / interval;
+                let drop = decay.saturating_mul(Uint128::from(steps));
+                std::cmp::max(start_price.saturating_sub(drop), *floor_price)
+            }
+            PricingMode::BondingCurve {
+                base_price,
+                increment,
+            } => base_price.saturating_add(increment.saturating_mul(Uint128::from(mint_count))),
+        };
+        Coin {
+            denom: self.price.denom.clone(),
+            amount,
+        }
+    }
+
+    pub fn sale_supply(&self) -> u32 {
+        self.mint_limit.saturating_sub(self.reserved_supply)
+    }
+
+    pub fn token_uri(&self, token_id: &str) -> String {
+        match &self.base_uri {
+            None => "".to_string(),
+            Some(base_uri) => format!("{}{}.json", base_uri, token_id),
+        }
+    }
+}
+
+fn render_field(field: &Option<String>, token_id: &str) -> Option<String> {
+    field
+        .as_ref()
+        .map(|v| v.replace(TOKEN_ID_PLACEHOLDER, token_id))
+}
+
+impl Metadata {
+    /// Substitutes `{id}` with the token id in the text fields and attribute values
+    pub fn render(&self, token_id: &str) -> Metadata {
+        Metadata {
+            image: render_field(&self.image, token_id),
+            image_data: render_field(&self.image_data, token_id),
+            external_url: render_field(&self.external_url, token_id),
+            description: render_field(&self.description, token_id),
+            name: render_field(&self.name, token_id),
+            attributes: self.attributes.as_ref().map(|attributes| {
+                attributes
+                    .iter()
+                    .map(|t| Trait {
+                        display_type: t.display_type.clone(),
+                        trait_type: t.trait_type.clone(),
+                        value: t.value.replace(TOKEN_ID_PLACEHOLDER, token_id),
+                    })
+                    .collect()
+            }),
+            background_color: self.background_color.clone(),
+            animation_url: render_field(&self.animation_url, token_id),
+            youtube_url: render_field(&self.youtube_url, token_id),
+        }
+    }
+}
+
 pub const CONFIG: Item<Config> = Item::new(CONFIG_KEY);
 pub const MINTSTATUS: Item<MintStatus> = Item::new(MINT_STATUS_KEY);
+pub const TRAIT_POOLS: Map<&str, TraitPool> = Map::new(TRAIT_POOLS_KEY);
+// (trait_type, value) -> minted count
+pub const TRAIT_SUPPLY: Map<(&str, &str), u32> = Map::new(TRAIT_SUPPLY_KEY);
+pub const TOKEN_TRAITS: Map<&str, TokenTraits> = Map::new(TOKEN_TRAITS_KEY);
+pub const BEACON: Item<String> = Item::new(BEACON_KEY);
+pub const REVEAL: Item<RevealState> = Item::new(REVEAL_KEY);
+// token id -> metadata of a SecureMint token, the reveal offset leaves it in place
+pub const MINTED_METADATA: Map<&str, MintedMetadata> = Map::new(MINTED_METADATA_KEY);
@@ -1,15 +1,48 @@
-use cosmwasm_std::{coin, from_binary, Attribute, ContractResult, Response};
+use cosmwasm_std::{
+    coin, from_binary, Attribute, BankMsg, Coin, ContractResult, CosmosMsg, Response, Uint128,
+    WasmMsg,
+};
 use cosmwasm_vm::testing::{
-    execute, instantiate, mock_env, mock_info, mock_instance_options, query,
+    execute, instantiate, migrate, mock_env, mock_info, mock_instance_options, query,
+};
+use cosmwasm_vm::{Instance, Storage};
+use delorean_mint::msg::{
+    ConfigResponse, ConfigUpdate, CurrentPriceResponse, ExecuteMsg, InstantiateMsg, MigrateMsg,
+    NftExecuteMsg, OwnershipResponse, QueryMsg, RevealStatusResponse, RoleMembersResponse,
+    SimulatePayoutsResponse, StatusResponse, TokenTraitsResponse, TraitSupplyResponse,
+};
+use delorean_mint::ownership::{Role, RoleGrant};
+use delorean_mint::state::{
+    Config, FundShare, Metadata, MintStatus, PricingMode, Trait, TraitOption, TraitPool,
 };
-use cosmwasm_vm::Instance;
-use delorean_mint::msg::{ConfigResponse, ExecuteMsg, InstantiateMsg, QueryMsg};
-use delorean_mint::state::{Config, FundShare, Metadata};
 use mock_tax::mock_dependencies::mock_dependencies_with_custom_querier;
+use sha2::{Digest, Sha256};
 // const DEFAULT_QUERY_GAS_LIMIT: u64 = 300_000;
 static WASM: &[u8] =
     include_bytes!("../../../target/wasm32-unknown-unknown/release/delorean_mint.wasm");
 
+fn nft_msg(rsp: &Response, index: usize) -> NftExecuteMsg {
+    match &rsp.messages[index].msg {
+        CosmosMsg::Wasm(WasmMsg::Execute { msg, .. }) => from_binary(msg).unwrap(),
+        _ => panic!("Expected a wasm execute to the nft contract"),
+    }
+}
+
+/// Attributes of each event of type `ty`, in emission order
+fn events<'a>(rsp: &'a Response, ty: &str) -> Vec<Vec<(&'a str, &'a str)>> {
+    rsp.events
+        .iter()
+        .filter(|event| event.ty == ty)
+        .map(|event| {
+            event
+                .attributes
+                .iter()
+                .map(|attr| (attr.key.as_str(), attr.value.as_str()))
+                .collect()
+        })
+        .collect()
+}
+
 #[test]
 fn delorean_distributer_test() {
     let backend = mock_dependencies_with_custom_querier(&[]);
@@ -38,8 +71,6 @@ fn delorean_distributer_test() {
     .to_vec();
 
     let config = Config {
-        admin: admin,
-        minter: String::from("minter"),
         nft_contract: String::from("nft_contract"),
         nft_metadata: Metadata {
             name: Some(String::from("NFT 1")),
@@ -54,11 +85,25 @@ fn delorean_distributer_test() {
         },
         shares: shares.to_vec(),
         price: coin(150000000u128, "uusd"),
-        mint_limit: 4u32,
+        pricing: PricingMode::Fixed {},
+        price_tolerance: Uint128::zero(),
+        mint_limit: 5u32,
+        reserved_supply: 1u32,
+        start_time: None,
+        end_time: None,
+        token_id_prefix: None,
+        base_uri: None,
+        placeholder_metadata: None,
+        placeholder_uri: None,
     };
 
     let instatiate_msg = InstantiateMsg {
         config: config.clone(),
+        admin: None,
+        roles: vec![RoleGrant {
+            role: Role::Minter,
+            address: String::from("minter"),
+        }],
     };
 
     let (instance_options, memory_limit) = mock_instance_options();
@@ -71,8 +116,12 @@ fn delorean_distributer_test() {
 
     assert_eq!(config_rsp.config, config);
 
-    let update_config_msg = ExecuteMsg::ConfigUpdate {
-        config: config.clone(),
+    let update_config_msg = ExecuteMsg::UpdateConfig {
+        config: ConfigUpdate {
+            mint_limit: Some(5u32),
+            base_uri: Some(None),
+            ..ConfigUpdate::default()
+        },
     };
     let rsp: ContractResult<Response> = execute(
         &mut deps,
@@ -89,6 +138,53 @@ fn delorean_distributer_test() {
         update_config_msg.clone(),
     );
     assert_eq!(rsp.is_err(), false);
+    let rsp = rsp.unwrap();
+    assert_eq!(
+        events(&rsp, "config-updated"),
+        vec![vec![("mint_limit", "5"), ("base_uri", "null")]]
+    );
+    assert_eq!(
+        rsp.attributes,
+        [
+            Attribute {
+                key: String::from("action"),
+                value: String::from("update_config"),
+            },
+            Attribute {
+                key: String::from("mint_limit"),
+                value: String::from("5"),
+            },
+            Attribute {
+                key: String::from("base_uri"),
+                value: String::from("null"),
+            }
+        ],
+        "Only the provided fields should be reported"
+    );
+
+    let rsp: ContractResult<Response> = execute(
+        &mut deps,
+        mock_env(),
+        admin_info.clone(),
+        ExecuteMsg::UpdateConfig {
+            config: ConfigUpdate {
+                reserved_supply: Some(6u32),
+                ..ConfigUpdate::default()
+            },
+        },
+    );
+    assert_eq!(
+        rsp.unwrap_err(),
+        "InvalidConfig: reserved_supply",
+        "Reserved supply can't exceed the mint limit"
+    );
+
+    let rsp = query(&mut deps, mock_env(), QueryMsg::Config {}).unwrap();
+    let config_rsp: ConfigResponse = from_binary(&rsp).unwrap();
+    assert_eq!(
+        config_rsp.config, config,
+        "Unchanged fields should be kept by a partial update"
+    );
 
     let rsp: ContractResult<Response> = execute(
         &mut deps,
@@ -219,6 +315,49 @@ fn delorean_distributer_test() {
     );
     assert_eq!(rsp.is_err(), true);
 
+    let rsp: ContractResult<Response> = execute(
+        &mut deps,
+        mock_env(),
+        admin_info.clone(),
+        ExecuteMsg::SecureMint {
+            owner: "user1".to_string(),
+            token_uri: "".to_string(),
+            extension: config.nft_metadata.clone(),
+        },
+    );
+    assert_eq!(
+        rsp.unwrap_err(),
+        "ReservedLimitReached: 1",
+        "Team mints should stop at the reserved allocation"
+    );
+
+    let rsp: ContractResult<Response> = execute(
+        &mut deps,
+        mock_env(),
+        mock_info(&String::from("minter"), &[]),
+        ExecuteMsg::SecureMint {
+            owner: "user1".to_string(),
+            token_uri: "".to_string(),
+            extension: config.nft_metadata.clone(),
+        },
+    );
+    assert_eq!(
+        rsp.unwrap_err(),
+        "MintLimitReached: 4",
+        "Minter secure mints should be bound by the sale supply"
+    );
+
+    let rsp = query(&mut deps, mock_env(), QueryMsg::Status {}).unwrap();
+    let rsp: StatusResponse = from_binary(&rsp).unwrap();
+    assert_eq!(
+        rsp.mint_status,
+        MintStatus {
+            mint_count: 5,
+            reserved_count: 1,
+            paused: false,
+        }
+    );
+
     let rsp: ContractResult<Response> = execute(
         &mut deps,
         mock_env(),
@@ -233,3 +372,1178 @@ fn delorean_distributer_test() {
         "Admin should be able to move funds to treasury and distributer"
     );
 }
+
+#[test]
+fn delorean_mint_simulate_move_funds_test() {
+    let backend = mock_dependencies_with_custom_querier(&[coin(1000000000u128, "uusd")]);
+    let admin_info = mock_info(&String::from("admin"), &[]);
+    let config = Config {
+        nft_contract: String::from("nft_contract"),
+        nft_metadata: Metadata::default(),
+        shares: vec![
+            FundShare {
+                address: String::from("team_fund"),
+                note: "".to_string(),
+                share: 2000u32, //20.00
+            },
+            FundShare {
+                address: String::from("treasury"),
+                note: "".to_string(),
+                share: 8000u32, //80.00
+            },
+        ],
+        price: coin(150000000u128, "uusd"),
+        pricing: PricingMode::Fixed {},
+        price_tolerance: Uint128::zero(),
+        mint_limit: 5u32,
+        reserved_supply: 0u32,
+        start_time: None,
+        end_time: None,
+        token_id_prefix: None,
+        base_uri: None,
+        placeholder_metadata: None,
+        placeholder_uri: None,
+    };
+
+    let (instance_options, memory_limit) = mock_instance_options();
+    let mut deps = Instance::from_code(WASM, backend, instance_options, memory_limit).unwrap();
+    let rsp: ContractResult<Response> = instantiate(
+        &mut deps,
+        mock_env(),
+        admin_info.clone(),
+        InstantiateMsg {
+            config,
+            admin: None,
+            roles: vec![],
+        },
+    );
+    assert_eq!(rsp.is_err(), false);
+
+    let rsp = query(&mut deps, mock_env(), QueryMsg::SimulateMoveFunds {}).unwrap();
+    let simulated: SimulatePayoutsResponse = from_binary(&rsp).unwrap();
+    let gross: Vec<(String, u128)> = simulated
+        .payouts
+        .iter()
+        .map(|payout| (payout.recipient.clone(), payout.gross.amount.u128()))
+        .collect();
+    assert_eq!(
+        gross,
+        vec![
+            ("team_fund".to_string(), 200000000u128),
+            ("treasury".to_string(), 800000000u128)
+        ]
+    );
+    for payout in &simulated.payouts {
+        assert_eq!(payout.gross.amount, payout.tax.amount + payout.net.amount);
+    }
+
+    let rsp: ContractResult<Response> =
+        execute(&mut deps, mock_env(), admin_info, ExecuteMsg::MoveFunds {});
+    let rsp = rsp.unwrap();
+    assert_eq!(
+        events(&rsp, "share-distributed"),
+        vec![
+            vec![
+                ("wallet", "team_fund"),
+                ("note", ""),
+                ("share", "2000"),
+                ("amount", "200000000"),
+                ("denom", "uusd"),
+            ],
+            vec![
+                ("wallet", "treasury"),
+                ("note", ""),
+                ("share", "8000"),
+                ("amount", "800000000"),
+                ("denom", "uusd"),
+            ],
+        ]
+    );
+    let sent: Vec<(String, Vec<Coin>)> = rsp
+        .messages
+        .iter()
+        .map(|msg| match &msg.msg {
+            CosmosMsg::Wasm(WasmMsg::Execute {
+                contract_addr,
+                funds,
+                ..
+            }) => (contract_addr.clone(), funds.clone()),
+            _ => panic!("Expected a deposit to the share wallet"),
+        })
+        .collect();
+    let expected: Vec<(String, Vec<Coin>)> = simulated
+        .payouts
+        .into_iter()
+        .map(|payout| (payout.recipient, vec![payout.net]))
+        .collect();
+    assert_eq!(sent, expected, "MoveFunds sends what the simulation showed");
+}
+
+#[test]
+fn delorean_mint_token_template_test() {
+    let backend = mock_dependencies_with_custom_querier(&[]);
+    let admin_info = mock_info(&String::from("admin"), &[]);
+    let user1_info = mock_info(
+        &String::from("user1"),
+        &[coin(150000000u128, "uusd")].to_vec(),
+    );
+
+    let config = Config {
+        nft_contract: String::from("nft_contract"),
+        nft_metadata: Metadata {
+            name: Some(String::from("Delorean #{id}")),
+            attributes: Some(vec![Trait {
+                display_type: None,
+                trait_type: String::from("serial"),
+                value: String::from("{id}"),
+            }]),
+            ..Metadata::default()
+        },
+        shares: vec![],
+        price: coin(150000000u128, "uusd"),
+        pricing: PricingMode::Fixed {},
+        price_tolerance: Uint128::zero(),
+        mint_limit: 4u32,
+        reserved_supply: 0u32,
+        start_time: None,
+        end_time: None,
+        token_id_prefix: Some(String::from("DLR-")),
+        base_uri: Some(String::from("ipfs://hash/")),
+        placeholder_metadata: None,
+        placeholder_uri: None,
+    };
+
+    let (instance_options, memory_limit) = mock_instance_options();
+    let mut deps = Instance::from_code(WASM, backend, instance_options, memory_limit).unwrap();
+    let _res: ContractResult<Response> = instantiate(
+        &mut deps,
+        mock_env(),
+        admin_info.clone(),
+        InstantiateMsg {
+            config: config,
+            admin: None,
+            roles: vec![],
+        },
+    );
+
+    let rsp: Response = execute(&mut deps, mock_env(), user1_info, ExecuteMsg::Mint {}).unwrap();
+    let (token_id, token_uri, extension) = match nft_msg(&rsp, 0) {
+        NftExecuteMsg::Mint {
+            token_id,
+            token_uri,
+            extension,
+            ..
+        } => (token_id, token_uri, extension),
+        _ => panic!("Mint should send a mint to the nft contract"),
+    };
+    assert_eq!(token_id, "DLR-1", "Token id should carry the prefix");
+    assert_eq!(
+        token_uri, "ipfs://hash/DLR-1.json",
+        "Token uri built from base uri"
+    );
+    assert_eq!(extension.name, Some(String::from("Delorean #DLR-1")));
+    assert_eq!(extension.attributes.unwrap()[0].value, "DLR-1");
+}
+
+#[test]
+fn delorean_mint_trait_test() {
+    let backend = mock_dependencies_with_custom_querier(&[]);
+    let admin_info = mock_info(&String::from("admin"), &[]);
+    let user1_info = mock_info(
+        &String::from("user1"),
+        &[coin(150000000u128, "uusd")].to_vec(),
+    );
+
+    let config = Config {
+        nft_contract: String::from("nft_contract"),
+        nft_metadata: Metadata::default(),
+        shares: vec![],
+        price: coin(150000000u128, "uusd"),
+        pricing: PricingMode::Fixed {},
+        price_tolerance: Uint128::zero(),
+        mint_limit: 4u32,
+        reserved_supply: 1u32,
+        start_time: None,
+        end_time: None,
+        token_id_prefix: None,
+        base_uri: None,
+        placeholder_metadata: None,
+        placeholder_uri: None,
+    };
+
+    let (instance_options, memory_limit) = mock_instance_options();
+    let mut deps = Instance::from_code(WASM, backend, instance_options, memory_limit).unwrap();
+    let _res: ContractResult<Response> = instantiate(
+        &mut deps,
+        mock_env(),
+        admin_info.clone(),
+        InstantiateMsg {
+            config: config.clone(),
+            admin: None,
+            roles: vec![],
+        },
+    );
+
+    let background = TraitPool {
+        trait_type: String::from("background"),
+        options: vec![
+            TraitOption {
+                value: String::from("blue"),
+                weight: 60,
+            },
+            TraitOption {
+                value: String::from("red"),
+                weight: 30,
+            },
+            TraitOption {
+                value: String::from("gold"),
+                weight: 10,
+            },
+        ],
+    };
+    let rsp: ContractResult<Response> = execute(
+        &mut deps,
+        mock_env(),
+        user1_info.clone(),
+        ExecuteMsg::SetTraitPool {
+            pool: background.clone(),
+        },
+    );
+    assert_eq!(
+        rsp.unwrap_err(),
+        "Unauthorized: user1 is not config_manager"
+    );
+
+    let rsp: ContractResult<Response> = execute(
+        &mut deps,
+        mock_env(),
+        admin_info.clone(),
+        ExecuteMsg::SetTraitPool {
+            pool: TraitPool {
+                trait_type: String::from("empty"),
+                options: vec![],
+            },
+        },
+    );
+    assert_eq!(rsp.unwrap_err(), "InvalidTraitPool: empty");
+
+    let rsp: ContractResult<Response> = execute(
+        &mut deps,
+        mock_env(),
+        admin_info.clone(),
+        ExecuteMsg::SetTraitPool { pool: background },
+    );
+    assert_eq!(rsp.is_err(), false, "Admin should register a trait pool");
+    assert_eq!(
+        events(&rsp.unwrap(), "trait-pool-set")[0][0],
+        ("trait_type", "background")
+    );
+
+    for serial in 1..=3 {
+        let rsp: Response = execute(
+            &mut deps,
+            mock_env(),
+            user1_info.clone(),
+            ExecuteMsg::Mint {},
+        )
+        .unwrap();
+        let token_id = serial.to_string();
+        assert_eq!(
+            events(&rsp, "traits-assigned")[0][0],
+            ("token_id", token_id.as_str())
+        );
+    }
+
+    let rsp: Response = execute(
+        &mut deps,
+        mock_env(),
+        admin_info.clone(),
+        ExecuteMsg::SecureMint {
+            owner: "admin".to_string(),
+            token_uri: "".to_string(),
+            extension: config.nft_metadata.clone(),
+        },
+    )
+    .unwrap();
+    assert_eq!(
+        events(&rsp, "traits-assigned")[0][0],
+        ("token_id", "4"),
+        "Team mints roll the trait pools too"
+    );
+    match nft_msg(&rsp, 0) {
+        NftExecuteMsg::Mint { extension, .. } => {
+            let attributes = extension.attributes.unwrap();
+            assert_eq!(attributes.len(), 1);
+            assert_eq!(attributes[0].trait_type, "background");
+        }
+        _ => panic!("SecureMint should send a mint to the nft contract"),
+    }
+
+    let rsp = query(&mut deps, mock_env(), QueryMsg::TraitSupply {}).unwrap();
+    let rsp: TraitSupplyResponse = from_binary(&rsp).unwrap();
+    assert_eq!(rsp.supply.len(), 3);
+    assert_eq!(
+        rsp.supply.iter().map(|s| s.count).sum::<u32>(),
+        4,
+        "Every minted token should get one background"
+    );
+
+    let rsp = query(
+        &mut deps,
+        mock_env(),
+        QueryMsg::TokenTraits {
+            token_id: String::from("1"),
+        },
+    )
+    .unwrap();
+    let rsp: TokenTraitsResponse = from_binary(&rsp).unwrap();
+    let token_traits = rsp.token_traits.unwrap();
+    assert_eq!(token_traits.traits.len(), 1);
+    assert_eq!(token_traits.traits[0].trait_type, "background");
+    assert_eq!(token_traits.rarity_score > 0, true);
+}
+
+#[test]
+fn delorean_mint_reveal_test() {
+    let backend = mock_dependencies_with_custom_querier(&[]);
+    let admin_info = mock_info(&String::from("admin"), &[]);
+    let user1_info = mock_info(
+        &String::from("user1"),
+        &[coin(150000000u128, "uusd")].to_vec(),
+    );
+
+    let config = Config {
+        nft_contract: String::from("nft_contract"),
+        nft_metadata: Metadata {
+            name: Some(String::from("Delorean #{id}")),
+            ..Metadata::default()
+        },
+        shares: vec![],
+        price: coin(150000000u128, "uusd"),
+        pricing: PricingMode::Fixed {},
+        price_tolerance: Uint128::zero(),
+        mint_limit: 5u32,
+        reserved_supply: 1u32,
+        start_time: None,
+        end_time: None,
+        token_id_prefix: None,
+        base_uri: Some(String::from("ipfs://final/")),
+        placeholder_metadata: Some(Metadata {
+            name: Some(String::from("Mystery #{id}")),
+            ..Metadata::default()
+        }),
+        placeholder_uri: Some(String::from("ipfs://hidden.json")),
+    };
+
+    let (instance_options, memory_limit) = mock_instance_options();
+    let mut deps = Instance::from_code(WASM, backend, instance_options, memory_limit).unwrap();
+    let _res: ContractResult<Response> = instantiate(
+        &mut deps,
+        mock_env(),
+        admin_info.clone(),
+        InstantiateMsg {
+            config: config,
+            admin: None,
+            roles: vec![],
+        },
+    );
+
+    let rsp: ContractResult<Response> = execute(
+        &mut deps,
+        mock_env(),
+        admin_info.clone(),
+        ExecuteMsg::Reveal {},
+    );
+    assert_eq!(
+        rsp.unwrap_err(),
+        "ProvenanceNotCommitted",
+        "Reveal needs a provenance hash first"
+    );
+
+    let rsp: ContractResult<Response> = execute(
+        &mut deps,
+        mock_env(),
+        user1_info.clone(),
+        ExecuteMsg::CommitProvenance {
+            provenance_hash: String::from("abcd"),
+        },
+    );
+    assert_eq!(
+        rsp.unwrap_err(),
+        "Unauthorized: user1 is not config_manager"
+    );
+
+    let rsp: ContractResult<Response> = execute(
+        &mut deps,
+        mock_env(),
+        admin_info.clone(),
+        ExecuteMsg::CommitProvenance {
+            provenance_hash: String::from("abcd"),
+        },
+    );
+    assert_eq!(rsp.is_err(), false, "Admin should commit provenance");
+    assert_eq!(
+        events(&rsp.unwrap(), "provenance-committed"),
+        vec![vec![("provenance_hash", "abcd")]]
+    );
+
+    let rsp: ContractResult<Response> = execute(
+        &mut deps,
+        mock_env(),
+        admin_info.clone(),
+        ExecuteMsg::SetTraitPool {
+            pool: TraitPool {
+                trait_type: String::from("background"),
+                options: vec![TraitOption {
+                    value: String::from("blue"),
+                    weight: 1,
+                }],
+            },
+        },
+    );
+    assert_eq!(rsp.is_err(), false);
+
+    for _ in 0..3 {
+        let rsp: Response = execute(
+            &mut deps,
+            mock_env(),
+            user1_info.clone(),
+            ExecuteMsg::Mint {},
+        )
+        .unwrap();
+        match nft_msg(&rsp, 0) {
+            NftExecuteMsg::Mint {
+                token_uri,
+                extension,
+                ..
+            } => {
+                assert_eq!(
+                    token_uri, "ipfs://hidden.json",
+                    "Placeholder uri before reveal"
+                );
+                assert_eq!(extension.name.unwrap().starts_with("Mystery #"), true);
+                assert_eq!(extension.attributes, None, "Traits stay hidden");
+            }
+            _ => panic!("Mint should send a mint to the nft contract"),
+        }
+        assert_eq!(events(&rsp, "traits-assigned").len(), 0);
+    }
+
+    let rsp: Response = execute(
+        &mut deps,
+        mock_env(),
+        admin_info.clone(),
+        ExecuteMsg::SecureMint {
+            owner: String::from("admin"),
+            token_uri: String::from("ipfs://founder.json"),
+            extension: Metadata {
+                name: Some(String::from("Founder")),
+                ..Metadata::default()
+            },
+        },
+    )
+    .unwrap();
+    match nft_msg(&rsp, 0) {
+        NftExecuteMsg::Mint {
+            token_uri,
+            extension,
+            ..
+        } => {
+            assert_eq!(token_uri, "ipfs://hidden.json");
+            assert_eq!(extension.name, Some(String::from("Mystery #4")));
+        }
+        _ => panic!("SecureMint should send a mint to the nft contract"),
+    }
+    assert_eq!(events(&rsp, "traits-assigned").len(), 0);
+    let rsp = query(
+        &mut deps,
+        mock_env(),
+        QueryMsg::TokenTraits {
+            token_id: String::from("1"),
+        },
+    );
+    assert_eq!(
+        rsp.unwrap_err(),
+        "Generic error: TraitsHidden: traits stay private until the reveal"
+    );
+
+    let rsp: ContractResult<Response> = execute(
+        &mut deps,
+        mock_env(),
+        admin_info.clone(),
+        ExecuteMsg::CommitProvenance {
+            provenance_hash: String::from("ef01"),
+        },
+    );
+    assert_eq!(
+        rsp.unwrap_err(),
+        "ProvenanceLocked: minting has started",
+        "Provenance can't change once tokens are out"
+    );
+
+    let rsp: ContractResult<Response> = execute(
+        &mut deps,
+        mock_env(),
+        admin_info.clone(),
+        ExecuteMsg::UpdateConfig {
+            config: ConfigUpdate {
+                base_uri: Some(Some(String::from("ipfs://other/"))),
+                ..ConfigUpdate::default()
+            },
+        },
+    );
+    assert_eq!(
+        rsp.unwrap_err(),
+        "ConfigLocked: base_uri is fixed by the provenance hash"
+    );
+
+    let rsp: ContractResult<Response> = execute(
+        &mut deps,
+        mock_env(),
+        admin_info.clone(),
+        ExecuteMsg::Reveal {},
+    );
+    assert_eq!(
+        rsp.unwrap_err(),
+        "SaleNotOver",
+        "One token is still for sale"
+    );
+
+    let rsp: ContractResult<Response> = execute(
+        &mut deps,
+        mock_env(),
+        admin_info.clone(),
+        ExecuteMsg::UpdateConfig {
+            config: ConfigUpdate {
+                end_time: Some(Some(mock_env().block.time.nanos() / 1_000_000_000)),
+                ..ConfigUpdate::default()
+            },
+        },
+    );
+    assert_eq!(rsp.is_err(), false, "Closing the sale is still allowed");
+
+    let rsp: ContractResult<Response> = execute(
+        &mut deps,
+        mock_env(),
+        admin_info.clone(),
+        ExecuteMsg::Reveal {},
+    );
+    assert_eq!(rsp.is_err(), false, "Reveal should work after the sale");
+    let revealed = rsp.unwrap();
+
+    let rsp: ContractResult<Response> = execute(
+        &mut deps,
+        mock_env(),
+        admin_info.clone(),
+        ExecuteMsg::Reveal {},
+    );
+    assert_eq!(rsp.unwrap_err(), "AlreadyRevealed");
+
+    let rsp = query(&mut deps, mock_env(), QueryMsg::RevealStatus {}).unwrap();
+    let rsp: RevealStatusResponse = from_binary(&rsp).unwrap();
+    assert_eq!(rsp.reveal.provenance_hash, Some(String::from("abcd")));
+    let offset = rsp.reveal.offset.unwrap();
+    let digest = Sha256::new()
+        .chain("abcd".as_bytes())
+        .chain(rsp.reveal.entropy.as_slice())
+        .finalize();
+    let mut seed = [0u8; 8];
+    seed.copy_from_slice(&digest[0..8]);
+    assert_eq!(
+        offset as u64,
+        u64::from_be_bytes(seed) % 5,
+        "Collectors can recompute the offset from the reveal status"
+    );
+    let offset_attr = offset.to_string();
+    assert_eq!(
+        events(&revealed, "collection-revealed"),
+        vec![vec![
+            ("provenance_hash", "abcd"),
+            ("offset", offset_attr.as_str())
+        ]]
+    );
+
+    let rsp: Response = execute(
+        &mut deps,
+        mock_env(),
+        admin_info.clone(),
+        ExecuteMsg::SyncRevealed { limit: Some(2) },
+    )
+    .unwrap();
+    assert_eq!(rsp.messages.len(), 2, "Sync should respect the limit");
+    assert_eq!(
+        events(&rsp, "metadata-synced"),
+        vec![vec![("from_serial", "1"), ("to_serial", "2")]]
+    );
+    assert_eq!(
+        events(&rsp, "traits-assigned").len(),
+        2,
+        "Held back traits go out with the sync"
+    );
+    match nft_msg(&rsp, 0) {
+        NftExecuteMsg::UpdateMetadata {
+            token_id,
+            token_uri,
+            ..
+        } => {
+            let revealed = (offset % 5) + 1;
+            assert_eq!(token_id, "1");
+            assert_eq!(token_uri, format!("ipfs://final/{}.json", revealed));
+        }
+        _ => panic!("Sync should update metadata on the nft contract"),
+    }
+
+    let rsp: Response = execute(
+        &mut deps,
+        mock_env(),
+        admin_info.clone(),
+        ExecuteMsg::SyncRevealed { limit: None },
+    )
+    .unwrap();
+    assert_eq!(
+        rsp.messages.len(),
+        2,
+        "Only the remaining minted tokens are synced"
+    );
+    match nft_msg(&rsp, 1) {
+        NftExecuteMsg::UpdateMetadata {
+            token_id,
+            token_uri,
+            extension,
+        } => {
+            assert_eq!(token_id, "4");
+            assert_eq!(
+                (token_uri, extension.name),
+                (
+                    String::from("ipfs://founder.json"),
+                    Some(String::from("Founder"))
+                ),
+                "SecureMint tokens keep the metadata they were minted with"
+            );
+            assert_eq!(extension.attributes.unwrap().len(), 1);
+        }
+        _ => panic!("Sync should update metadata on the nft contract"),
+    }
+
+    let rsp = query(
+        &mut deps,
+        mock_env(),
+        QueryMsg::TokenTraits {
+            token_id: String::from("4"),
+        },
+    )
+    .unwrap();
+    let rsp: TokenTraitsResponse = from_binary(&rsp).unwrap();
+    assert_eq!(rsp.token_traits.unwrap().traits[0].value, "blue");
+}
+
+#[test]
+fn delorean_mint_pause_window_test() {
+    let backend = mock_dependencies_with_custom_querier(&[]);
+    let admin_info = mock_info(&String::from("admin"), &[]);
+    let user1_info = mock_info(
+        &String::from("user1"),
+        &[coin(150000000u128, "uusd")].to_vec(),
+    );
+    let now = mock_env().block.time.nanos() / 1_000_000_000;
+
+    let config = Config {
+        nft_contract: String::from("nft_contract"),
+        nft_metadata: Metadata::default(),
+        shares: vec![],
+        price: coin(150000000u128, "uusd"),
+        pricing: PricingMode::Fixed {},
+        price_tolerance: Uint128::zero(),
+        mint_limit: 4u32,
+        reserved_supply: 0u32,
+        start_time: Some(now + 100),
+        end_time: Some(now + 200),
+        token_id_prefix: None,
+        base_uri: None,
+        placeholder_metadata: None,
+        placeholder_uri: None,
+    };
+
+    let (instance_options, memory_limit) = mock_instance_options();
+    let mut deps = Instance::from_code(WASM, backend, instance_options, memory_limit).unwrap();
+    let _res: ContractResult<Response> = instantiate(
+        &mut deps,
+        mock_env(),
+        admin_info.clone(),
+        InstantiateMsg {
+            config: config.clone(),
+            admin: None,
+            roles: vec![RoleGrant {
+                role: Role::Minter,
+                address: String::from("minter"),
+            }],
+        },
+    );
+
+    let rsp: ContractResult<Response> = execute(
+        &mut deps,
+        mock_env(),
+        user1_info.clone(),
+        ExecuteMsg::Mint {},
+    );
+    assert_eq!(
+        rsp.unwrap_err(),
+        format!("SaleNotStarted: starts at {}", now + 100)
+    );
+
+    let mut env = mock_env();
+    env.block.time = env.block.time.plus_seconds(200);
+    let rsp: ContractResult<Response> =
+        execute(&mut deps, env, user1_info.clone(), ExecuteMsg::Mint {});
+    assert_eq!(
+        rsp.unwrap_err(),
+        format!("SaleEnded: ended at {}", now + 200)
+    );
+
+    let rsp: ContractResult<Response> = execute(
+        &mut deps,
+        mock_env(),
+        admin_info.clone(),
+        ExecuteMsg::UpdateConfig {
+            config: ConfigUpdate {
+                start_time: Some(None),
+                end_time: Some(None),
+                ..ConfigUpdate::default()
+            },
+        },
+    );
+    assert_eq!(rsp.is_err(), false);
+
+    let rsp: ContractResult<Response> = execute(
+        &mut deps,
+        mock_env(),
+        user1_info.clone(),
+        ExecuteMsg::Pause {},
+    );
+    assert_eq!(
+        rsp.unwrap_err(),
+        "Unauthorized: user1 is not config_manager",
+        "Only admin can pause"
+    );
+
+    let rsp: ContractResult<Response> = execute(
+        &mut deps,
+        mock_env(),
+        admin_info.clone(),
+        ExecuteMsg::Pause {},
+    );
+    assert_eq!(
+        events(&rsp.unwrap(), "mint-paused"),
+        vec![vec![("paused", "true")]]
+    );
+
+    let rsp: ContractResult<Response> = execute(
+        &mut deps,
+        mock_env(),
+        user1_info.clone(),
+        ExecuteMsg::Mint {},
+    );
+    assert_eq!(rsp.unwrap_err(), "Paused");
+
+    let rsp: ContractResult<Response> = execute(
+        &mut deps,
+        mock_env(),
+        mock_info(&String::from("minter"), &[]),
+        ExecuteMsg::SecureMint {
+            owner: "user1".to_string(),
+            token_uri: "".to_string(),
+            extension: Metadata::default(),
+        },
+    );
+    assert_eq!(
+        rsp.unwrap_err(),
+        "Paused",
+        "Pause should also stop secure mint"
+    );
+
+    let rsp: ContractResult<Response> = execute(
+        &mut deps,
+        mock_env(),
+        admin_info.clone(),
+        ExecuteMsg::Unpause {},
+    );
+    assert_eq!(rsp.is_err(), false);
+
+    let rsp: ContractResult<Response> = execute(
+        &mut deps,
+        mock_env(),
+        user1_info.clone(),
+        ExecuteMsg::Mint {},
+    );
+    assert_eq!(rsp.is_err(), false, "Mint should work once unpaused");
+}
+
+#[test]
+fn delorean_mint_pricing_test() {
+    let backend = mock_dependencies_with_custom_querier(&[]);
+    let admin_info = mock_info(&String::from("admin"), &[]);
+    let now = mock_env().block.time.nanos() / 1_000_000_000;
+
+    let config = Config {
+        nft_contract: String::from("nft_contract"),
+        nft_metadata: Metadata::default(),
+        shares: vec![],
+        price: coin(100u128, "uusd"),
+        pricing: PricingMode::DutchAuction {
+            start_time: now,
+            start_price: Uint128::from(1000u128),
+            floor_price: Uint128::from(400u128),
+            decay: Uint128::from(100u128),
+            interval: 60,
+        },
+        price_tolerance: Uint128::from(100u128),
+        mint_limit: 10u32,
+        reserved_supply: 0u32,
+        start_time: None,
+        end_time: None,
+        token_id_prefix: None,
+        base_uri: None,
+        placeholder_metadata: None,
+        placeholder_uri: None,
+    };
+
+    let (instance_options, memory_limit) = mock_instance_options();
+    let mut deps = Instance::from_code(WASM, backend, instance_options, memory_limit).unwrap();
+    let _res: ContractResult<Response> = instantiate(
+        &mut deps,
+        mock_env(),
+        admin_info.clone(),
+        InstantiateMsg {
+            config: config.clone(),
+            admin: None,
+            roles: vec![RoleGrant {
+                role: Role::Minter,
+                address: String::from("minter"),
+            }],
+        },
+    );
+
+    let mut env = mock_env();
+    env.block.time = env.block.time.plus_seconds(150);
+    let rsp = query(&mut deps, env.clone(), QueryMsg::CurrentPrice {}).unwrap();
+    let rsp: CurrentPriceResponse = from_binary(&rsp).unwrap();
+    assert_eq!(
+        rsp.price,
+        coin(800u128, "uusd"),
+        "Price should decay twice after 150 seconds"
+    );
+
+    let rsp: ContractResult<Response> = execute(
+        &mut deps,
+        env.clone(),
+        mock_info(&String::from("user1"), &[coin(700u128, "uusd")]),
+        ExecuteMsg::Mint {},
+    );
+    assert_eq!(
+        rsp.unwrap_err(),
+        "InsufficientFunds: required 800, received 700"
+    );
+
+    let rsp: ContractResult<Response> = execute(
+        &mut deps,
+        env.clone(),
+        mock_info(&String::from("user1"), &[coin(1000u128, "uusd")]),
+        ExecuteMsg::Mint {},
+    );
+    assert_eq!(
+        rsp.unwrap_err(),
+        "Overpaid: at most 900, received 1000",
+        "Only the stated tolerance may be sent above the price"
+    );
+
+    let rsp: Response = execute(
+        &mut deps,
+        env.clone(),
+        mock_info(&String::from("user1"), &[coin(850u128, "uusd")]),
+        ExecuteMsg::Mint {},
+    )
+    .unwrap();
+    assert_eq!(rsp.messages.len(), 1, "Nothing is refunded");
+    let event = rsp
+        .events
+        .iter()
+        .find(|event| event.ty == "delorean-mint")
+        .unwrap();
+    assert_eq!(
+        event.attributes[5..],
+        [
+            Attribute::new("amount", "850"),
+            Attribute::new("denom", "uusd"),
+            Attribute::new("price", "800"),
+        ],
+        "The whole amount within the tolerance is the payment"
+    );
+
+    env.block.time = env.block.time.plus_seconds(100_000);
+    let rsp = query(&mut deps, env, QueryMsg::CurrentPrice {}).unwrap();
+    let rsp: CurrentPriceResponse = from_binary(&rsp).unwrap();
+    assert_eq!(rsp.price, coin(400u128, "uusd"), "Price stops at the floor");
+
+    let rsp: ContractResult<Response> = execute(
+        &mut deps,
+        mock_env(),
+        admin_info.clone(),
+        ExecuteMsg::UpdateConfig {
+            config: ConfigUpdate {
+                pricing: Some(PricingMode::BondingCurve {
+                    base_price: Uint128::from(100u128),
+                    increment: Uint128::from(10u128),
+                }),
+                ..ConfigUpdate::default()
+            },
+        },
+    );
+    assert_eq!(rsp.is_err(), false);
+
+    let rsp = query(&mut deps, mock_env(), QueryMsg::CurrentPrice {}).unwrap();
+    let rsp: CurrentPriceResponse = from_binary(&rsp).unwrap();
+    assert_eq!(
+        rsp.price,
+        coin(110u128, "uusd"),
+        "Bonding curve price grows with the mint count"
+    );
+}
+
+#[test]
+fn delorean_mint_ownership_test() {
+    let backend = mock_dependencies_with_custom_querier(&[]);
+    let admin_info = mock_info(&String::from("admin"), &[]);
+    let new_admin_info = mock_info(&String::from("new_admin"), &[]);
+    let operator_info = mock_info(&String::from("operator"), &[]);
+
+    let config = Config {
+        nft_contract: String::from("nft_contract"),
+        nft_metadata: Metadata::default(),
+        shares: vec![],
+        price: coin(150000000u128, "uusd"),
+        pricing: PricingMode::Fixed {},
+        price_tolerance: Uint128::zero(),
+        mint_limit: 4u32,
+        reserved_supply: 0u32,
+        start_time: None,
+        end_time: None,
+        token_id_prefix: None,
+        base_uri: None,
+        placeholder_metadata: None,
+        placeholder_uri: None,
+    };
+
+    let (instance_options, memory_limit) = mock_instance_options();
+    let mut deps = Instance::from_code(WASM, backend, instance_options, memory_limit).unwrap();
+    let _res: ContractResult<Response> = instantiate(
+        &mut deps,
+        mock_env(),
+        admin_info.clone(),
+        InstantiateMsg {
+            config: config.clone(),
+            admin: None,
+            roles: vec![],
+        },
+    );
+
+    let rsp: ContractResult<Response> = execute(
+        &mut deps,
+        mock_env(),
+        admin_info.clone(),
+        ExecuteMsg::ProposeNewAdmin {
+            new_admin: String::from("new_admin"),
+        },
+    );
+    assert_eq!(rsp.is_err(), false, "Admin should propose a new admin");
+
+    let rsp: ContractResult<Response> = execute(
+        &mut deps,
+        mock_env(),
+        operator_info.clone(),
+        ExecuteMsg::AcceptAdmin {},
+    );
+    assert_eq!(
+        rsp.unwrap_err(),
+        "Unauthorized: operator is not pending_admin",
+        "Only the pending admin can accept"
+    );
+
+    let rsp = query(&mut deps, mock_env(), QueryMsg::Ownership {}).unwrap();
+    let rsp: OwnershipResponse = from_binary(&rsp).unwrap();
+    assert_eq!(
+        rsp,
+        OwnershipResponse {
+            admin: Some(String::from("admin")),
+            pending_admin: Some(String::from("new_admin")),
+        },
+        "Admin stays until the proposal is accepted"
+    );
+
+    let rsp: ContractResult<Response> = execute(
+        &mut deps,
+        mock_env(),
+        new_admin_info.clone(),
+        ExecuteMsg::AcceptAdmin {},
+    );
+    assert_eq!(rsp.is_err(), false, "Pending admin should accept");
+
+    let rsp: ContractResult<Response> = execute(
+        &mut deps,
+        mock_env(),
+        admin_info.clone(),
+        ExecuteMsg::UpdateConfig {
+            config: ConfigUpdate::default(),
+        },
+    );
+    assert_eq!(
+        rsp.unwrap_err(),
+        "Unauthorized: admin is not config_manager",
+        "Old admin lost access"
+    );
+
+    let rsp: ContractResult<Response> = execute(
+        &mut deps,
+        mock_env(),
+        new_admin_info.clone(),
+        ExecuteMsg::GrantRole {
+            role: Role::ConfigManager,
+            address: String::from("operator"),
+        },
+    );
+    assert_eq!(rsp.is_err(), false, "Admin should grant roles");
+
+    let rsp = query(
+        &mut deps,
+        mock_env(),
+        QueryMsg::RoleMembers {
+            role: Role::ConfigManager,
+        },
+    )
+    .unwrap();
+    let rsp: RoleMembersResponse = from_binary(&rsp).unwrap();
+    assert_eq!(rsp.members, vec![String::from("operator")]);
+
+    let rsp: ContractResult<Response> = execute(
+        &mut deps,
+        mock_env(),
+        operator_info.clone(),
+        ExecuteMsg::UpdateConfig {
+            config: ConfigUpdate::default(),
+        },
+    );
+    assert_eq!(rsp.is_err(), false, "Config manager should update config");
+
+    let rsp: ContractResult<Response> = execute(
+        &mut deps,
+        mock_env(),
+        operator_info.clone(),
+        ExecuteMsg::MoveFunds {},
+    );
+    assert_eq!(
+        rsp.unwrap_err(),
+        "Unauthorized: operator is not treasury_operator",
+        "Config manager is not a treasury operator"
+    );
+
+    let rsp: ContractResult<Response> = execute(
+        &mut deps,
+        mock_env(),
+        new_admin_info.clone(),
+        ExecuteMsg::RenounceAdmin {},
+    );
+    assert_eq!(rsp.is_err(), false, "Admin should renounce");
+
+    let rsp: ContractResult<Response> = execute(
+        &mut deps,
+        mock_env(),
+        new_admin_info.clone(),
+        ExecuteMsg::GrantRole {
+            role: Role::Minter,
+            address: String::from("new_admin"),
+        },
+    );
+    assert_eq!(
+        rsp.unwrap_err(),
+        "Unauthorized: new_admin is not admin",
+        "No admin after renounce"
+    );
+
+    let rsp: ContractResult<Response> = execute(
+        &mut deps,
+        mock_env(),
+        operator_info,
+        ExecuteMsg::UpdateConfig {
+            config: ConfigUpdate::default(),
+        },
+    );
+    assert_eq!(rsp.is_err(), false, "Roles survive the renounce");
+}
+
+#[test]
+fn delorean_mint_migrate_test() {
+    let mut backend = mock_dependencies_with_custom_querier(&[]);
+    let minter_info = mock_info(&String::from("minter"), &[]);
+
+    // store as written by the unversioned contract
+    let legacy_config = br#"{"admin":"admin","minter":"minter","nft_contract":"nft","nft_metadata":{"name":"Delorean"},"shares":[{"address":"team","note":"team","share":10000}],"price":{"denom":"uusd","amount":"1000000"},"mint_limit":10}"#;
+    backend.storage.set(b"config", legacy_config).0.unwrap();
+    backend
+        .storage
+        .set(b"mint_status", br#"{"mint_count":3}"#)
+        .0
+        .unwrap();
+
+    let (instance_options, memory_limit) = mock_instance_options();
+    let mut deps = Instance::from_code(WASM, backend, instance_options, memory_limit).unwrap();
+
+    let rsp: ContractResult<Response> = migrate(&mut deps, mock_env(), MigrateMsg {});
+    assert_eq!(rsp.is_err(), false, "Legacy store should migrate");
+
+    let rsp = query(&mut deps, mock_env(), QueryMsg::Config {}).unwrap();
+    let rsp: ConfigResponse = from_binary(&rsp).unwrap();
+    assert_eq!(rsp.config.nft_contract, "nft");
+    assert_eq!(rsp.config.mint_limit, 10);
+    assert_eq!(rsp.config.pricing, PricingMode::Fixed {});
+    assert_eq!(rsp.config.reserved_supply, 0);
+
+    let rsp = query(&mut deps, mock_env(), QueryMsg::Ownership {}).unwrap();
+    let rsp: OwnershipResponse = from_binary(&rsp).unwrap();
+    assert_eq!(rsp.admin, Some(String::from("admin")));
+
+    let rsp = query(
+        &mut deps,
+        mock_env(),
+        QueryMsg::RoleMembers { role: Role::Minter },
+    )
+    .unwrap();
+    let rsp: RoleMembersResponse = from_binary(&rsp).unwrap();
+    assert_eq!(
+        rsp.members,
+        vec![String::from("minter")],
+        "Legacy minter keeps the minter role"
+    );
+
+    let rsp: ContractResult<Response> = execute(
+        &mut deps,
+        mock_env(),
+        minter_info,
+        ExecuteMsg::SecureMint {
+            owner: String::from("user1"),
+            token_uri: String::from(""),
+            extension: Metadata::default(),
+        },
+    );
+    assert_eq!(rsp.is_err(), false, "Minter should still secure mint");
+
+    let rsp = query(&mut deps, mock_env(), QueryMsg::Status {}).unwrap();
+    let rsp: StatusResponse = from_binary(&rsp).unwrap();
+    assert_eq!(
+        rsp.mint_status,
+        MintStatus {
+            mint_count: 4,
+            reserved_count: 0,
+            paused: false,
+        },
+        "Mint count carries over"
+    );
+}
@@ -1,26 +1,47 @@
 use std::vec;
 
-use crate::errors::{ContractError, Unauthorized};
+use crate::errors::ContractError;
+use crate::migrations::{migrate_store, set_version, CONTRACT_NAME, CONTRACT_VERSION};
 use crate::msg::{
-    AnchorExecuteMsg, ConfigResponse, ExecuteMsg, InstantiateMsg, MigrateMsg, QueryMsg,
+    AnchorExecuteMsg, ConfigResponse, ConfigUpdate, ExecuteMsg, InstantiateMsg, MigrateMsg, Payout,
+    QueryMsg, SimulatePayoutsResponse,
+};
+use crate::ownership::{
+    assert_admin, assert_role, get_ownership, get_role_members, init_ownership, try_accept_admin,
+    try_grant_role, try_propose_new_admin, try_renounce_admin, try_revoke_role, Role, OWNERSHIP,
 };
 use crate::state::{Config, CONFIG};
 use cosmwasm_std::{
-    entry_point, to_binary, Addr, BankMsg, Coin, Deps, DepsMut, Env, MessageInfo, QueryResponse,
-    Response, StdResult, SubMsg, Uint128, WasmMsg,
+    attr, entry_point, to_binary, Addr, Attribute, BankMsg, Coin, Deps, DepsMut, Env, Event,
+    MessageInfo, QueryResponse, Response, StdResult, SubMsg, Uint128, WasmMsg,
 };
 use cw20::Cw20ExecuteMsg;
+use delorean_common::deposit::assert_depositor;
+use delorean_common::errors::invalid_config;
+use delorean_common::events::{instantiate_event, json_attribute};
+use delorean_common::payout::payout;
 use moneymarket::market::Cw20HookMsg;
 use moneymarket::querier::deduct_tax;
 
 pub fn instantiate(
-    deps: DepsMut,
+    mut deps: DepsMut,
     _env: Env,
-    _info: MessageInfo,
+    info: MessageInfo,
     msg: InstantiateMsg,
 ) -> Result<Response, ContractError> {
+    let admin = msg.admin.clone().unwrap_or_else(|| info.sender.to_string());
+    let event = instantiate_event(
+        CONTRACT_NAME,
+        CONTRACT_VERSION,
+        &admin,
+        &msg.roles,
+        &msg.config,
+    )?;
+    init_ownership(deps.branch(), admin, msg.roles)?;
+    validate_config(deps.as_ref(), &msg.config)?;
     CONFIG.save(deps.storage, &msg.config)?;
-    Ok(Response::default())
+    set_version(deps.storage)?;
+    Ok(Response::default().add_event(event))
 }
 
 pub fn execute(
@@ -30,7 +51,23 @@ pub fn execute(
     msg: ExecuteMsg,
 ) -> Result<Response, ContractError> {
     match msg {
-        ExecuteMsg::ConfigUpdate { config } => try_config_update(deps, env, info, config),
+        ExecuteMsg::UpdateConfig { config } => try_update_config(deps, env, info, config),
+
+        ExecuteMsg::ProposeNewAdmin { new_admin } => {
+            Ok(try_propose_new_admin(deps, env, info, new_admin)?)
+        }
+
+        ExecuteMsg::AcceptAdmin {} => Ok(try_accept_admin(deps, env, info)?),
+
+        ExecuteMsg::RenounceAdmin {} => Ok(try_renounce_admin(deps, env, info)?),
+
+        ExecuteMsg::GrantRole { role, address } => {
+            Ok(try_grant_role(deps, env, info, role, address)?)
+        }
+
+        ExecuteMsg::RevokeRole { role, address } => {
+            Ok(try_revoke_role(deps, env, info, role, address)?)
+        }
 
         ExecuteMsg::Deposit {} => try_deposit(deps, env, info),
         ExecuteMsg::AnchorWithdraw { amount } => try_anchor_withdraw(deps, env, info, amount),
@@ -45,19 +82,50 @@ fn transfer_funds(to: &Addr, cns: Vec<Coin>) -> BankMsg {
     };
 }
 
-pub fn try_config_update(
+pub fn try_update_config(
     deps: DepsMut,
     _env: Env,
     info: MessageInfo,
-    new_config: Config,
+    update: ConfigUpdate,
 ) -> Result<Response, ContractError> {
-    let mut _config = CONFIG.load(deps.storage)?;
-    if info.sender != _config.admin {
-        return Err(Unauthorized {}.build());
+    assert_role(deps.storage, &info.sender, Role::ConfigManager)?;
+    let mut config = CONFIG.load(deps.storage)?;
+    let mut changes: Vec<Attribute> = vec![];
+
+    if let Some(aust_contract) = update.aust_contract {
+        changes.push(attr("aust_contract", &aust_contract));
+        config.aust_contract = aust_contract;
+    }
+    if let Some(anchor_smart_contract) = update.anchor_smart_contract {
+        changes.push(attr("anchor_smart_contract", &anchor_smart_contract));
+        config.anchor_smart_contract = anchor_smart_contract;
+    }
+    if let Some(deposit_policy) = update.deposit_policy {
+        changes.push(attr("deposit_policy", json_attribute(&deposit_policy)?));
+        config.deposit_policy = deposit_policy;
     }
-    CONFIG.save(deps.storage, &new_config)?;
 
-    Ok(Response::default())
+    validate_config(deps.as_ref(), &config)?;
+    CONFIG.save(deps.storage, &config)?;
+    Ok(Response::default()
+        .add_attribute("action", "update_config")
+        .add_attributes(changes.clone())
+        .add_event(Event::new("config-updated").add_attributes(changes)))
+}
+
+fn validate_config(deps: Deps, config: &Config) -> Result<(), ContractError> {
+    deps.api
+        .addr_validate(&config.aust_contract)
+        .map_err(|_| invalid_config("aust_contract"))?;
+    deps.api
+        .addr_validate(&config.anchor_smart_contract)
+        .map_err(|_| invalid_config("anchor_smart_contract"))?;
+    for depositor in &config.deposit_policy.allowlist {
+        deps.api
+            .addr_validate(depositor)
+            .map_err(|_| invalid_config("deposit_policy"))?;
+    }
+    Ok(())
 }
 
 pub fn try_anchor_withdraw(
@@ -66,10 +134,8 @@ pub fn try_anchor_withdraw(
     info: MessageInfo,
     amount: Uint128,
 ) -> Result<Response, ContractError> {
+    assert_role(deps.storage, &info.sender, Role::TreasuryOperator)?;
     let config = CONFIG.load(deps.storage)?;
-    if info.sender != config.admin {
-        return Err(Unauthorized {}.build());
-    }
     // let balance = query_token_balance(deps.as_ref(), Addr::unchecked(config.clone().aust_contract), _env.clone().contract.address )?;
 
     let msg = Cw20ExecuteMsg::Send {
@@ -85,7 +151,12 @@ pub fn try_anchor_withdraw(
 
     Ok(Response::default()
         .add_submessage(exec)
-        .add_attribute("action", "anchor_withdraw"))
+        .add_attribute("action", "anchor_withdraw")
+        .add_event(
+            Event::new("anchor-withdraw")
+                .add_attribute("operator", info.sender)
+                .add_attribute("aust_amount", amount),
+        ))
 }
 
 pub fn try_withdraw_fund(
@@ -93,16 +164,24 @@ pub fn try_withdraw_fund(
     _env: Env,
     info: MessageInfo,
 ) -> Result<Response, ContractError> {
-    let config_state = CONFIG.load(deps.storage)?;
-    if info.sender != config_state.admin {
-        return Err(Unauthorized {}.build());
-    }
-    let balance = deps.querier.query_balance(&_env.contract.address, "uusd")?;
+    // funds go to the sender, so this stays with the admin
+    assert_admin(deps.storage, &info.sender)?;
+    let withdrawn = withdraw_payout(deps.as_ref(), &_env, info.sender.to_string())?;
 
-    Ok(Response::new().add_message(transfer_funds(
-        &info.sender,
-        vec![deduct_tax(deps.as_ref(), balance)?],
-    )))
+    Ok(Response::new()
+        .add_attribute("action", "withdraw_fund")
+        .add_event(
+            Event::new("funds-withdrawn")
+                .add_attribute("recipient", withdrawn.recipient)
+                .add_attribute("amount", withdrawn.net.amount)
+                .add_attribute("denom", withdrawn.net.denom.clone()),
+        )
+        .add_message(transfer_funds(&info.sender, vec![withdrawn.net])))
+}
+
+fn withdraw_payout(deps: Deps, env: &Env, recipient: String) -> StdResult<Payout> {
+    let balance = deps.querier.query_balance(&env.contract.address, "uusd")?;
+    payout(deps, recipient, balance)
 }
 
 fn acnchor_deposit(contract_addr: String, coins: Vec<Coin>) -> Result<SubMsg, ContractError> {
@@ -119,31 +198,54 @@ pub fn try_deposit(deps: DepsMut, _env: Env, info: MessageInfo) -> Result<Respon
     let sent_funds = info.funds.clone();
     let config = CONFIG.load(deps.storage)?;
 
+    if sent_funds.is_empty() {
+        return Err(ContractError::NoFundsSent {});
+    }
+    if &sent_funds[0].denom != "uusd" {
+        return Err(ContractError::WrongDenom {
+            expected: "uusd".to_string(),
+            received: sent_funds[0].denom.clone(),
+        });
+    }
+    assert_depositor(&config.deposit_policy, &info.sender, sent_funds[0].amount)?;
+
+    let deposit = deduct_tax(
+        deps.as_ref(),
+        deduct_tax(
+            deps.as_ref(),
+            Coin {
+                denom: "uusd".to_string(),
+                amount: sent_funds[0].amount,
+            },
+        )?,
+    )?;
+
     Ok(Response::new()
         .add_submessage(acnchor_deposit(
             config.anchor_smart_contract.clone().to_string(),
-            vec![deduct_tax(
-                deps.as_ref(),
-                deduct_tax(
-                    deps.as_ref(),
-                    Coin {
-                        denom: "uusd".to_string(),
-                        amount: sent_funds[0].amount,
-                    },
-                )?,
-            )?],
+            vec![deposit.clone()],
         )?)
-        .add_attribute("action", "treasury_deposit"))
+        .add_attribute("action", "treasury_deposit")
+        .add_event(
+            Event::new("treasury-deposit")
+                .add_attribute("sender", info.sender)
+                .add_attribute("amount", sent_funds[0].amount)
+                .add_attribute("denom", sent_funds[0].denom.clone())
+                .add_attribute("anchor_amount", deposit.amount),
+        ))
 }
 
 #[cfg_attr(not(feature = "library"), entry_point)]
-pub fn migrate(_deps: DepsMut, _env: Env, _msg: MigrateMsg) -> Result<Response, ContractError> {
-    Ok(Response::default())
+pub fn migrate(deps: DepsMut, _env: Env, _msg: MigrateMsg) -> Result<Response, ContractError> {
+    migrate_store(deps)
 }
 
 pub fn query(deps: Deps, env: Env, msg: QueryMsg) -> StdResult<QueryResponse> {
     match msg {
         QueryMsg::Config {} => get_config(deps, env),
+        QueryMsg::Ownership {} => get_ownership(deps, env),
+        QueryMsg::RoleMembers { role } => get_role_members(deps, env, role),
+        QueryMsg::SimulateWithdrawFund {} => get_simulate_withdraw_fund(deps, env),
     }
 }
 
@@ -152,3 +254,12 @@ fn get_config(deps: Deps, _env: Env) -> StdResult<QueryResponse> {
     let rsp = ConfigResponse { config: state };
     to_binary(&rsp)
 }
+
+/// Withdrawals go to the admin, nothing once it has been renounced
+fn get_simulate_withdraw_fund(deps: Deps, env: Env) -> StdResult<QueryResponse> {
+    let payouts = match OWNERSHIP.load(deps.storage)?.admin {
+        Some(admin) => vec![withdraw_payout(deps, &env, admin)?],
+        None => vec![],
+    };
+    to_binary(&SimulatePayoutsResponse { payouts })
+}
@@ -1,33 +1,18 @@
 use cosmwasm_std::StdError;
-use snafu::Snafu;
+use delorean_common::errors::CommonError;
+use thiserror::Error;
 
-#[derive(Snafu, Debug)]
-#[snafu(visibility = "pub(crate)")]
+#[derive(Error, Debug)]
 pub enum ContractError {
-    /// this is needed so we can use `bucket.load(...)?` and have it auto-converted to the custom error
-    #[snafu(display("StdError: {}", original))]
-    Std { original: StdError },
-    #[snafu(display("Unauthorized"))]
-    Unauthorized { backtrace: Option<snafu::Backtrace> },
+    #[error("{0}")]
+    Std(#[from] StdError),
 
-    #[snafu(display("EmptyBalance"))]
-    EmptyBalance {},
+    #[error("{0}")]
+    Common(#[from] CommonError),
 
-    #[snafu(display("InsufficientFund"))]
-    InsufficientFund {},
+    #[error("NoFundsSent")]
+    NoFundsSent {},
 
-    #[snafu(display("MintLimitReached"))]
-    MintLimitReached {},
-
-    #[snafu(display("NotFound"))]
-    NotFound {},
-
-    #[snafu(display("PriceMismatch"))]
-    PriceMismatch {},
-}
-
-impl From<StdError> for ContractError {
-    fn from(original: StdError) -> Self {
-        Std { original }.build()
-    }
+    #[error("WrongDenom: expected {expected}, received {received}")]
+    WrongDenom { expected: String, received: String },
 }
@@ -0,0 +1,53 @@
+use crate::errors::ContractError;
+use crate::ownership::{init_ownership, Role};
+use crate::state::{Config, DepositPolicy, CONFIG};
+use cosmwasm_std::{DepsMut, Response, StdResult, Storage};
+use cw2::set_contract_version;
+use cw_storage_plus::Item;
+use delorean_common::versioning::{assert_upgrade, migrate_response};
+use semver::Version;
+use serde::{Deserialize, Serialize};
+
+pub const CONTRACT_NAME: &str = concat!("crates.io:", env!("CARGO_PKG_NAME"));
+pub const CONTRACT_VERSION: &str = env!("CARGO_PKG_VERSION");
+
+const LEGACY_CONFIG_KEY: &str = "config";
+
+/// Config layout from before the admin moved into ownership
+#[derive(Serialize, Deserialize)]
+struct LegacyConfig {
+    admin: String,
+    aust_contract: String,
+    anchor_smart_contract: String,
+}
+
+const LEGACY_CONFIG: Item<LegacyConfig> = Item::new(LEGACY_CONFIG_KEY);
+
+pub fn set_version(storage: &mut dyn Storage) -> StdResult<()> {
+    set_contract_version(storage, CONTRACT_NAME, CONTRACT_VERSION)
+}
+
+pub fn migrate_store(mut deps: DepsMut) -> Result<Response, ContractError> {
+    let stored = assert_upgrade(deps.storage, CONTRACT_NAME, CONTRACT_VERSION)?;
+    if stored < Version::new(0, 2, 0) {
+        migrate_from_legacy(deps.branch())?;
+    }
+
+    set_version(deps.storage)?;
+    Ok(migrate_response(CONTRACT_NAME, &stored, CONTRACT_VERSION))
+}
+
+/// Moves the admin into ownership
+fn migrate_from_legacy(mut deps: DepsMut) -> Result<(), ContractError> {
+    let legacy = LEGACY_CONFIG.load(deps.storage)?;
+    init_ownership::<Role>(deps.branch(), legacy.admin, vec![])?;
+    CONFIG.save(
+        deps.storage,
+        &Config {
+            aust_contract: legacy.aust_contract,
+            anchor_smart_contract: legacy.anchor_smart_contract,
+            deposit_policy: DepositPolicy::default(),
+        },
+    )?;
+    Ok(())
+}
@@ -1,22 +1,42 @@
-use crate::state::Config;
+use crate::ownership::{Role, RoleGrant};
+use crate::state::{Config, DepositPolicy};
 use cosmwasm_std::Uint128;
+pub use delorean_common::ownership::OwnershipResponse;
+pub use delorean_common::payout::Payout;
 use schemars::JsonSchema;
 use serde::{Deserialize, Serialize};
 
 #[derive(Serialize, Deserialize, Clone, Debug, PartialEq, JsonSchema)]
 pub struct InstantiateMsg {
     pub config: Config,
+    /// Defaults to the instantiating wallet
+    pub admin: Option<String>,
+    #[serde(default)]
+    pub roles: Vec<RoleGrant>,
 }
 
 #[derive(Serialize, Deserialize, Clone, Debug, PartialEq, JsonSchema)]
 #[serde(rename_all = "snake_case")]
 pub enum ExecuteMsg {
     Deposit {},
-    ConfigUpdate { config: Config },
+    UpdateConfig { config: ConfigUpdate },
+    ProposeNewAdmin { new_admin: String },
+    AcceptAdmin {},
+    RenounceAdmin {},
+    GrantRole { role: Role, address: String },
+    RevokeRole { role: Role, address: String },
     AnchorWithdraw { amount: Uint128 },
     WithdrawFund {},
 }
 
+/// Only the provided fields are changed
+#[derive(Serialize, Deserialize, Clone, Debug, PartialEq, JsonSchema, Default)]
+pub struct ConfigUpdate {
+    pub aust_contract: Option<String>,
+    pub anchor_smart_contract: Option<String>,
+    pub deposit_policy: Option<DepositPolicy>,
+}
+
 #[derive(Serialize, Deserialize, Clone, Debug, PartialEq, JsonSchema)]
 pub struct MigrateMsg {}
 
@@ -29,10 +49,20 @@ pub enum AnchorExecuteMsg {
 #[derive(Serialize, Deserialize, Clone, Debug, PartialEq, JsonSchema)]
 #[serde(rename_all = "snake_case")]
 pub enum QueryMsg {
+    Ownership {},
+    RoleMembers { role: Role },
     Config {},
+    SimulateWithdrawFund {},
 }
 
 #[derive(Serialize, Deserialize, Clone, Debug, PartialEq, JsonSchema)]
 pub struct ConfigResponse {
     pub config: Config,
 }
+
+pub type RoleMembersResponse = delorean_common::ownership::RoleMembersResponse<Role>;
+
+#[derive(Serialize, Deserialize, Clone, Debug, PartialEq, JsonSchema)]
+pub struct SimulatePayoutsResponse {
+    pub payouts: Vec<Payout>,
+}
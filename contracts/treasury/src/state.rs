@@ -1,4 +1,5 @@
 use cw_storage_plus::Item;
+pub use delorean_common::deposit::DepositPolicy;
 use schemars::JsonSchema;
 use serde::{Deserialize, Serialize};
 
@@ -6,9 +7,10 @@ const CONFIG_KEY: &str = "config";
 
 #[derive(Serialize, Deserialize, Clone, Debug, PartialEq, JsonSchema)]
 pub struct Config {
-    pub admin: String,
     pub aust_contract: String,
     pub anchor_smart_contract: String,
+    #[serde(default)]
+    pub deposit_policy: DepositPolicy,
 }
 
 pub const CONFIG: Item<Config> = Item::new(CONFIG_KEY);
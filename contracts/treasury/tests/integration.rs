@@ -1,14 +1,34 @@
-use cosmwasm_std::{coin, from_binary, ContractResult, Response};
+use cosmwasm_std::Uint128;
+use cosmwasm_std::{coin, from_binary, BankMsg, ContractResult, CosmosMsg, Response};
 use cosmwasm_vm::{
     testing::{execute, instantiate, migrate, mock_env, mock_info, mock_instance_options, query},
     Instance,
 };
-use delorean_treasury::msg::{ConfigResponse, ExecuteMsg, InstantiateMsg, MigrateMsg, QueryMsg};
-use delorean_treasury::state::Config;
+use delorean_treasury::msg::{
+    ConfigResponse, ConfigUpdate, ExecuteMsg, InstantiateMsg, MigrateMsg, OwnershipResponse,
+    QueryMsg, SimulatePayoutsResponse,
+};
+use delorean_treasury::ownership::{Role, RoleGrant};
+use delorean_treasury::state::{Config, DepositPolicy};
 
 static WASM: &[u8] =
     include_bytes!("../../../target/wasm32-unknown-unknown/release/delorean_treasury.wasm");
 
+/// Attributes of each event of type `ty`, in emission order
+fn events<'a>(rsp: &'a Response, ty: &str) -> Vec<Vec<(&'a str, &'a str)>> {
+    rsp.events
+        .iter()
+        .filter(|event| event.ty == ty)
+        .map(|event| {
+            event
+                .attributes
+                .iter()
+                .map(|attr| (attr.key.as_str(), attr.value.as_str()))
+                .collect()
+        })
+        .collect()
+}
+
 #[test]
 #[cfg(test)]
 fn delorean_treasury_test() {
@@ -19,13 +39,18 @@ fn delorean_treasury_test() {
     let user1_info = mock_info(&String::from("user1"), &[coin(10000u128, "uusd")].to_vec());
 
     let config = Config {
-        admin: admin.clone(),
         aust_contract: "aust_contrac".to_string(),
         anchor_smart_contract: "anchor_smart_contract".to_string(),
+        deposit_policy: DepositPolicy::default(),
     };
 
     let instatiate_msg = InstantiateMsg {
         config: config.clone(),
+        admin: None,
+        roles: vec![RoleGrant {
+            role: Role::TreasuryOperator,
+            address: String::from("operator"),
+        }],
     };
 
     let (instance_options, memory_limit) = mock_instance_options();
@@ -39,8 +64,11 @@ fn delorean_treasury_test() {
 
     assert_eq!(config_rsp.config, config);
 
-    let update_config_msg = ExecuteMsg::ConfigUpdate {
-        config: config.clone(),
+    let update_config_msg = ExecuteMsg::UpdateConfig {
+        config: ConfigUpdate {
+            aust_contract: Some(config.aust_contract.clone()),
+            ..ConfigUpdate::default()
+        },
     };
     let rsp: ContractResult<Response> =
         execute(&mut deps, mock_env(), user1_info.clone(), update_config_msg);
@@ -53,7 +81,162 @@ fn delorean_treasury_test() {
         ExecuteMsg::Deposit {},
     );
     assert_eq!(rsp.is_err(), false, "Treasury deposit should work");
+    assert_eq!(
+        events(&rsp.unwrap(), "treasury-deposit"),
+        vec![vec![
+            ("sender", "user1"),
+            ("amount", "10000"),
+            ("denom", "uusd"),
+            ("anchor_amount", "10000"),
+        ]]
+    );
+
+    let rsp: ContractResult<Response> = execute(
+        &mut deps,
+        mock_env(),
+        admin_info.clone(),
+        ExecuteMsg::UpdateConfig {
+            config: ConfigUpdate {
+                deposit_policy: Some(DepositPolicy {
+                    closed: true,
+                    ..DepositPolicy::default()
+                }),
+                ..ConfigUpdate::default()
+            },
+        },
+    );
+    assert_eq!(
+        events(&rsp.unwrap(), "config-updated"),
+        vec![vec![(
+            "deposit_policy",
+            r#"{"allowlist":[],"min_deposit":"0","closed":true}"#
+        )]]
+    );
+
+    let rsp: ContractResult<Response> = execute(
+        &mut deps,
+        mock_env(),
+        user1_info.clone(),
+        ExecuteMsg::Deposit {},
+    );
+    assert_eq!(
+        rsp.unwrap_err(),
+        "DepositsClosed: deposit from user1 rejected",
+        "Closed treasury should reject deposits"
+    );
+
+    let rsp = query(&mut deps, mock_env(), QueryMsg::Ownership {}).unwrap();
+    let rsp: OwnershipResponse = from_binary(&rsp).unwrap();
+    assert_eq!(
+        rsp.admin,
+        Some(admin.clone()),
+        "Instantiator should be admin"
+    );
+
+    let rsp: ContractResult<Response> = execute(
+        &mut deps,
+        mock_env(),
+        user1_info.clone(),
+        ExecuteMsg::AnchorWithdraw {
+            amount: Uint128::from(100u128),
+        },
+    );
+    assert_eq!(
+        rsp.unwrap_err(),
+        "Unauthorized: user1 is not treasury_operator"
+    );
+
+    let operator_info = mock_info(&String::from("operator"), &[]);
+    let rsp: ContractResult<Response> = execute(
+        &mut deps,
+        mock_env(),
+        operator_info.clone(),
+        ExecuteMsg::AnchorWithdraw {
+            amount: Uint128::from(100u128),
+        },
+    );
+    assert_eq!(
+        rsp.is_err(),
+        false,
+        "Treasury operator can redeem from anchor"
+    );
+    assert_eq!(
+        events(&rsp.unwrap(), "anchor-withdraw"),
+        vec![vec![("operator", "operator"), ("aust_amount", "100")]]
+    );
+
+    let rsp: ContractResult<Response> = execute(
+        &mut deps,
+        mock_env(),
+        operator_info,
+        ExecuteMsg::WithdrawFund {},
+    );
+    assert_eq!(
+        rsp.unwrap_err(),
+        "Unauthorized: operator is not admin",
+        "Withdraw fund stays with the admin"
+    );
 
     let rsp: ContractResult<Response> = migrate(&mut deps, mock_env(), MigrateMsg {});
     assert_eq!(rsp.is_err(), false, "Migrate should work");
 }
+
+#[test]
+fn delorean_treasury_simulate_withdraw_test() {
+    let backend = mock_tax::mock_dependencies::mock_dependencies_with_custom_querier(&[coin(
+        500000000u128,
+        "uusd",
+    )]);
+    let admin_info = mock_info(&String::from("admin"), &[]);
+    let config = Config {
+        aust_contract: "aust_contrac".to_string(),
+        anchor_smart_contract: "anchor_smart_contract".to_string(),
+        deposit_policy: DepositPolicy::default(),
+    };
+
+    let (instance_options, memory_limit) = mock_instance_options();
+    let mut deps = Instance::from_code(WASM, backend, instance_options, memory_limit).unwrap();
+    let rsp: ContractResult<Response> = instantiate(
+        &mut deps,
+        mock_env(),
+        admin_info.clone(),
+        InstantiateMsg {
+            config,
+            admin: None,
+            roles: vec![],
+        },
+    );
+    assert_eq!(rsp.is_err(), false);
+
+    let rsp = query(&mut deps, mock_env(), QueryMsg::SimulateWithdrawFund {}).unwrap();
+    let rsp: SimulatePayoutsResponse = from_binary(&rsp).unwrap();
+    assert_eq!(rsp.payouts.len(), 1);
+    let payout = rsp.payouts[0].clone();
+    assert_eq!(payout.recipient, "admin");
+    assert_eq!(payout.gross, coin(500000000u128, "uusd"));
+    assert_eq!(payout.gross.amount, payout.tax.amount + payout.net.amount);
+
+    let rsp: ContractResult<Response> = execute(
+        &mut deps,
+        mock_env(),
+        admin_info,
+        ExecuteMsg::WithdrawFund {},
+    );
+    let rsp = rsp.unwrap();
+    assert_eq!(
+        events(&rsp, "funds-withdrawn"),
+        vec![vec![
+            ("recipient", "admin"),
+            ("amount", "500000000"),
+            ("denom", "uusd"),
+        ]]
+    );
+    assert_eq!(
+        rsp.messages[0].msg,
+        CosmosMsg::Bank(BankMsg::Send {
+            to_address: payout.recipient,
+            amount: vec![payout.net],
+        }),
+        "WithdrawFund sends what the simulation showed"
+    );
+}
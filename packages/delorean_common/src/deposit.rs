@@ -0,0 +1,38 @@
+use crate::errors::CommonError;
+use cosmwasm_std::{Addr, Uint128};
+use schemars::JsonSchema;
+use serde::{Deserialize, Serialize};
+
+/// Who may call Deposit, the default lets anyone deposit any amount
+#[derive(Serialize, Deserialize, Clone, Debug, PartialEq, JsonSchema, Default)]
+pub struct DepositPolicy {
+    /// Anyone may deposit while this is empty
+    pub allowlist: Vec<String>,
+    pub min_deposit: Uint128,
+    pub closed: bool,
+}
+
+pub fn assert_depositor(
+    policy: &DepositPolicy,
+    sender: &Addr,
+    amount: Uint128,
+) -> Result<(), CommonError> {
+    if policy.closed {
+        return Err(CommonError::DepositsClosed {
+            sender: sender.to_string(),
+        });
+    }
+    if !policy.allowlist.is_empty() && !policy.allowlist.iter().any(|a| a == sender.as_str()) {
+        return Err(CommonError::DepositorNotAllowed {
+            sender: sender.to_string(),
+        });
+    }
+    if amount < policy.min_deposit {
+        return Err(CommonError::DepositTooSmall {
+            sender: sender.to_string(),
+            minimum: policy.min_deposit,
+            received: amount,
+        });
+    }
+    Ok(())
+}
@@ -0,0 +1,44 @@
+use cosmwasm_std::{StdError, Uint128};
+use thiserror::Error;
+
+/// Errors raised by the shared modules, each contract wraps them in its own
+/// `ContractError`
+#[derive(Error, Debug)]
+pub enum CommonError {
+    #[error("{0}")]
+    Std(#[from] StdError),
+
+    #[error("InvalidConfig: {field}")]
+    InvalidConfig { field: String },
+
+    #[error("Unauthorized: {sender} is not {required}")]
+    Unauthorized { sender: String, required: String },
+
+    #[error("WrongContract: expected {expected}, found {found}")]
+    WrongContract { expected: String, found: String },
+
+    #[error("CannotDowngrade: stored {stored}, current {current}")]
+    CannotDowngrade { stored: String, current: String },
+
+    #[error("InvalidVersion: {version}")]
+    InvalidVersion { version: String },
+
+    #[error("DepositsClosed: deposit from {sender} rejected")]
+    DepositsClosed { sender: String },
+
+    #[error("DepositorNotAllowed: {sender}")]
+    DepositorNotAllowed { sender: String },
+
+    #[error("DepositTooSmall: {sender} sent {received}, minimum {minimum}")]
+    DepositTooSmall {
+        sender: String,
+        minimum: Uint128,
+        received: Uint128,
+    },
+}
+
+pub fn invalid_config(field: &str) -> CommonError {
+    CommonError::InvalidConfig {
+        field: field.to_string(),
+    }
+}
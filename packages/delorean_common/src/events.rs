@@ -0,0 +1,22 @@
+use cosmwasm_std::{to_vec, Event, StdResult};
+use serde::Serialize;
+
+/// Structured values go into attributes as their json message form
+pub fn json_attribute<T: Serialize>(value: &T) -> StdResult<String> {
+    Ok(String::from_utf8(to_vec(value)?)?)
+}
+
+pub fn instantiate_event<R: Serialize, C: Serialize>(
+    contract: &str,
+    version: &str,
+    admin: &str,
+    roles: &R,
+    config: &C,
+) -> StdResult<Event> {
+    Ok(Event::new("contract-instantiated")
+        .add_attribute("contract", contract)
+        .add_attribute("version", version)
+        .add_attribute("admin", admin)
+        .add_attribute("roles", json_attribute(roles)?)
+        .add_attribute("config", json_attribute(config)?))
+}
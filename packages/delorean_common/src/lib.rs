@@ -0,0 +1,6 @@
+pub mod deposit;
+pub mod errors;
+pub mod events;
+pub mod ownership;
+pub mod payout;
+pub mod versioning;
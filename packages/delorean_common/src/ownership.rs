@@ -0,0 +1,234 @@
+use crate::errors::CommonError;
+use cosmwasm_std::{
+    to_binary, Addr, Deps, DepsMut, Env, Event, MessageInfo, Order, QueryResponse, Response,
+    StdResult, Storage,
+};
+use cw_storage_plus::{Item, Map};
+use schemars::JsonSchema;
+use serde::{Deserialize, Serialize};
+
+const OWNERSHIP_KEY: &str = "ownership";
+const ROLES_KEY: &str = "roles";
+
+#[derive(Serialize, Deserialize, Clone, Debug, PartialEq, JsonSchema)]
+pub struct Ownership {
+    /// None once the admin has been renounced
+    pub admin: Option<String>,
+    pub pending_admin: Option<String>,
+}
+
+/// Implemented by each contract's own `Role` enum, the name is the storage
+/// key and the `required` part of an `Unauthorized` error
+pub trait RoleName {
+    fn as_str(&self) -> &str;
+}
+
+#[derive(Serialize, Deserialize, Clone, Debug, PartialEq, JsonSchema)]
+pub struct RoleGrant<R> {
+    pub role: R,
+    pub address: String,
+}
+
+#[derive(Serialize, Deserialize, Clone, Debug, PartialEq, JsonSchema)]
+pub struct OwnershipResponse {
+    pub admin: Option<String>,
+    pub pending_admin: Option<String>,
+}
+
+#[derive(Serialize, Deserialize, Clone, Debug, PartialEq, JsonSchema)]
+pub struct RoleMembersResponse<R> {
+    pub role: R,
+    pub members: Vec<String>,
+}
+
+pub const OWNERSHIP: Item<Ownership> = Item::new(OWNERSHIP_KEY);
+// (role, address) -> granted
+pub const ROLES: Map<(&str, &str), bool> = Map::new(ROLES_KEY);
+
+pub fn init_ownership<R: RoleName>(
+    deps: DepsMut,
+    admin: String,
+    roles: Vec<RoleGrant<R>>,
+) -> Result<(), CommonError> {
+    let admin = deps.api.addr_validate(&admin)?;
+    OWNERSHIP.save(
+        deps.storage,
+        &Ownership {
+            admin: Some(admin.to_string()),
+            pending_admin: None,
+        },
+    )?;
+    for grant in roles {
+        let address = deps.api.addr_validate(&grant.address)?;
+        ROLES.save(deps.storage, (grant.role.as_str(), address.as_str()), &true)?;
+    }
+    Ok(())
+}
+
+pub fn is_admin(storage: &dyn Storage, sender: &Addr) -> StdResult<bool> {
+    let ownership = OWNERSHIP.load(storage)?;
+    Ok(ownership.admin.as_deref() == Some(sender.as_str()))
+}
+
+pub fn assert_admin(storage: &dyn Storage, sender: &Addr) -> Result<(), CommonError> {
+    if !is_admin(storage, sender)? {
+        return Err(unauthorized(sender, "admin"));
+    }
+    Ok(())
+}
+
+/// The admin holds every role
+pub fn has_role<R: RoleName>(storage: &dyn Storage, sender: &Addr, role: R) -> StdResult<bool> {
+    if is_admin(storage, sender)? {
+        return Ok(true);
+    }
+    Ok(ROLES
+        .may_load(storage, (role.as_str(), sender.as_str()))?
+        .unwrap_or(false))
+}
+
+pub fn assert_role<R: RoleName>(
+    storage: &dyn Storage,
+    sender: &Addr,
+    role: R,
+) -> Result<(), CommonError> {
+    let required = role.as_str().to_string();
+    if !has_role(storage, sender, role)? {
+        return Err(unauthorized(sender, &required));
+    }
+    Ok(())
+}
+
+pub fn unauthorized(sender: &Addr, required: &str) -> CommonError {
+    CommonError::Unauthorized {
+        sender: sender.to_string(),
+        required: required.to_string(),
+    }
+}
+
+pub fn try_propose_new_admin(
+    deps: DepsMut,
+    _env: Env,
+    info: MessageInfo,
+    new_admin: String,
+) -> Result<Response, CommonError> {
+    assert_admin(deps.storage, &info.sender)?;
+    let new_admin = deps.api.addr_validate(&new_admin)?;
+    OWNERSHIP.update(deps.storage, |mut ownership| -> StdResult<_> {
+        ownership.pending_admin = Some(new_admin.to_string());
+        Ok(ownership)
+    })?;
+
+    Ok(Response::default()
+        .add_attribute("action", "propose_new_admin")
+        .add_attribute("pending_admin", new_admin.clone())
+        .add_event(
+            Event::new("admin-proposed")
+                .add_attribute("admin", info.sender)
+                .add_attribute("pending_admin", new_admin),
+        ))
+}
+
+pub fn try_accept_admin(
+    deps: DepsMut,
+    _env: Env,
+    info: MessageInfo,
+) -> Result<Response, CommonError> {
+    let mut ownership = OWNERSHIP.load(deps.storage)?;
+    if ownership.pending_admin.as_deref() != Some(info.sender.as_str()) {
+        return Err(unauthorized(&info.sender, "pending_admin"));
+    }
+    ownership.admin = ownership.pending_admin.take();
+    OWNERSHIP.save(deps.storage, &ownership)?;
+
+    Ok(Response::default()
+        .add_attribute("action", "accept_admin")
+        .add_attribute("admin", info.sender.clone())
+        .add_event(Event::new("admin-accepted").add_attribute("admin", info.sender)))
+}
+
+pub fn try_renounce_admin(
+    deps: DepsMut,
+    _env: Env,
+    info: MessageInfo,
+) -> Result<Response, CommonError> {
+    assert_admin(deps.storage, &info.sender)?;
+    let ownership = Ownership {
+        admin: None,
+        pending_admin: None,
+    };
+    OWNERSHIP.save(deps.storage, &ownership)?;
+
+    Ok(Response::default()
+        .add_attribute("action", "renounce_admin")
+        .add_event(Event::new("admin-renounced").add_attribute("admin", info.sender)))
+}
+
+pub fn try_grant_role<R: RoleName>(
+    deps: DepsMut,
+    _env: Env,
+    info: MessageInfo,
+    role: R,
+    address: String,
+) -> Result<Response, CommonError> {
+    assert_admin(deps.storage, &info.sender)?;
+    let address = deps.api.addr_validate(&address)?;
+    ROLES.save(deps.storage, (role.as_str(), address.as_str()), &true)?;
+
+    Ok(Response::default()
+        .add_attribute("action", "grant_role")
+        .add_attribute("role", role.as_str())
+        .add_attribute("address", address.clone())
+        .add_event(
+            Event::new("role-granted")
+                .add_attribute("role", role.as_str())
+                .add_attribute("address", address),
+        ))
+}
+
+pub fn try_revoke_role<R: RoleName>(
+    deps: DepsMut,
+    _env: Env,
+    info: MessageInfo,
+    role: R,
+    address: String,
+) -> Result<Response, CommonError> {
+    assert_admin(deps.storage, &info.sender)?;
+    ROLES.remove(deps.storage, (role.as_str(), address.as_str()));
+
+    Ok(Response::default()
+        .add_attribute("action", "revoke_role")
+        .add_attribute("role", role.as_str())
+        .add_attribute("address", address.clone())
+        .add_event(
+            Event::new("role-revoked")
+                .add_attribute("role", role.as_str())
+                .add_attribute("address", address),
+        ))
+}
+
+pub fn get_ownership(deps: Deps, _env: Env) -> StdResult<QueryResponse> {
+    let ownership = OWNERSHIP.load(deps.storage)?;
+    let rsp = OwnershipResponse {
+        admin: ownership.admin,
+        pending_admin: ownership.pending_admin,
+    };
+    to_binary(&rsp)
+}
+
+pub fn get_role_members<R: RoleName + Serialize>(
+    deps: Deps,
+    _env: Env,
+    role: R,
+) -> StdResult<QueryResponse> {
+    let members: StdResult<Vec<String>> = ROLES
+        .prefix(role.as_str())
+        .keys(deps.storage, None, None, Order::Ascending)
+        .map(|key| Ok(String::from_utf8(key)?))
+        .collect();
+    let rsp = RoleMembersResponse {
+        role,
+        members: members?,
+    };
+    to_binary(&rsp)
+}
@@ -0,0 +1,25 @@
+use cosmwasm_std::{coin, Coin, Deps, StdResult};
+use moneymarket::querier::deduct_tax;
+use schemars::JsonSchema;
+use serde::{Deserialize, Serialize};
+
+/// A transfer as the execute handler would send it, tax is what
+/// `deduct_tax` keeps back from the gross amount
+#[derive(Serialize, Deserialize, Clone, Debug, PartialEq, JsonSchema)]
+pub struct Payout {
+    pub recipient: String,
+    pub gross: Coin,
+    pub tax: Coin,
+    pub net: Coin,
+}
+
+pub fn payout(deps: Deps, recipient: String, gross: Coin) -> StdResult<Payout> {
+    let net = deduct_tax(deps, gross.clone())?;
+    let tax = coin((gross.amount - net.amount).u128(), gross.denom.clone());
+    Ok(Payout {
+        recipient,
+        gross,
+        tax,
+        net,
+    })
+}
@@ -0,0 +1,55 @@
+use crate::errors::CommonError;
+use cosmwasm_std::{Event, Response, Storage};
+use cw2::CONTRACT;
+use semver::Version;
+
+/// Stores written before versioning have no cw2 entry
+pub const LEGACY_VERSION: &str = "0.1.0";
+
+/// The version a migration starts from, after checking the store belongs to
+/// `name` and is not newer than `current`
+pub fn assert_upgrade(
+    storage: &dyn Storage,
+    name: &str,
+    current: &str,
+) -> Result<Version, CommonError> {
+    let stored_version = match CONTRACT.may_load(storage)? {
+        Some(stored) => {
+            if stored.contract != name {
+                return Err(CommonError::WrongContract {
+                    expected: name.to_string(),
+                    found: stored.contract,
+                });
+            }
+            stored.version
+        }
+        None => LEGACY_VERSION.to_string(),
+    };
+    let stored = parse_version(&stored_version)?;
+    if stored > parse_version(current)? {
+        return Err(CommonError::CannotDowngrade {
+            stored: stored_version,
+            current: current.to_string(),
+        });
+    }
+    Ok(stored)
+}
+
+pub fn parse_version(version: &str) -> Result<Version, CommonError> {
+    Version::parse(version).map_err(|_| CommonError::InvalidVersion {
+        version: version.to_string(),
+    })
+}
+
+pub fn migrate_response(name: &str, from: &Version, to: &str) -> Response {
+    Response::default()
+        .add_attribute("action", "migrate")
+        .add_attribute("from_version", from.to_string())
+        .add_attribute("to_version", to)
+        .add_event(
+            Event::new("contract-migrated")
+                .add_attribute("contract", name)
+                .add_attribute("from_version", from.to_string())
+                .add_attribute("to_version", to),
+        )
+}